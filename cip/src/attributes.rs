@@ -0,0 +1,382 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use nom::{multi::count, number::complete::le_u16};
+
+use crate::{cip::{CipDataType, CipService}, objects::message_router::MessageRouterResponseStatusCodes, router::{CipObject, ObjectPath, ServiceError, ServiceResult}};
+
+/// Highest class attribute generated by [`AttributeObject`].
+const MAX_CLASS_ATTRIBUTE: u16 = 7;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Get,
+    Set,
+    /// Can be set until the first successful write.
+    SettableOnce
+}
+
+/// An attribute with its encoded value.
+#[derive(Clone, Debug)]
+pub struct Attribute {
+    pub id: u16,
+    pub data_type: CipDataType,
+    pub access: Access,
+    /// The value is kept across power cycles.
+    pub nonvolatile: bool,
+    /// Reported in class attribute 4.
+    pub optional: bool,
+    value: Vec<u8>,
+    written: bool
+}
+
+impl Attribute {
+    /// A read-only attribute.
+    pub fn get(id: u16, data_type: CipDataType, value: Vec<u8>) -> Self {
+        Self { id, data_type, access: Access::Get, nonvolatile: false, optional: false, value, written: false }
+    }
+
+    pub fn settable(mut self) -> Self {
+        self.access = Access::Set;
+        return self;
+    }
+
+    pub fn settable_once(mut self) -> Self {
+        self.access = Access::SettableOnce;
+        return self;
+    }
+
+    pub fn nonvolatile(mut self) -> Self {
+        self.nonvolatile = true;
+        return self;
+    }
+
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        return self;
+    }
+
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Whether a Set service may change the attribute now.
+    pub fn is_settable(&self) -> bool {
+        match self.access {
+            Access::Get => false,
+            Access::Set => true,
+            Access::SettableOnce => !self.written
+        }
+    }
+}
+
+/// Length of the `data_type` value at the start of `data`, or `None` if the type does not tell.
+fn value_length(data_type: CipDataType, data: &[u8]) -> Option<usize> {
+    match data_type {
+        CipDataType::ShortString => Some(data.first().map(|length| *length as usize + 1).unwrap_or(1)),
+        CipDataType::String => Some(data.get(..2).map(|length| u16::from_le_bytes([length[0], length[1]]) as usize + 2).unwrap_or(2)),
+        _ => data_type.size()
+    }
+}
+
+/// Checks that `data` is a complete value of `data_type`.
+fn check_length(data_type: CipDataType, data: &[u8]) -> Result<(), ServiceError> {
+    // Structures and arrays are checked by the object that owns them.
+    let expected = match value_length(data_type, data) {
+        Some(expected) => expected,
+        None => return Ok(())
+    };
+
+    if data.len() < expected {
+        return Err(MessageRouterResponseStatusCodes::NotEnoughData.into());
+    }
+    if data.len() > expected {
+        return Err(MessageRouterResponseStatusCodes::TooMuchData.into());
+    }
+
+    return Ok(());
+}
+
+/// The attributes of one instance, ordered by ID.
+#[derive(Clone, Debug, Default)]
+pub struct AttributeTable {
+    attributes: BTreeMap<u16, Attribute>
+}
+
+impl AttributeTable {
+    pub fn new() -> Self {
+        Self { attributes: BTreeMap::new() }
+    }
+
+    /// Adds `attribute`, replacing an attribute with the same ID.
+    pub fn with(mut self, attribute: Attribute) -> Self {
+        self.attributes.insert(attribute.id, attribute);
+        return self;
+    }
+
+    pub fn attribute(&self, id: u16) -> Option<&Attribute> {
+        self.attributes.get(&id)
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = &Attribute> {
+        self.attributes.values()
+    }
+
+    pub fn value(&self, id: u16) -> Option<&[u8]> {
+        self.attributes.get(&id).map(|attribute| attribute.value())
+    }
+
+    /// Replaces a value from the device side, without access checks.
+    pub fn set_value(&mut self, id: u16, value: Vec<u8>) {
+        if let Some(attribute) = self.attributes.get_mut(&id) {
+            attribute.value = value;
+        }
+    }
+
+    /// Values that have to be stored in nonvolatile memory.
+    pub fn nonvolatile_values(&self) -> Vec<(u16, &[u8])> {
+        self.attributes.values().filter(|attribute| attribute.nonvolatile).map(|attribute| (attribute.id, attribute.value())).collect()
+    }
+
+    pub fn max_id(&self) -> u16 {
+        self.attributes.keys().next_back().copied().unwrap_or(0)
+    }
+
+    pub fn get_attribute_single(&self, id: u16) -> ServiceResult {
+        return match self.attributes.get(&id) {
+            Some(attribute) => Ok(attribute.value.clone()),
+            None => Err(MessageRouterResponseStatusCodes::AttributeNotSupported.into())
+        };
+    }
+
+    pub fn set_attribute_single(&mut self, id: u16, data: &[u8]) -> Result<(), ServiceError> {
+        let attribute = self.attributes.get_mut(&id).ok_or(ServiceError::from(MessageRouterResponseStatusCodes::AttributeNotSupported))?;
+
+        if attribute.access == Access::SettableOnce && attribute.written {
+            return Err(MessageRouterResponseStatusCodes::WriteOnceValue.into());
+        }
+        if !attribute.is_settable() {
+            return Err(MessageRouterResponseStatusCodes::AttributeNotSettable.into());
+        }
+
+        check_length(attribute.data_type, data)?;
+        attribute.value = data.to_vec();
+        attribute.written = true;
+        return Ok(());
+    }
+
+    /// Values of every attribute in ID order.
+    pub fn get_attributes_all(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        for attribute in self.attributes.values() {
+            vec.extend_from_slice(&attribute.value);
+        }
+
+        return vec;
+    }
+
+    /// Answers a Get_Attribute_List request: a count followed by attribute IDs.
+    pub fn get_attribute_list(&self, data: &[u8]) -> ServiceResult {
+        let parsed: nom::IResult<&[u8], Vec<u16>> = le_u16(data).and_then(|(input, length)| count(le_u16, length as usize)(input));
+        let ids = match parsed {
+            Ok((_, ids)) => ids,
+            Err(_) => return Err(MessageRouterResponseStatusCodes::NotEnoughData.into())
+        };
+
+        let mut vec = Vec::new();
+        let mut failed = false;
+        vec.extend_from_slice(&(ids.len() as u16).to_le_bytes());
+
+        for id in ids {
+            vec.extend_from_slice(&id.to_le_bytes());
+            match self.attributes.get(&id) {
+                Some(attribute) => {
+                    vec.extend_from_slice(&0u16.to_le_bytes());
+                    vec.extend_from_slice(&attribute.value);
+                }
+                None => {
+                    vec.extend_from_slice(&(MessageRouterResponseStatusCodes::AttributeNotSupported as u16).to_le_bytes());
+                    failed = true;
+                }
+            }
+        }
+
+        if failed {
+            return Err(ServiceError { general_status: MessageRouterResponseStatusCodes::AttributeListError as u8, additional_status: Vec::new(), data: vec });
+        }
+
+        return Ok(vec);
+    }
+
+    /// Set_Attribute_List: writes each attribute in turn and replies with its status.
+    /// Values of structures and arrays are taken to keep their current length.
+    pub fn set_attribute_list(&mut self, data: &[u8]) -> ServiceResult {
+        let (mut input, length) = match le_u16::<_, nom::error::Error<&[u8]>>(data) {
+            Ok(parsed) => parsed,
+            Err(_) => return Err(MessageRouterResponseStatusCodes::NotEnoughData.into())
+        };
+
+        let mut vec = Vec::new();
+        let mut failed = false;
+        vec.extend_from_slice(&length.to_le_bytes());
+
+        for _ in 0..length {
+            let (rest, id) = match le_u16::<_, nom::error::Error<&[u8]>>(input) {
+                Ok(parsed) => parsed,
+                Err(_) => return Err(MessageRouterResponseStatusCodes::NotEnoughData.into())
+            };
+            vec.extend_from_slice(&id.to_le_bytes());
+
+            // Without the attribute the value length is unknown, so the rest cannot be parsed.
+            let attribute = match self.attributes.get(&id) {
+                Some(attribute) => attribute,
+                None => {
+                    vec.extend_from_slice(&(MessageRouterResponseStatusCodes::AttributeNotSupported as u16).to_le_bytes());
+                    return Err(ServiceError { general_status: MessageRouterResponseStatusCodes::AttributeListError as u8, additional_status: Vec::new(), data: vec });
+                }
+            };
+
+            let length = value_length(attribute.data_type, rest).unwrap_or(attribute.value.len());
+            if rest.len() < length {
+                return Err(MessageRouterResponseStatusCodes::NotEnoughData.into());
+            }
+            let (value, rest) = rest.split_at(length);
+            input = rest;
+
+            let status = match self.set_attribute_single(id, value) {
+                Ok(()) => 0,
+                Err(error) => {
+                    failed = true;
+                    error.general_status as u16
+                }
+            };
+            vec.extend_from_slice(&status.to_le_bytes());
+        }
+
+        if failed {
+            return Err(ServiceError { general_status: MessageRouterResponseStatusCodes::AttributeListError as u8, additional_status: Vec::new(), data: vec });
+        }
+
+        return Ok(vec);
+    }
+
+    /// Runs one of the standard attribute services.
+    pub fn handle(&mut self, service: u8, path: &ObjectPath, data: &[u8]) -> ServiceResult {
+        let attribute = match path.attribute.map(u16::try_from) {
+            Some(Ok(attribute)) => Some(attribute),
+            Some(Err(_)) => return Err(MessageRouterResponseStatusCodes::AttributeNotSupported.into()),
+            None => None
+        };
+
+        if service == CipService::GetAttributesAll as u8 {
+            return Ok(self.get_attributes_all());
+        }
+        if service == CipService::GetAttributeList as u8 {
+            return self.get_attribute_list(data);
+        }
+        if service == CipService::SetAttributesList as u8 {
+            return self.set_attribute_list(data);
+        }
+
+        let is_single = service == CipService::GetAttributeSingle as u8 || service == CipService::SetAttributeSingle as u8;
+        if !is_single {
+            return Err(MessageRouterResponseStatusCodes::ServiceNotSupported.into());
+        }
+
+        let attribute = attribute.ok_or(ServiceError::from(MessageRouterResponseStatusCodes::PathSegmentError))?;
+        if service == CipService::GetAttributeSingle as u8 {
+            return self.get_attribute_single(attribute);
+        }
+
+        self.set_attribute_single(attribute, data)?;
+        return Ok(Vec::new());
+    }
+}
+
+/// A class whose instances are described by attribute tables. The standard
+/// attribute services and class attributes 1 to 7 are generated from the tables.
+///
+/// Objects that need extra services or side effects wrap an `AttributeObject`
+/// and forward everything they don't handle themselves.
+pub struct AttributeObject {
+    pub class_id: u16,
+    pub revision: u16,
+    /// Services the class supports beyond the required ones, for class attribute 5.
+    pub optional_services: Vec<u16>,
+    instances: BTreeMap<u32, AttributeTable>
+}
+
+impl AttributeObject {
+    pub fn new(class_id: u16, revision: u16) -> Self {
+        Self { class_id, revision, optional_services: Vec::new(), instances: BTreeMap::new() }
+    }
+
+    pub fn with_instance(mut self, id: u32, table: AttributeTable) -> Self {
+        self.instances.insert(id, table);
+        return self;
+    }
+
+    pub fn instance(&self, id: u32) -> Option<&AttributeTable> {
+        self.instances.get(&id)
+    }
+
+    pub fn instance_mut(&mut self, id: u32) -> Option<&mut AttributeTable> {
+        self.instances.get_mut(&id)
+    }
+
+    /// Class attributes 1 to 7, computed from the current instances.
+    pub fn class_attributes(&self) -> AttributeTable {
+        let max_instance = self.instances.keys().next_back().copied().unwrap_or(0) as u16;
+        let max_attribute = self.instances.values().map(|table| table.max_id()).max().unwrap_or(0);
+
+        let mut optional_attributes: Vec<u16> = self.instances.values().flat_map(|table| table.attributes().filter(|attribute| attribute.optional).map(|attribute| attribute.id)).collect();
+        optional_attributes.sort();
+        optional_attributes.dedup();
+
+        return AttributeTable::new()
+            .with(Attribute::get(1, CipDataType::Uint, self.revision.to_le_bytes().to_vec()))
+            .with(Attribute::get(2, CipDataType::Uint, max_instance.to_le_bytes().to_vec()))
+            .with(Attribute::get(3, CipDataType::Uint, (self.instances.len() as u16).to_le_bytes().to_vec()))
+            .with(Attribute::get(4, CipDataType::Struct, id_list(&optional_attributes)))
+            .with(Attribute::get(5, CipDataType::Struct, id_list(&self.optional_services)))
+            .with(Attribute::get(6, CipDataType::Uint, MAX_CLASS_ATTRIBUTE.to_le_bytes().to_vec()))
+            .with(Attribute::get(7, CipDataType::Uint, max_attribute.to_le_bytes().to_vec()));
+    }
+}
+
+/// A UINT count followed by the UINT IDs.
+fn id_list(ids: &[u16]) -> Vec<u8> {
+    let mut vec = Vec::new();
+    vec.extend_from_slice(&(ids.len() as u16).to_le_bytes());
+    for id in ids {
+        vec.extend_from_slice(&id.to_le_bytes());
+    }
+
+    return vec;
+}
+
+impl CipObject for AttributeObject {
+    fn class_id(&self) -> u16 {
+        self.class_id
+    }
+
+    fn has_instance(&self, instance: u32) -> bool {
+        self.instances.contains_key(&instance)
+    }
+
+    fn class_service(&mut self, service: u8, path: &ObjectPath, data: &[u8]) -> ServiceResult {
+        if service == CipService::SetAttributeSingle as u8 {
+            return match path.attribute {
+                Some(1..=7) => Err(MessageRouterResponseStatusCodes::AttributeNotSettable.into()),
+                _ => Err(MessageRouterResponseStatusCodes::AttributeNotSupported.into())
+            };
+        }
+
+        return self.class_attributes().handle(service, path, data);
+    }
+
+    fn instance_service(&mut self, service: u8, path: &ObjectPath, data: &[u8]) -> ServiceResult {
+        return match self.instances.get_mut(&path.instance) {
+            Some(table) => table.handle(service, path, data),
+            None => Err(MessageRouterResponseStatusCodes::PathDestinationUnknown.into())
+        };
+    }
+}
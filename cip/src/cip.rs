@@ -1,12 +1,28 @@
 use alloc::{boxed::Box, vec::{Vec}};
 use async_trait::async_trait;
-use nom::{bytes::complete::take, sequence::tuple, InputTake};
+use nom::{bytes::complete::take, error::{Error, ErrorKind}, multi::count, number::complete::{le_u16, le_u32, le_u8}, sequence::tuple, IResult};
 
-use crate::{common::Serializable, objects::{connection_manager::UnconnectedSendRequest, message_router::MessageRouter}};
+use crate::{common::Serializable, error::{CipError, KeyMismatch}, objects::{connection_manager::{UnconnectedSendRequest, FORWARD_OPEN, LARGE_FORWARD_OPEN}, ethernet_link::GET_AND_CLEAR, file::{DOWNLOAD_TRANSFER, INITIATE_DOWNLOAD}, identity::{Identity, ResetType}, message_router::{MessageRouter, MessageRouterResponseStatusCodes}}, value::CipValue};
 
-pub trait EpathSegments: Serializable {
+pub trait EpathSegments: Serializable + Send + Sync {
     fn get_type(&self) -> u8;
     fn get_data(&self) -> Vec<u8>;
+
+    fn as_logical(&self) -> Option<&LogicalSegment> {
+        None
+    }
+
+    fn as_data(&self) -> Option<&DataSegment> {
+        None
+    }
+
+    fn as_symbol(&self) -> Option<&AnsiSymbolSegment> {
+        None
+    }
+
+    fn as_network(&self) -> Option<&NetworkSegment> {
+        None
+    }
 }
 
 pub struct LogicalSegment {
@@ -42,6 +58,14 @@ impl LogicalSegment {
         self.logical_type = logical_type;
         self.value = value;
     }
+
+    pub fn logical_type(&self) -> u8 {
+        self.logical_type
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
 }
 
 impl EpathSegments for LogicalSegment {
@@ -65,23 +89,27 @@ impl EpathSegments for LogicalSegment {
 
         return result;
     }
+
+    fn as_logical(&self) -> Option<&LogicalSegment> {
+        Some(self)
+    }
 }
 
 impl Serializable for LogicalSegment {
     fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
-        let encoding1 = input.take_split(1);
-        let encoding = encoding1.0[0];
-        let logical_segment_type = (encoding & 0b00011100) >> 2;
+        let (input, encoding) = le_u8(input)?;
+        let logical_type = (encoding & 0b00011100) >> 2;
         let logical_format = encoding & 0b00000011;
 
-        let value = encoding1.1.take_split((logical_format + 1).into());
-        let mut actual_value: u32 = 0;
+        // 16 and 32-bit values are preceded by a pad byte.
+        let (input, value) = match logical_format {
+            0b00 => le_u8(input).map(|(input, value)| (input, value as u32))?,
+            0b01 => tuple((le_u8, le_u16))(input).map(|(input, (_, value))| (input, value as u32))?,
+            0b10 => tuple((le_u8, le_u32))(input).map(|(input, (_, value))| (input, value))?,
+            _ => return Err(nom::Err::Error(Error::new(input, ErrorKind::Switch)))
+        };
 
-        for num in value.0 {
-            actual_value = (actual_value << 8) + u32::from(num.clone());
-        }
-
-        return Ok((value.1, LogicalSegment { logical_type: logical_segment_type, logical_format: logical_format, value: actual_value}));
+        return Ok((input, LogicalSegment { logical_type, logical_format, value }));
     }
 
     fn serialize(&self) -> Vec<u8> {
@@ -126,13 +154,25 @@ impl EpathSegments for PortSegment {
 
 impl Serializable for PortSegment {
     fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
-        let encoding1 = input.take_split(1);
-        let encoding = encoding1.0[0];
-        let is_extended = (encoding & 0b00010000) != 0;
-        let value = encoding & 0b1111;
-        let (reamining, link_address) = input.take_split(1);
+        let (input, encoding) = le_u8(input)?;
+        let extended_link_address = (encoding & 0b00010000) != 0;
+        let port_identifier = encoding & 0b1111;
+
+        if port_identifier == 15 {
+            return Err(nom::Err::Error(Error::new(input, ErrorKind::Verify)));
+        }
 
-        return Ok((reamining, PortSegment { extended_link_address: is_extended, port_identifier: value, link_address: alloc::vec![link_address[0]] }));
+        if !extended_link_address {
+            let (input, link_address) = le_u8(input)?;
+            return Ok((input, PortSegment { extended_link_address, port_identifier, link_address: alloc::vec![link_address] }));
+        }
+
+        // The segment is padded to an even length.
+        let (input, length) = le_u8(input)?;
+        let (input, link_address) = take(length)(input)?;
+        let (input, _) = take(length as usize % 2)(input)?;
+
+        return Ok((input, PortSegment { extended_link_address, port_identifier, link_address: link_address.to_vec() }));
     }
 
     fn serialize(&self) -> Vec<u8> {
@@ -162,6 +202,344 @@ impl PortSegment {
     }
 }
 
+/// Electronic key segment: logical type Special with key format 4.
+/// Zero in any field other than `compatibility` means "don't care".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ElectronicKeySegment {
+    pub vendor_id: u16,
+    pub device_type: u16,
+    pub product_code: u16,
+    /// The target may accept the key if it can emulate the keyed device.
+    /// Without it every non-zero field must match exactly.
+    pub compatibility: bool,
+    pub major_revision: u8,
+    pub minor_revision: u8
+}
+
+const ELECTRONIC_KEY_SEGMENT: u8 = 0x34;
+const ELECTRONIC_KEY_FORMAT: u8 = 0x04;
+
+impl ElectronicKeySegment {
+    /// Key that only matches a device with exactly this identity.
+    pub fn exact(identity: &Identity) -> Self {
+        Self { vendor_id: identity.vendor_id, device_type: identity.device_type, product_code: identity.product_code, compatibility: false, major_revision: identity.major_revision, minor_revision: identity.minor_revision }
+    }
+
+    /// Key that also matches later minor revisions of the same device.
+    pub fn compatible(identity: &Identity) -> Self {
+        Self { compatibility: true, ..Self::exact(identity) }
+    }
+
+    /// Checks `identity` the way a target checks a key against itself.
+    pub fn check(&self, identity: &Identity) -> Result<(), KeyMismatch> {
+        if (self.vendor_id != 0 && self.vendor_id != identity.vendor_id) || (self.product_code != 0 && self.product_code != identity.product_code) {
+            return Err(KeyMismatch::VendorOrProduct);
+        }
+        if self.device_type != 0 && self.device_type != identity.device_type {
+            return Err(KeyMismatch::DeviceType);
+        }
+
+        if self.major_revision != 0 && self.major_revision != identity.major_revision & 0x7F {
+            return Err(KeyMismatch::Revision);
+        }
+        let minor_matches = match self.compatibility {
+            true => identity.minor_revision >= self.minor_revision,
+            false => self.minor_revision == 0 || identity.minor_revision == self.minor_revision
+        };
+        if !minor_matches {
+            return Err(KeyMismatch::Revision);
+        }
+
+        return Ok(());
+    }
+}
+
+impl EpathSegments for ElectronicKeySegment {
+    fn get_type(&self) -> u8 {
+        return ELECTRONIC_KEY_SEGMENT;
+    }
+
+    fn get_data(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        result.push(ELECTRONIC_KEY_FORMAT);
+        result.extend_from_slice(&self.vendor_id.to_le_bytes());
+        result.extend_from_slice(&self.device_type.to_le_bytes());
+        result.extend_from_slice(&self.product_code.to_le_bytes());
+        result.push(self.major_revision & 0x7F | (self.compatibility as u8) << 7);
+        result.push(self.minor_revision);
+
+        return result;
+    }
+}
+
+impl Serializable for ElectronicKeySegment {
+    fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
+        let (input, (segment_type, key_format)) = tuple((le_u8, le_u8))(input)?;
+        if segment_type != ELECTRONIC_KEY_SEGMENT || key_format != ELECTRONIC_KEY_FORMAT {
+            return Err(nom::Err::Error(Error::new(input, ErrorKind::Verify)));
+        }
+
+        let (input, (vendor_id, device_type, product_code, major_revision, minor_revision)) = tuple((le_u16, le_u16, le_u16, le_u8, le_u8))(input)?;
+
+        return Ok((input, ElectronicKeySegment { vendor_id, device_type, product_code, compatibility: major_revision & 0x80 != 0, major_revision: major_revision & 0x7F, minor_revision }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        vec.push(self.get_type());
+        vec.extend(self.get_data());
+
+        return vec;
+    }
+}
+
+/// Simple data segment, used for configuration data in Forward Open paths.
+pub struct DataSegment {
+    data: Vec<u8>
+}
+
+const SIMPLE_DATA_SEGMENT: u8 = 0x80;
+
+impl DataSegment {
+    /// Fails if `data` is longer than the 255 words the segment can carry.
+    pub fn new(data: Vec<u8>) -> Result<Self, CipError> {
+        if data.len() > u8::MAX as usize * 2 {
+            return Err(CipError::InvalidValue("data does not fit in a data segment"));
+        }
+
+        return Ok(Self { data });
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl EpathSegments for DataSegment {
+    fn get_type(&self) -> u8 {
+        return SIMPLE_DATA_SEGMENT;
+    }
+
+    /// Word count followed by the data, padded to whole words.
+    fn get_data(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        result.push(self.data.len().div_ceil(2) as u8);
+        result.extend_from_slice(&self.data);
+        if !self.data.len().is_multiple_of(2) {
+            result.push(0);
+        }
+
+        return result;
+    }
+
+    fn as_data(&self) -> Option<&DataSegment> {
+        Some(self)
+    }
+}
+
+impl Serializable for DataSegment {
+    fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
+        let (input, (segment_type, words)) = tuple((le_u8, le_u8))(input)?;
+        if segment_type != SIMPLE_DATA_SEGMENT {
+            return Err(nom::Err::Error(Error::new(input, ErrorKind::Verify)));
+        }
+
+        let (input, data) = take(words as usize * 2)(input)?;
+        return Ok((input, DataSegment { data: data.to_vec() }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        vec.push(self.get_type());
+        vec.extend(self.get_data());
+
+        return vec;
+    }
+}
+
+/// ANSI extended symbol segment, used to address tags by name.
+pub struct AnsiSymbolSegment {
+    symbol: Vec<u8>
+}
+
+const ANSI_SYMBOL_SEGMENT: u8 = 0x91;
+
+impl AnsiSymbolSegment {
+    /// Fails if `symbol` is longer than 255 bytes.
+    pub fn new(symbol: &str) -> Result<Self, CipError> {
+        if symbol.len() > u8::MAX as usize {
+            return Err(CipError::InvalidValue("symbol is longer than 255 bytes"));
+        }
+
+        return Ok(Self { symbol: symbol.as_bytes().to_vec() });
+    }
+
+    pub fn symbol(&self) -> &[u8] {
+        &self.symbol
+    }
+}
+
+impl EpathSegments for AnsiSymbolSegment {
+    fn get_type(&self) -> u8 {
+        return ANSI_SYMBOL_SEGMENT;
+    }
+
+    /// Byte count followed by the symbol, padded to an even length.
+    fn get_data(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        result.push(self.symbol.len() as u8);
+        result.extend_from_slice(&self.symbol);
+        if !self.symbol.len().is_multiple_of(2) {
+            result.push(0);
+        }
+
+        return result;
+    }
+
+    fn as_symbol(&self) -> Option<&AnsiSymbolSegment> {
+        Some(self)
+    }
+}
+
+impl Serializable for AnsiSymbolSegment {
+    fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
+        let (input, (segment_type, length)) = tuple((le_u8, le_u8))(input)?;
+        if segment_type != ANSI_SYMBOL_SEGMENT {
+            return Err(nom::Err::Error(Error::new(input, ErrorKind::Verify)));
+        }
+
+        let (input, symbol) = take(length)(input)?;
+        let (input, _) = take(length as usize % 2)(input)?;
+        return Ok((input, AnsiSymbolSegment { symbol: symbol.to_vec() }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        vec.push(self.get_type());
+        vec.extend(self.get_data());
+
+        return vec;
+    }
+}
+
+/// Network segment. Subtypes with bit 4 set carry their length in words.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NetworkSegment {
+    Schedule(u8),
+    FixedTag(u8),
+    /// Minimum time between productions in milliseconds.
+    ProductionInhibitTime(u8),
+    /// Minimum time between productions in microseconds.
+    ProductionInhibitTimeMicroseconds(u32),
+    Safety(Vec<u8>),
+    Extended { subtype: u16, data: Vec<u8> },
+    Other { subtype: u8, data: Vec<u8> }
+}
+
+const NETWORK_SEGMENT: u8 = 0x40;
+const SCHEDULE: u8 = 0x01;
+const FIXED_TAG: u8 = 0x02;
+const PRODUCTION_INHIBIT_TIME: u8 = 0x03;
+const SAFETY: u8 = 0x10;
+const PRODUCTION_INHIBIT_TIME_MICROSECONDS: u8 = 0x11;
+const EXTENDED_NETWORK: u8 = 0x1F;
+const SIZED_SUBTYPE: u8 = 0x10;
+
+impl NetworkSegment {
+    fn subtype(&self) -> u8 {
+        match self {
+            NetworkSegment::Schedule(_) => SCHEDULE,
+            NetworkSegment::FixedTag(_) => FIXED_TAG,
+            NetworkSegment::ProductionInhibitTime(_) => PRODUCTION_INHIBIT_TIME,
+            NetworkSegment::ProductionInhibitTimeMicroseconds(_) => PRODUCTION_INHIBIT_TIME_MICROSECONDS,
+            NetworkSegment::Safety(_) => SAFETY,
+            NetworkSegment::Extended { .. } => EXTENDED_NETWORK,
+            NetworkSegment::Other { subtype, .. } => *subtype & 0x1F
+        }
+    }
+}
+
+impl EpathSegments for NetworkSegment {
+    fn get_type(&self) -> u8 {
+        return NETWORK_SEGMENT | self.subtype();
+    }
+
+    /// One data byte, or a word count followed by whole words for sized subtypes.
+    fn get_data(&self) -> Vec<u8> {
+        let data = match self {
+            NetworkSegment::Schedule(value) | NetworkSegment::FixedTag(value) | NetworkSegment::ProductionInhibitTime(value) => return alloc::vec![*value],
+            NetworkSegment::ProductionInhibitTimeMicroseconds(time) => time.to_le_bytes().to_vec(),
+            NetworkSegment::Safety(data) => data.clone(),
+            NetworkSegment::Extended { subtype, data } => {
+                let mut result = subtype.to_le_bytes().to_vec();
+                result.extend_from_slice(data);
+                result
+            }
+            NetworkSegment::Other { subtype, data } if subtype & SIZED_SUBTYPE != 0 => data.clone(),
+            NetworkSegment::Other { data, .. } => return alloc::vec![data.first().copied().unwrap_or(0)]
+        };
+
+        let mut result = Vec::new();
+        result.push(data.len().div_ceil(2) as u8);
+        result.extend_from_slice(&data);
+        if data.len() % 2 != 0 {
+            result.push(0);
+        }
+
+        return result;
+    }
+
+    fn as_network(&self) -> Option<&NetworkSegment> {
+        Some(self)
+    }
+}
+
+impl Serializable for NetworkSegment {
+    fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
+        let (input, segment_type) = le_u8(input)?;
+        if segment_type & 0xE0 != NETWORK_SEGMENT {
+            return Err(nom::Err::Error(Error::new(input, ErrorKind::Verify)));
+        }
+
+        let subtype = segment_type & 0x1F;
+        if subtype & SIZED_SUBTYPE == 0 {
+            let (input, value) = le_u8(input)?;
+            let segment = match subtype {
+                SCHEDULE => NetworkSegment::Schedule(value),
+                FIXED_TAG => NetworkSegment::FixedTag(value),
+                PRODUCTION_INHIBIT_TIME => NetworkSegment::ProductionInhibitTime(value),
+                subtype => NetworkSegment::Other { subtype, data: alloc::vec![value] }
+            };
+            return Ok((input, segment));
+        }
+
+        let (input, words) = le_u8(input)?;
+        let (input, data) = take(words as usize * 2)(input)?;
+        let segment = match subtype {
+            SAFETY => NetworkSegment::Safety(data.to_vec()),
+            PRODUCTION_INHIBIT_TIME_MICROSECONDS => {
+                let (_, time) = le_u32(data)?;
+                NetworkSegment::ProductionInhibitTimeMicroseconds(time)
+            }
+            EXTENDED_NETWORK => {
+                let (data, subtype) = le_u16(data)?;
+                NetworkSegment::Extended { subtype, data: data.to_vec() }
+            }
+            subtype => NetworkSegment::Other { subtype, data: data.to_vec() }
+        };
+
+        return Ok((input, segment));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        vec.push(self.get_type());
+        vec.extend(self.get_data());
+
+        return vec;
+    }
+}
+
 pub struct EPath {
     pub attributes: Vec<Box<dyn EpathSegments>>
 }
@@ -170,6 +548,49 @@ impl EPath {
     pub fn new() -> Self {
         Self { attributes: Vec::new() }
     }
+
+    /// Parses padded path segments until `input` is used up.
+    pub fn parse(mut input: &[u8]) -> IResult<&[u8], Self> {
+        let mut attributes: Vec<Box<dyn EpathSegments>> = Vec::new();
+
+        while let Some(segment_type) = input.first() {
+            match segment_type >> 5 {
+                0b000 => {
+                    let (remaining, segment) = PortSegment::deserialize(input)?;
+                    attributes.push(Box::new(segment));
+                    input = remaining;
+                }
+                0b001 if *segment_type == ELECTRONIC_KEY_SEGMENT => {
+                    let (remaining, segment) = ElectronicKeySegment::deserialize(input)?;
+                    attributes.push(Box::new(segment));
+                    input = remaining;
+                }
+                0b001 => {
+                    let (remaining, segment) = LogicalSegment::deserialize(input)?;
+                    attributes.push(Box::new(segment));
+                    input = remaining;
+                }
+                0b010 => {
+                    let (remaining, segment) = NetworkSegment::deserialize(input)?;
+                    attributes.push(Box::new(segment));
+                    input = remaining;
+                }
+                0b100 if *segment_type == SIMPLE_DATA_SEGMENT => {
+                    let (remaining, segment) = DataSegment::deserialize(input)?;
+                    attributes.push(Box::new(segment));
+                    input = remaining;
+                }
+                0b100 if *segment_type == ANSI_SYMBOL_SEGMENT => {
+                    let (remaining, segment) = AnsiSymbolSegment::deserialize(input)?;
+                    attributes.push(Box::new(segment));
+                    input = remaining;
+                }
+                _ => return Err(nom::Err::Error(Error::new(input, ErrorKind::Switch)))
+            }
+        }
+
+        return Ok((input, EPath { attributes }));
+    }
 }
 
 #[repr(u8)]
@@ -244,22 +665,96 @@ pub enum CipClass {
 
 #[repr(u16)]
 #[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CipDataType {
-    Bool = 0x01,
-    Sint = 0x02,
-    Int = 0x03,
-    Dint = 0x04,
-    Lint = 0x05,
-    Usint = 0x06,
-    Uint = 0x07,
-    Udint = 0x08,
-    Ulint = 0x09,
-    Real = 0x0A,
-    Lreal = 0x0B,
-    Byte = 0x0C,
-    Word = 0x0D,
-    Dword = 0x0E,
-    Lword = 0x0F,
+    Bool = 0xC1,
+    Sint = 0xC2,
+    Int = 0xC3,
+    Dint = 0xC4,
+    Lint = 0xC5,
+    Usint = 0xC6,
+    Uint = 0xC7,
+    Udint = 0xC8,
+    Ulint = 0xC9,
+    Real = 0xCA,
+    Lreal = 0xCB,
+    Stime = 0xCC,
+    Date = 0xCD,
+    TimeOfDay = 0xCE,
+    DateAndTime = 0xCF,
+    String = 0xD0,
+    Byte = 0xD1,
+    Word = 0xD2,
+    Dword = 0xD3,
+    Lword = 0xD4,
+    Ftime = 0xD6,
+    Ltime = 0xD7,
+    Itime = 0xD8,
+    ShortString = 0xDA,
+    Time = 0xDB,
+    Epath = 0xDC,
+    EngUnit = 0xDD,
+    AbbreviatedStruct = 0xA0,
+    AbbreviatedArray = 0xA1,
+    Struct = 0xA2,
+    Array = 0xA3
+}
+
+impl CipDataType {
+    /// Encoded size of the type, or `None` if it varies.
+    pub fn size(&self) -> Option<usize> {
+        match self {
+            CipDataType::Bool | CipDataType::Sint | CipDataType::Usint | CipDataType::Byte => Some(1),
+            CipDataType::Int | CipDataType::Uint | CipDataType::Word | CipDataType::Date | CipDataType::Itime | CipDataType::EngUnit => Some(2),
+            CipDataType::Dint | CipDataType::Udint | CipDataType::Real | CipDataType::Dword | CipDataType::Stime | CipDataType::TimeOfDay | CipDataType::Ftime | CipDataType::Time => Some(4),
+            CipDataType::DateAndTime => Some(6),
+            CipDataType::Lint | CipDataType::Ulint | CipDataType::Lreal | CipDataType::Lword | CipDataType::Ltime => Some(8),
+            _ => None
+        }
+    }
+}
+
+impl TryFrom<u16> for CipDataType {
+    type Error = u16;
+
+    fn try_from(code: u16) -> Result<Self, Self::Error> {
+        let data_type = match code {
+            0xC1 => CipDataType::Bool,
+            0xC2 => CipDataType::Sint,
+            0xC3 => CipDataType::Int,
+            0xC4 => CipDataType::Dint,
+            0xC5 => CipDataType::Lint,
+            0xC6 => CipDataType::Usint,
+            0xC7 => CipDataType::Uint,
+            0xC8 => CipDataType::Udint,
+            0xC9 => CipDataType::Ulint,
+            0xCA => CipDataType::Real,
+            0xCB => CipDataType::Lreal,
+            0xCC => CipDataType::Stime,
+            0xCD => CipDataType::Date,
+            0xCE => CipDataType::TimeOfDay,
+            0xCF => CipDataType::DateAndTime,
+            0xD0 => CipDataType::String,
+            0xD1 => CipDataType::Byte,
+            0xD2 => CipDataType::Word,
+            0xD3 => CipDataType::Dword,
+            0xD4 => CipDataType::Lword,
+            0xD6 => CipDataType::Ftime,
+            0xD7 => CipDataType::Ltime,
+            0xD8 => CipDataType::Itime,
+            0xDA => CipDataType::ShortString,
+            0xDB => CipDataType::Time,
+            0xDC => CipDataType::Epath,
+            0xDD => CipDataType::EngUnit,
+            0xA0 => CipDataType::AbbreviatedStruct,
+            0xA1 => CipDataType::AbbreviatedArray,
+            0xA2 => CipDataType::Struct,
+            0xA3 => CipDataType::Array,
+            _ => return Err(code)
+        };
+
+        return Ok(data_type);
+    }
 }
 
 pub struct MessageRouterRequest {
@@ -269,8 +764,12 @@ pub struct MessageRouterRequest {
 }
 
 impl Serializable for MessageRouterRequest {
-    fn deserialize(_input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
-        todo!()
+    fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
+        let (input, (service, path_size)) = tuple((le_u8, le_u8))(input)?;
+        let (data, path) = take(path_size as usize * 2)(input)?;
+        let (_, epath) = EPath::parse(path)?;
+
+        return Ok((&[], MessageRouterRequest { service, epath, data: data.to_vec() }));
     }
 
     fn serialize(&self) -> Vec<u8> {
@@ -314,11 +813,51 @@ impl Serializable for MessageRouterResponse {
         let general_status = raw_general_status[0].into();
         let size_of_additional_status = raw_size_of_additional_status[0].into();
 
-        return Ok((&input, MessageRouterResponse { service, reserved, general_status, size_of_additional_status, additional_status: Vec::new(), data: input.to_vec()}));
+        let mut additional_status = Vec::new();
+        let mut input = input;
+        for _ in 0..size_of_additional_status {
+            let (remaining, status) = le_u16(input)?;
+            additional_status.push(status);
+            input = remaining;
+        }
+
+        return Ok((&[], MessageRouterResponse { service, reserved, general_status, size_of_additional_status, additional_status, data: input.to_vec()}));
     }
 
     fn serialize(&self) -> Vec<u8> {
-        todo!()
+        let mut vec = alloc::vec![self.service, 0, self.general_status, self.additional_status.len() as u8];
+        for status in &self.additional_status {
+            vec.extend_from_slice(&status.to_le_bytes());
+        }
+        vec.extend_from_slice(&self.data);
+
+        return vec;
+    }
+}
+
+impl MessageRouterResponse {
+    /// Turns a non-zero general status into an error.
+    pub fn check(&self) -> Result<(), CipError> {
+        if self.general_status != 0 {
+            return Err(CipError::Service { general_status: self.general_status, additional_status: self.additional_status.clone() });
+        }
+
+        return Ok(());
+    }
+}
+
+/// One attribute of a Get_Attribute_List or Set_Attribute_List reply.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeListEntry {
+    pub id: u16,
+    pub status: u16,
+    /// Value read by Get_Attribute_List, `None` for sets and failed reads.
+    pub value: Option<CipValue>
+}
+
+impl AttributeListEntry {
+    pub fn is_ok(&self) -> bool {
+        self.status == 0
     }
 }
 
@@ -328,41 +867,111 @@ pub struct DataResult {
 }
 
 #[async_trait]
-pub trait Client {
-    async fn begin_session(&mut self);
-    async fn send_unconnected(&mut self, packet: Vec<u8>);
-    async fn send_connected(&mut self, packet: Vec<u8>);
-    async fn read_data(&mut self) -> DataResult;
-    async fn send_nop(&mut self);
-    async fn close_session(&mut self);
+pub trait Client: Send {
+    async fn begin_session(&mut self) -> Result<(), CipError>;
+    async fn send_unconnected(&mut self, packet: Vec<u8>) -> Result<(), CipError>;
+    async fn send_connected(&mut self, packet: Vec<u8>) -> Result<(), CipError>;
+    async fn read_data(&mut self) -> Result<DataResult, CipError>;
+    async fn send_nop(&mut self) -> Result<(), CipError>;
+    async fn close_session(&mut self) -> Result<(), CipError>;
+
+    /// Sends an unconnected message and waits for the reply that belongs to it.
+    ///
+    /// Transports that correlate replies (for example through the encapsulation
+    /// sender context) should override this; the default assumes the next
+    /// packet read is the reply.
+    async fn request_unconnected(&mut self, packet: Vec<u8>) -> Result<DataResult, CipError> {
+        self.send_unconnected(packet).await?;
+        return self.read_data().await;
+    }
+
+    /// Makes sure a session is open and returns a number that changes whenever
+    /// the transport replaces it, for example after a reconnect.
+    async fn session_generation(&mut self) -> Result<u64, CipError> {
+        return Ok(0);
+    }
 }
 
 pub struct CipClient {
-    client: Box<dyn Client>
+    client: Box<dyn Client>,
+    expected_key: Option<ElectronicKeySegment>,
+    /// Session generation the expected key was last verified on.
+    verified_generation: Option<u64>
+}
+
+/// Services that change the state of the target and need a verified key.
+fn is_write(class_id: u32, service: u8) -> bool {
+    const WRITES: [u8; 10] = [
+        CipService::SetAttributesAll as u8, CipService::SetAttributesList as u8, CipService::Reset as u8, CipService::Start as u8, CipService::Stop as u8,
+        CipService::Create as u8, CipService::Delete as u8, CipService::ApplyAttributes as u8, CipService::SetAttributeSingle as u8, CipService::Save as u8
+    ];
+
+    return WRITES.contains(&service)
+        || (class_id == CipClass::File as u32 && (service == INITIATE_DOWNLOAD || service == DOWNLOAD_TRANSFER))
+        || (class_id == CipClass::EtherNetLink as u32 && service == GET_AND_CLEAR)
+        || (class_id == CipClass::ConnectionManager as u32 && (service == FORWARD_OPEN || service == LARGE_FORWARD_OPEN));
 }
 
 impl CipClient {
     pub fn new(client: impl Client + 'static) -> Self {
-        Self { client: Box::new(client) }
+        Self { client: Box::new(client), expected_key: None, verified_generation: None }
+    }
+
+    pub async fn connect(&mut self) -> Result<(), CipError> {
+        self.verified_generation = None;
+        self.client.begin_session().await
+    }
+
+    /// Makes the client check the device's Identity against `key` before the first write
+    /// of each session, including sessions a reconnecting transport opens on its own.
+    /// Writes fail with `CipError::KeyMismatch` while it does not match.
+    ///
+    /// Requests built by the caller are checked too, except raw packets passed to
+    /// `send_unconnected`. A reconnect between the identity read and the write itself
+    /// is not caught.
+    pub fn set_expected_key(&mut self, key: Option<ElectronicKeySegment>) {
+        self.expected_key = key;
+        self.verified_generation = None;
     }
 
-    pub async fn connect(&mut self) {
-        self.client.begin_session().await;
+    /// Reads the Identity object and checks it against `key`.
+    pub async fn verify_identity(&mut self, key: &ElectronicKeySegment) -> Result<Identity, CipError> {
+        let identity = self.read_identity().await?;
+        key.check(&identity).map_err(CipError::KeyMismatch)?;
+        return Ok(identity);
     }
 
-    pub async fn send_unconnected(&mut self, packet: Vec<u8>) {
-        self.client.send_unconnected(packet).await;
+    async fn check_key(&mut self, class_id: u32, service: u8) -> Result<(), CipError> {
+        let Some(key) = self.expected_key else {
+            return Ok(());
+        };
+        if !is_write(class_id, service) {
+            return Ok(());
+        }
+
+        let generation = self.client.session_generation().await?;
+        if self.verified_generation == Some(generation) {
+            return Ok(());
+        }
+
+        self.verify_identity(&key).await?;
+        self.verified_generation = Some(generation);
+        return Ok(());
     }
 
-    pub async fn read_data(&mut self) -> DataResult {
+    pub async fn send_unconnected(&mut self, packet: Vec<u8>) -> Result<(), CipError> {
+        self.client.send_unconnected(packet).await
+    }
+
+    pub async fn read_data(&mut self) -> Result<DataResult, CipError> {
         self.client.read_data().await
     }
 
-    pub async fn disconnect(&mut self) {
-        self.client.close_session().await;
+    pub async fn disconnect(&mut self) -> Result<(), CipError> {
+        self.client.close_session().await
     }
 
-    pub async fn call_service(&mut self, class_id: u32, instance_id: u32, service_num: u8, data: Vec<u8>) -> MessageRouterResponse {
+    pub async fn call_service(&mut self, class_id: u32, instance_id: u32, service_num: u8, data: Vec<u8>) -> Result<MessageRouterResponse, CipError> {
         let mut class_segment = LogicalSegment::new();
         let mut instance_segment = LogicalSegment::new(); 
     
@@ -377,15 +986,38 @@ impl CipClient {
     
         let request = MessageRouterRequest { service: new_service_num, epath, data };
 
-        self.send_unconnected_cm(request).await;
+        return self.request_unconnected_cm(request).await;
+    }
+
+    pub async fn send_unconnected_cm(&mut self, request: MessageRouterRequest) -> Result<(), CipError> {
+        self.check_key(Self::class_of(&request), request.service).await?;
 
-        let data = self.client.read_data().await;
-    
-        let result = MessageRouterResponse::deserialize(&data.data).unwrap();
-        return result.1;
+        let request = Self::unconnected_send(request);
+        self.client.send_unconnected(request.serialize()).await
     }
 
-    pub async fn send_unconnected_cm(&mut self, request: MessageRouterRequest) {
+    /// Sends `request` through the Connection Manager's Unconnected Send and waits for its reply.
+    pub async fn request_unconnected_cm(&mut self, request: MessageRouterRequest) -> Result<MessageRouterResponse, CipError> {
+        self.check_key(Self::class_of(&request), request.service).await?;
+        return self.send_request_cm(request).await;
+    }
+
+    /// Class addressed by the first class segment of `request`, 0 if it has none.
+    fn class_of(request: &MessageRouterRequest) -> u32 {
+        let class = request.epath.attributes.iter().filter_map(|segment| segment.as_logical()).find(|segment| segment.logical_type() == LogicalType::ClassId as u8);
+        return class.map(|segment| segment.value()).unwrap_or(0);
+    }
+
+    /// `request_unconnected_cm` without the key check.
+    async fn send_request_cm(&mut self, request: MessageRouterRequest) -> Result<MessageRouterResponse, CipError> {
+        let request = Self::unconnected_send(request);
+        let data = self.client.request_unconnected(request.serialize()).await?;
+
+        let result = MessageRouterResponse::deserialize(&data.data)?;
+        return Ok(result.1);
+    }
+
+    fn unconnected_send(request: MessageRouterRequest) -> MessageRouterRequest {
         let mut unconnected_send_epath  = EPath::new();
         let mut port_segment = PortSegment::new();
         port_segment.set_address(alloc::vec![2]);
@@ -407,12 +1039,10 @@ impl CipClient {
         identity_epath.attributes.push(Box::new(identity_class_segment));
         identity_epath.attributes.push(Box::new(identity_instance_segment));
     
-        let request = MessageRouterRequest { service: 0x52, epath, data: UnconnectedSendRequest { priority: 0b11, timeout_ticks: 240, message_request: request, route_path: unconnected_send_epath }.serialize() };
-    
-        self.client.send_unconnected(request.serialize()).await;
+        return MessageRouterRequest { service: 0x52, epath, data: UnconnectedSendRequest { priority: 0b11, timeout_ticks: 240, message_request: request, route_path: unconnected_send_epath }.serialize() };
     }
 
-    pub async fn get_supported_classes(&mut self) -> Vec<u16> {
+    pub async fn get_supported_classes(&mut self) -> Result<Vec<u16>, CipError> {
         let mut class_segment = LogicalSegment::new();
         let mut instance_segment = LogicalSegment::new(); 
         let mut attribute_segment = LogicalSegment::new(); 
@@ -427,38 +1057,80 @@ impl CipClient {
         epath.attributes.push(Box::new(attribute_segment));
     
         let request = MessageRouterRequest { service: CipService::GetAttributesAll as u8, epath, data: alloc::vec![] };
-        self.send_unconnected(request.serialize()).await;
-        let data = self.client.read_data().await;
+        let data = self.client.request_unconnected(request.serialize()).await?;
     
-        let response = MessageRouterResponse::deserialize(&data.data).unwrap();
-        let get_all_response = MessageRouter::deserialize(&response.1.data).unwrap();
+        let response = MessageRouterResponse::deserialize(&data.data)?;
+        let get_all_response = MessageRouter::deserialize(&response.1.data)?;
     
-        return get_all_response.1.objects;
+        return Ok(get_all_response.1.objects);
     }
     
-    pub async fn get_attribute_single(&mut self, class_id: u32, instance_id: u32, attribute_id: u32) -> MessageRouterResponse {
-        let mut class_segment = LogicalSegment::new();
-        let mut instance_segment = LogicalSegment::new(); 
-        let mut attribute_segment = LogicalSegment::new(); 
-    
-        class_segment.set_segment(LogicalType::ClassId as u8, class_id);
-        instance_segment.set_segment(LogicalType::InstanceId as u8, instance_id);
-        attribute_segment.set_segment(LogicalType::AttributeId as u8, attribute_id);
-    
-        let mut epath  = EPath::new();
-        epath.attributes.push(Box::new(class_segment));
-        epath.attributes.push(Box::new(instance_segment));
-        epath.attributes.push(Box::new(attribute_segment));
-    
-        let request = MessageRouterRequest { service: CipService::GetAttributeSingle as u8, epath, data: alloc::vec![] };
-        self.send_unconnected_cm(request).await;
-        let data = self.client.read_data().await;
-    
-        let result = MessageRouterResponse::deserialize(&data.data).unwrap();
-        return result.1;
+    pub async fn get_attribute_single(&mut self, class_id: u32, instance_id: u32, attribute_id: u32) -> Result<MessageRouterResponse, CipError> {
+        return self.attribute_request(CipService::GetAttributeSingle as u8, class_id, instance_id, attribute_id, alloc::vec![]).await;
     }
     
-    pub async fn set_attribute_single(&mut self, class_id: u32, instance_id: u32, attribute_id: u32) -> MessageRouterResponse {
+    pub async fn set_attribute_single(&mut self, class_id: u32, instance_id: u32, attribute_id: u32, data: Vec<u8>) -> Result<MessageRouterResponse, CipError> {
+        return self.attribute_request(CipService::SetAttributeSingle as u8, class_id, instance_id, attribute_id, data).await;
+    }
+
+    /// Reads several attributes in one request. Each attribute is parsed as the given type.
+    pub async fn get_attribute_list(&mut self, class_id: u32, instance_id: u32, attributes: &[(u16, CipDataType)]) -> Result<Vec<AttributeListEntry>, CipError> {
+        let mut request = Vec::new();
+        request.extend_from_slice(&(attributes.len() as u16).to_le_bytes());
+        for (id, _) in attributes {
+            request.extend_from_slice(&id.to_le_bytes());
+        }
+
+        let data = self.attribute_list_request(CipService::GetAttributeList as u8, class_id, instance_id, request).await?;
+        let (mut input, length) = le_u16::<_, Error<&[u8]>>(data.as_slice())?;
+
+        let mut entries = Vec::new();
+        for _ in 0..length {
+            let (rest, (id, status)) = tuple((le_u16::<_, Error<&[u8]>>, le_u16))(input)?;
+            input = rest;
+
+            let mut value = None;
+            if status == 0 {
+                let data_type = attributes.iter().find(|(requested, _)| *requested == id).map(|(_, data_type)| *data_type).ok_or(CipError::Parse)?;
+                let (rest, parsed) = CipValue::parse(data_type, input)?;
+                input = rest;
+                value = Some(parsed);
+            }
+
+            entries.push(AttributeListEntry { id, status, value });
+        }
+
+        return Ok(entries);
+    }
+
+    /// Writes several attributes in one request and returns the status of each.
+    pub async fn set_attribute_list(&mut self, class_id: u32, instance_id: u32, values: &[(u16, CipValue)]) -> Result<Vec<AttributeListEntry>, CipError> {
+        let mut request = Vec::new();
+        request.extend_from_slice(&(values.len() as u16).to_le_bytes());
+        for (id, value) in values {
+            request.extend_from_slice(&id.to_le_bytes());
+            request.extend(value.encode());
+        }
+
+        let data = self.attribute_list_request(CipService::SetAttributesList as u8, class_id, instance_id, request).await?;
+        let (input, length) = le_u16::<_, Error<&[u8]>>(data.as_slice())?;
+        let (_, statuses) = count(tuple((le_u16::<_, Error<&[u8]>>, le_u16)), length as usize)(input)?;
+
+        return Ok(statuses.into_iter().map(|(id, status)| AttributeListEntry { id, status, value: None }).collect());
+    }
+
+    /// Sends a list service. An attribute list error still carries the per-attribute replies.
+    async fn attribute_list_request(&mut self, service: u8, class_id: u32, instance_id: u32, data: Vec<u8>) -> Result<Vec<u8>, CipError> {
+        let response = self.call_service(class_id, instance_id, service, data).await?;
+        if response.general_status != MessageRouterResponseStatusCodes::AttributeListError as u8 {
+            response.check()?;
+        }
+
+        return Ok(response.data);
+    }
+
+    /// Sends `service` to a single attribute of an instance.
+    pub(crate) async fn attribute_request(&mut self, service: u8, class_id: u32, instance_id: u32, attribute_id: u32, data: Vec<u8>) -> Result<MessageRouterResponse, CipError> {
         let mut class_segment = LogicalSegment::new();
         let mut instance_segment = LogicalSegment::new(); 
         let mut attribute_segment = LogicalSegment::new(); 
@@ -472,14 +1144,36 @@ impl CipClient {
         epath.attributes.push(Box::new(instance_segment));
         epath.attributes.push(Box::new(attribute_segment));
     
-        let request = MessageRouterRequest { service: CipService::SetAttributeSingle as u8, epath, data: alloc::vec![] };
-        self.send_unconnected_cm(request).await;
-        let data = self.client.read_data().await;
-        let result = MessageRouterResponse::deserialize(&data.data).unwrap();
-        return result.1;
+        let request = MessageRouterRequest { service, epath, data };
+        return self.request_unconnected_cm(request).await;
+    }
+
+    /// Reads the Identity object with Get_Attributes_All.
+    pub async fn get_identity(&mut self) -> Result<Identity, CipError> {
+        return self.read_identity().await;
+    }
+
+    /// Sent without the key check, which uses it.
+    async fn read_identity(&mut self) -> Result<Identity, CipError> {
+        let mut epath = EPath::new();
+        epath.attributes.push(Box::new(LogicalSegment::init(LogicalType::ClassId as u8, CipClass::Identity as u32)));
+        epath.attributes.push(Box::new(LogicalSegment::init(LogicalType::InstanceId as u8, 1)));
+
+        let request = MessageRouterRequest { service: CipService::GetAttributesAll as u8, epath, data: alloc::vec![] };
+        let response = self.send_request_cm(request).await?;
+        response.check()?;
+
+        let (_, identity) = Identity::deserialize(&response.data)?;
+        return Ok(identity);
+    }
+
+    /// Asks the device to reset. The connection usually drops shortly after.
+    pub async fn reset(&mut self, reset_type: ResetType) -> Result<(), CipError> {
+        let response = self.call_service(CipClass::Identity as u32, 1, CipService::Reset as u8, alloc::vec![reset_type as u8]).await?;
+        return response.check();
     }
 
-    pub async fn send_nop(&mut self) {
-        self.client.send_nop().await;
+    pub async fn send_nop(&mut self) -> Result<(), CipError> {
+        self.client.send_nop().await
     }
 }
\ No newline at end of file
@@ -0,0 +1,881 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{iter::Peekable, str::Chars};
+
+use crate::{cip::{CipClient, CipDataType, DataSegment, EPath, ElectronicKeySegment}, error::CipError, objects::{connection_manager::{ForwardOpenRequest, ForwardOpenResponse}, file::FileRevision, parameter::{ParameterDescriptor, Scaling}}, value::CipValue};
+
+/// One comma separated field of an entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EdsField {
+    Empty,
+    /// Unquoted text such as numbers, keywords and references.
+    Text(String),
+    /// Quoted string, adjacent strings already joined.
+    Quoted(String)
+}
+
+impl EdsField {
+    pub fn is_empty(&self) -> bool {
+        *self == EdsField::Empty
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            EdsField::Empty => None,
+            EdsField::Text(text) | EdsField::Quoted(text) => Some(text)
+        }
+    }
+
+    /// Decimal, `0x` hexadecimal or `0b` binary integer.
+    pub fn as_number(&self) -> Option<i64> {
+        match self {
+            EdsField::Text(text) => parse_number(text),
+            _ => None
+        }
+    }
+}
+
+fn parse_number(text: &str) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text)
+    };
+
+    let value = if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if let Some(binary) = digits.strip_prefix("0b").or_else(|| digits.strip_prefix("0B")) {
+        i64::from_str_radix(binary, 2).ok()?
+    } else {
+        digits.parse().ok()?
+    };
+
+    return Some(if negative { -value } else { value });
+}
+
+/// `Keyword = field, field, ...;`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdsEntry {
+    pub keyword: String,
+    pub fields: Vec<EdsField>,
+    pub line: usize
+}
+
+impl EdsEntry {
+    pub fn field(&self, index: usize) -> &EdsField {
+        self.fields.get(index).unwrap_or(&EdsField::Empty)
+    }
+
+    fn error(&self, reason: &'static str) -> CipError {
+        CipError::Eds { line: self.line, reason }
+    }
+
+    fn string(&self, index: usize) -> String {
+        self.field(index).as_str().unwrap_or_default().into()
+    }
+
+    fn number(&self, index: usize) -> Result<Option<i64>, CipError> {
+        let field = self.field(index);
+        if field.is_empty() {
+            return Ok(None);
+        }
+
+        return field.as_number().map(Some).ok_or(self.error("expected a number"));
+    }
+
+    fn integer<T: TryFrom<i64>>(&self, index: usize) -> Result<Option<T>, CipError> {
+        match self.number(index)? {
+            Some(value) => T::try_from(value).map(Some).map_err(|_| self.error("number out of range")),
+            None => Ok(None)
+        }
+    }
+
+    fn required<T: TryFrom<i64>>(&self, index: usize) -> Result<T, CipError> {
+        return self.integer(index)?.ok_or(self.error("missing required field"));
+    }
+
+    fn reference(&self, index: usize) -> Result<EdsReference, CipError> {
+        return EdsReference::parse(self.field(index)).ok_or(self.error("expected a number, ParamN, AssemN or path"));
+    }
+
+    /// Number after `prefix` in keywords like `Param12`.
+    fn numbered(&self, prefix: &str) -> Option<u32> {
+        return numbered(&self.keyword, prefix);
+    }
+}
+
+fn numbered(keyword: &str, prefix: &str) -> Option<u32> {
+    let head = keyword.get(..prefix.len())?;
+    if !head.eq_ignore_ascii_case(prefix) {
+        return None;
+    }
+
+    let digits = &keyword[prefix.len()..];
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    return digits.parse().ok();
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdsSection {
+    pub name: String,
+    pub entries: Vec<EdsEntry>,
+    pub line: usize
+}
+
+impl EdsSection {
+    /// Looks up an entry; keywords are not case sensitive.
+    pub fn entry(&self, keyword: &str) -> Option<&EdsEntry> {
+        self.entries.iter().find(|entry| entry.keyword.eq_ignore_ascii_case(keyword))
+    }
+
+    fn required(&self, keyword: &str, reason: &'static str) -> Result<&EdsEntry, CipError> {
+        return self.entry(keyword).ok_or(CipError::Eds { line: self.line, reason });
+    }
+
+    fn string(&self, keyword: &str) -> Option<String> {
+        self.entry(keyword).and_then(|entry| entry.field(0).as_str()).map(String::from)
+    }
+}
+
+/// One element of an EDS path string such as `"20 04 24 [Param3] 2C 96"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdsPathItem {
+    Byte(u8),
+    /// Replaced by the value of a parameter when the path is used.
+    Param(u32)
+}
+
+/// Parses the hex bytes and parameter references of a path string.
+pub fn parse_path(text: &str) -> Option<Vec<EdsPathItem>> {
+    let mut items = Vec::new();
+    for token in text.split_ascii_whitespace() {
+        let token = token.strip_prefix('[').and_then(|token| token.strip_suffix(']')).unwrap_or(token);
+        match numbered(token, "Param") {
+            Some(number) => items.push(EdsPathItem::Param(number)),
+            None => items.push(EdsPathItem::Byte(u8::from_str_radix(token, 16).ok()?))
+        }
+    }
+
+    return Some(items);
+}
+
+fn parse_bytes(text: &str) -> Option<Vec<u8>> {
+    parse_path(text)?.into_iter().map(|item| match item {
+        EdsPathItem::Byte(byte) => Some(byte),
+        EdsPathItem::Param(_) => None
+    }).collect()
+}
+
+/// Field that holds a value directly or refers to where it is defined.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EdsReference {
+    None,
+    Value(i64),
+    Param(u32),
+    Assembly(u32),
+    Path(Vec<EdsPathItem>)
+}
+
+impl EdsReference {
+    fn parse(field: &EdsField) -> Option<Self> {
+        match field {
+            EdsField::Empty => Some(EdsReference::None),
+            EdsField::Quoted(path) => parse_path(path).map(EdsReference::Path),
+            EdsField::Text(text) => {
+                if let Some(number) = numbered(text, "Param") {
+                    return Some(EdsReference::Param(number));
+                }
+                if let Some(number) = numbered(text, "Assem") {
+                    return Some(EdsReference::Assembly(number));
+                }
+                parse_number(text).map(EdsReference::Value)
+            }
+        }
+    }
+}
+
+/// The `[File]` section.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdsFileInfo {
+    pub description: Option<String>,
+    pub create_date: Option<String>,
+    pub create_time: Option<String>,
+    pub modification_date: Option<String>,
+    pub modification_time: Option<String>,
+    pub revision: Option<FileRevision>,
+    pub home_url: Option<String>
+}
+
+/// The `[Device]` section.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdsDevice {
+    pub vendor_id: u16,
+    pub vendor_name: Option<String>,
+    pub device_type: u16,
+    pub device_type_name: Option<String>,
+    pub product_code: u16,
+    pub major_revision: u8,
+    pub minor_revision: u8,
+    pub product_name: String,
+    pub catalog: Option<String>,
+    pub icon: Option<String>
+}
+
+/// A `ParamN` entry of `[Params]`, with the matching `EnumN` of `[EnumPar]` if there is one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EdsParam {
+    pub number: u32,
+    pub link_path: Vec<u8>,
+    pub descriptor: ParameterDescriptor,
+    pub data_type: CipDataType,
+    pub data_size: u8,
+    pub name: String,
+    pub units: String,
+    pub help: String,
+    pub minimum: Option<CipValue>,
+    pub maximum: Option<CipValue>,
+    pub default: Option<CipValue>,
+    pub scaling: Option<Scaling>,
+    pub enumeration: Vec<(i64, String)>
+}
+
+/// One member of an assembly: its size in bits and what it holds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdsAssemblyMember {
+    pub size: u16,
+    pub reference: EdsReference
+}
+
+/// An `AssemN` entry of `[Assembly]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdsAssembly {
+    pub number: u32,
+    pub name: String,
+    pub path: Vec<EdsPathItem>,
+    /// Size in bytes, when the entry states it.
+    pub size: Option<u16>,
+    pub descriptor: u16,
+    pub members: Vec<EdsAssemblyMember>
+}
+
+impl EdsAssembly {
+    /// Stated size, or the size of all members rounded up to bytes.
+    pub fn byte_size(&self) -> u16 {
+        match self.size {
+            Some(size) => size,
+            None => self.members.iter().map(|member| member.size as u32).sum::<u32>().div_ceil(8) as u16
+        }
+    }
+}
+
+/// A `ConnectionN` entry of `[Connection Manager]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdsConnection {
+    pub number: u32,
+    pub trigger_transport: u32,
+    pub connection_parameters: u32,
+    pub ot_rpi: EdsReference,
+    pub ot_size: EdsReference,
+    pub ot_format: EdsReference,
+    pub to_rpi: EdsReference,
+    pub to_size: EdsReference,
+    pub to_format: EdsReference,
+    pub config1_size: EdsReference,
+    pub config1_format: EdsReference,
+    pub config2_size: EdsReference,
+    pub config2_format: EdsReference,
+    pub name: String,
+    pub help: String,
+    pub path: Vec<EdsPathItem>
+}
+
+/// A parsed EDS file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Eds {
+    pub sections: Vec<EdsSection>
+}
+
+impl Eds {
+    pub fn parse(text: &str) -> Result<Self, CipError> {
+        let mut parser = Parser { chars: text.chars().peekable(), line: 1 };
+        let mut sections: Vec<EdsSection> = Vec::new();
+
+        loop {
+            parser.skip_blank();
+            let line = parser.line;
+            match parser.chars.peek() {
+                None => break,
+                Some('[') => {
+                    parser.next();
+                    let name = parser.take_until(']', "section name is not closed by ']'")?;
+                    sections.push(EdsSection { name: name.trim().into(), entries: Vec::new(), line });
+                }
+                Some(_) => {
+                    let keyword = parser.take_until('=', "expected '=' after the keyword")?;
+                    if keyword.trim().is_empty() {
+                        return Err(CipError::Eds { line, reason: "entry without a keyword" });
+                    }
+                    let fields = parser.fields()?;
+
+                    let section = sections.last_mut().ok_or(CipError::Eds { line, reason: "entry outside of a section" })?;
+                    section.entries.push(EdsEntry { keyword: keyword.trim().into(), fields, line });
+                }
+            }
+        }
+
+        return Ok(Eds { sections });
+    }
+
+    /// Looks up a section; names are not case sensitive.
+    pub fn section(&self, name: &str) -> Option<&EdsSection> {
+        self.sections.iter().find(|section| section.name.eq_ignore_ascii_case(name))
+    }
+
+    fn required_section(&self, name: &str, reason: &'static str) -> Result<&EdsSection, CipError> {
+        return self.section(name).ok_or(CipError::Eds { line: 0, reason });
+    }
+
+    pub fn file_info(&self) -> Result<EdsFileInfo, CipError> {
+        let section = self.required_section("File", "missing [File] section")?;
+
+        let mut revision = None;
+        if let Some(entry) = section.entry("Revision") {
+            let text = entry.field(0).as_str().unwrap_or_default();
+            let (major, minor) = text.split_once('.').ok_or(entry.error("revision is not major.minor"))?;
+            let major = major.parse().map_err(|_| entry.error("revision is not major.minor"))?;
+            let minor = minor.parse().map_err(|_| entry.error("revision is not major.minor"))?;
+            revision = Some(FileRevision { major, minor });
+        }
+
+        return Ok(EdsFileInfo { description: section.string("DescText"), create_date: section.string("CreateDate"), create_time: section.string("CreateTime"), modification_date: section.string("ModDate"), modification_time: section.string("ModTime"), revision, home_url: section.string("HomeURL") });
+    }
+
+    pub fn device(&self) -> Result<EdsDevice, CipError> {
+        let section = self.required_section("Device", "missing [Device] section")?;
+
+        let vendor_id = section.required("VendCode", "missing VendCode")?.required(0)?;
+        let device_type = section.required("ProdType", "missing ProdType")?.required(0)?;
+        let product_code = section.required("ProdCode", "missing ProdCode")?.required(0)?;
+        let major_revision = section.required("MajRev", "missing MajRev")?.required(0)?;
+        let minor_revision = section.required("MinRev", "missing MinRev")?.required(0)?;
+        let product_name = section.required("ProdName", "missing ProdName")?.string(0);
+
+        return Ok(EdsDevice { vendor_id, vendor_name: section.string("VendName"), device_type, device_type_name: section.string("ProdTypeStr"), product_code, major_revision, minor_revision, product_name, catalog: section.string("Catalog"), icon: section.string("Icon") });
+    }
+
+    /// Every `ParamN` entry of `[Params]`, in file order.
+    pub fn params(&self) -> Result<Vec<EdsParam>, CipError> {
+        let section = match self.section("Params") {
+            Some(section) => section,
+            None => return Ok(Vec::new())
+        };
+
+        let mut params = Vec::new();
+        for entry in &section.entries {
+            if let Some(number) = entry.numbered("Param") {
+                let enumeration = match self.enumeration_entry(number).or_else(|| section.entries.iter().find(|candidate| candidate.numbered("Enum") == Some(number))) {
+                    Some(entry) => parse_enumeration(entry)?,
+                    None => Vec::new()
+                };
+                params.push(parse_param(number, entry, enumeration)?);
+            }
+        }
+
+        return Ok(params);
+    }
+
+    /// `EnumN` belongs in `[EnumPar]`, though some files put it in `[Params]`.
+    fn enumeration_entry(&self, number: u32) -> Option<&EdsEntry> {
+        return self.section("EnumPar")?.entries.iter().find(|entry| entry.numbered("Enum") == Some(number));
+    }
+
+    pub fn param(&self, number: u32) -> Result<Option<EdsParam>, CipError> {
+        return Ok(self.params()?.into_iter().find(|param| param.number == number));
+    }
+
+    /// Every `AssemN` entry of `[Assembly]`, in file order.
+    pub fn assemblies(&self) -> Result<Vec<EdsAssembly>, CipError> {
+        let section = match self.section("Assembly") {
+            Some(section) => section,
+            None => return Ok(Vec::new())
+        };
+
+        let mut assemblies = Vec::new();
+        for entry in &section.entries {
+            if let Some(number) = entry.numbered("Assem") {
+                let path = match entry.field(1) {
+                    EdsField::Empty => Vec::new(),
+                    field => field.as_str().and_then(parse_path).ok_or(entry.error("invalid assembly path"))?
+                };
+
+                let mut members = Vec::new();
+                let mut index = 6;
+                while index < entry.fields.len() {
+                    let size = entry.integer(index)?.unwrap_or(0);
+                    members.push(EdsAssemblyMember { size, reference: entry.reference(index + 1)? });
+                    index += 2;
+                }
+
+                assemblies.push(EdsAssembly { number, name: entry.string(0), path, size: entry.integer(2)?, descriptor: entry.integer(3)?.unwrap_or(0), members });
+            }
+        }
+
+        return Ok(assemblies);
+    }
+
+    pub fn assembly(&self, number: u32) -> Result<Option<EdsAssembly>, CipError> {
+        return Ok(self.assemblies()?.into_iter().find(|assembly| assembly.number == number));
+    }
+
+    /// Every `ConnectionN` entry of `[Connection Manager]`, in file order.
+    pub fn connections(&self) -> Result<Vec<EdsConnection>, CipError> {
+        let section = match self.section("Connection Manager") {
+            Some(section) => section,
+            None => return Ok(Vec::new())
+        };
+
+        let mut connections = Vec::new();
+        for entry in &section.entries {
+            if let Some(number) = entry.numbered("Connection") {
+                let path = entry.field(14).as_str().map(parse_path).unwrap_or(Some(Vec::new())).ok_or(entry.error("invalid connection path"))?;
+
+                connections.push(EdsConnection {
+                    number,
+                    trigger_transport: entry.required(0)?,
+                    connection_parameters: entry.required(1)?,
+                    ot_rpi: entry.reference(2)?,
+                    ot_size: entry.reference(3)?,
+                    ot_format: entry.reference(4)?,
+                    to_rpi: entry.reference(5)?,
+                    to_size: entry.reference(6)?,
+                    to_format: entry.reference(7)?,
+                    config1_size: entry.reference(8)?,
+                    config1_format: entry.reference(9)?,
+                    config2_size: entry.reference(10)?,
+                    config2_format: entry.reference(11)?,
+                    name: entry.string(12),
+                    help: entry.string(13),
+                    path
+                });
+            }
+        }
+
+        return Ok(connections);
+    }
+
+    pub fn connection(&self, number: u32) -> Result<Option<EdsConnection>, CipError> {
+        return Ok(self.connections()?.into_iter().find(|connection| connection.number == number));
+    }
+}
+
+fn parse_param(number: u32, entry: &EdsEntry, enumeration: Vec<(i64, String)>) -> Result<EdsParam, CipError> {
+    let link_path = match entry.field(2) {
+        EdsField::Empty => Vec::new(),
+        field => field.as_str().and_then(parse_bytes).ok_or(entry.error("invalid link path"))?
+    };
+    let descriptor = ParameterDescriptor(entry.integer(3)?.unwrap_or(0));
+    let data_type = CipDataType::try_from(entry.required::<u16>(4)?).map_err(|_| entry.error("unknown data type"))?;
+    let data_size = entry.required(5)?;
+
+    let minimum = typed_value(entry, 9, data_type)?;
+    let maximum = typed_value(entry, 10, data_type)?;
+    let default = typed_value(entry, 11, data_type)?;
+
+    let mut scaling = None;
+    if descriptor.scaling() {
+        scaling = Some(Scaling { multiplier: entry.integer(12)?.unwrap_or(1), divisor: entry.integer(13)?.unwrap_or(1), base: entry.integer(14)?.unwrap_or(1), offset: entry.integer(15)?.unwrap_or(0), decimal_precision: entry.integer(20)?.unwrap_or(0) });
+    }
+
+    return Ok(EdsParam { number, link_path, descriptor, data_type, data_size, name: entry.string(6), units: entry.string(7), help: entry.string(8), minimum, maximum, default, scaling, enumeration });
+}
+
+/// `EnumN = value, "text", value, "text", ...;`
+fn parse_enumeration(entry: &EdsEntry) -> Result<Vec<(i64, String)>, CipError> {
+    let mut enumeration = Vec::new();
+    for pair in entry.fields.chunks(2) {
+        let value = pair[0].as_number().ok_or(entry.error("expected an enumeration value"))?;
+        let text = pair.get(1).and_then(EdsField::as_str).ok_or(entry.error("expected an enumeration string"))?;
+        enumeration.push((value, text.into()));
+    }
+
+    return Ok(enumeration);
+}
+
+/// Reads a minimum, maximum or default value as `data_type`.
+fn typed_value(entry: &EdsEntry, index: usize, data_type: CipDataType) -> Result<Option<CipValue>, CipError> {
+    let field = entry.field(index);
+    let text = match field.as_str() {
+        Some(text) => text,
+        None => return Ok(None)
+    };
+
+    let value = match data_type {
+        CipDataType::Real => text.parse().ok().map(CipValue::Real),
+        CipDataType::Lreal => text.parse().ok().map(CipValue::Lreal),
+        CipDataType::ShortString => Some(CipValue::ShortString(text.into())),
+        CipDataType::String => Some(CipValue::String(text.into())),
+        data_type => match (field.as_number(), data_type) {
+            (Some(number), CipDataType::Bool) => Some(CipValue::Bool(number != 0)),
+            (Some(number), CipDataType::Sint) => number.try_into().ok().map(CipValue::Sint),
+            (Some(number), CipDataType::Int) => number.try_into().ok().map(CipValue::Int),
+            (Some(number), CipDataType::Dint) => number.try_into().ok().map(CipValue::Dint),
+            (Some(number), CipDataType::Lint) => Some(CipValue::Lint(number)),
+            (Some(number), CipDataType::Usint) => number.try_into().ok().map(CipValue::Usint),
+            (Some(number), CipDataType::Uint) => number.try_into().ok().map(CipValue::Uint),
+            (Some(number), CipDataType::Udint) => number.try_into().ok().map(CipValue::Udint),
+            (Some(number), CipDataType::Ulint) => number.try_into().ok().map(CipValue::Ulint),
+            (Some(number), CipDataType::Byte) => number.try_into().ok().map(CipValue::Byte),
+            (Some(number), CipDataType::Word) => number.try_into().ok().map(CipValue::Word),
+            (Some(number), CipDataType::Dword) => number.try_into().ok().map(CipValue::Dword),
+            (Some(number), CipDataType::Lword) => number.try_into().ok().map(CipValue::Lword),
+            // Values of other types are kept as they are in the file.
+            _ => return Ok(None)
+        }
+    };
+
+    return value.map(Some).ok_or(entry.error("value does not fit the parameter data type"));
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+        }
+
+        return Some(c);
+    }
+
+    fn skip_comment(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c == '\n' {
+                return;
+            }
+            self.next();
+        }
+    }
+
+    /// Skips whitespace and comments.
+    fn skip_blank(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c == '$' {
+                self.skip_comment();
+            } else if c.is_whitespace() {
+                self.next();
+            } else {
+                return;
+            }
+        }
+    }
+
+    /// Reads up to `end` on the current line.
+    fn take_until(&mut self, end: char, reason: &'static str) -> Result<String, CipError> {
+        let mut text = String::new();
+        loop {
+            match self.chars.peek() {
+                Some(&c) if c == end => {
+                    self.next();
+                    return Ok(text);
+                }
+                Some(&c) if c != '\n' && c != ';' && c != '$' => {
+                    text.push(c);
+                    self.next();
+                }
+                _ => return Err(CipError::Eds { line: self.line, reason })
+            }
+        }
+    }
+
+    /// Reads a quoted string after its opening quote.
+    fn string(&mut self) -> Result<String, CipError> {
+        let line = self.line;
+        let mut text = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(text),
+                Some('\\') => match self.next() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some(c) if c != '\n' => text.push(c),
+                    _ => return Err(CipError::Eds { line, reason: "unterminated string" })
+                },
+                Some('\n') | None => return Err(CipError::Eds { line, reason: "unterminated string" }),
+                Some(c) => text.push(c)
+            }
+        }
+    }
+
+    /// Reads the fields of an entry up to and including the closing ';'.
+    fn fields(&mut self) -> Result<Vec<EdsField>, CipError> {
+        let mut fields = Vec::new();
+        let mut text = String::new();
+        let mut quoted: Option<String> = None;
+
+        loop {
+            match self.next() {
+                None => return Err(CipError::Eds { line: self.line, reason: "entry is not terminated by ';'" }),
+                Some('$') => self.skip_comment(),
+                Some('"') => {
+                    if !text.trim().is_empty() {
+                        return Err(CipError::Eds { line: self.line, reason: "quoted string after unquoted text" });
+                    }
+                    let string = self.string()?;
+                    quoted.get_or_insert_with(String::new).push_str(&string);
+                }
+                Some(end @ (',' | ';')) => {
+                    let field = match quoted.take() {
+                        Some(string) => EdsField::Quoted(string),
+                        None if text.trim().is_empty() => EdsField::Empty,
+                        None => EdsField::Text(text.trim().into())
+                    };
+                    fields.push(field);
+                    text.clear();
+
+                    if end == ';' {
+                        return Ok(fields);
+                    }
+                }
+                Some(c) => {
+                    if quoted.is_some() && !c.is_whitespace() {
+                        return Err(CipError::Eds { line: self.line, reason: "unquoted text after a quoted string" });
+                    }
+                    text.push(c);
+                }
+            }
+        }
+    }
+}
+
+/// Choices an EDS connection entry leaves to the originator.
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionOptions {
+    /// Packet interval for both directions in microseconds, instead of the EDS default.
+    pub rpi: Option<u32>,
+    /// Configuration data, instead of the defaults of the configuration assembly.
+    pub configuration: Option<Vec<u8>>,
+    /// Parameter values to use instead of the defaults in the EDS.
+    pub params: Vec<(u32, CipValue)>,
+    /// Leaves out the electronic key built from `[Device]`.
+    pub skip_electronic_key: bool,
+    pub connection_timeout_multiplier: u8
+}
+
+/// Trigger and transport bits of a connection entry.
+const TRIGGER_CYCLIC: u32 = 1 << 16;
+const TRIGGER_CHANGE_OF_STATE: u32 = 1 << 17;
+const TRIGGER_APPLICATION: u32 = 1 << 18;
+const DIRECTION_SERVER: u32 = 1 << 31;
+
+/// Real time format of a connection that carries the 32-bit run/idle header.
+const RUN_IDLE_HEADER_FORMAT: u32 = 4;
+const RUN_IDLE_HEADER_SIZE: u32 = 4;
+const SEQUENCE_COUNT_SIZE: u32 = 2;
+
+/// Largest connection size of a (not Large) Forward Open.
+const MAX_CONNECTION_SIZE: u32 = 0x1FF;
+
+/// Forward Open network parameters from the size, size types, priorities
+/// and connection types the EDS allows for one direction.
+fn network_parameters(size: u32, size_types: u32, priorities: u32, connection_types: u32, prefer_multicast: bool) -> Result<u16, CipError> {
+    if size > MAX_CONNECTION_SIZE {
+        return Err(CipError::InvalidValue("connection size needs a Large Forward Open"));
+    }
+
+    // Fixed size unless only variable size is offered.
+    let variable = size_types == 0b10;
+    let priority = match priorities {
+        p if p & 0b0100 != 0 => 2,
+        p if p & 0b0010 != 0 => 1,
+        p if p & 0b1000 != 0 => 3,
+        _ => 0
+    };
+    let connection_type = match connection_types {
+        t if prefer_multicast && t & 0b010 != 0 => 1,
+        t if t & 0b100 != 0 => 2,
+        t if t & 0b010 != 0 => 1,
+        _ => 0
+    };
+
+    return Ok(size as u16 | (variable as u16) << 9 | priority << 10 | connection_type << 13);
+}
+
+impl Eds {
+    fn param_value(&self, number: u32, options: &ConnectionOptions) -> Result<CipValue, CipError> {
+        if let Some((_, value)) = options.params.iter().find(|(param, _)| *param == number) {
+            return Ok(value.clone());
+        }
+
+        let param = self.param(number)?.ok_or(CipError::InvalidValue("EDS refers to a missing parameter"))?;
+        return param.default.ok_or(CipError::InvalidValue("EDS parameter has no default value"));
+    }
+
+    fn reference_number(&self, reference: &EdsReference, options: &ConnectionOptions) -> Result<Option<u32>, CipError> {
+        match reference {
+            EdsReference::None => Ok(None),
+            EdsReference::Value(value) => u32::try_from(*value).map(Some).map_err(|_| CipError::InvalidValue("negative size or interval in EDS")),
+            EdsReference::Param(number) => match self.param_value(*number, options)?.as_f64() {
+                Some(value) if value >= 0.0 => Ok(Some(value as u32)),
+                _ => Err(CipError::InvalidValue("EDS parameter is not a size or interval"))
+            },
+            EdsReference::Assembly(_) | EdsReference::Path(_) => Err(CipError::InvalidValue("expected a size or interval"))
+        }
+    }
+
+    /// Requested packet interval, checked against the limits of its parameter.
+    fn rpi(&self, reference: &EdsReference, options: &ConnectionOptions) -> Result<u32, CipError> {
+        let rpi = match options.rpi {
+            Some(rpi) => rpi,
+            None => self.reference_number(reference, options)?.ok_or(CipError::InvalidValue("connection has no default RPI"))?
+        };
+
+        if let EdsReference::Param(number) = reference {
+            if let Some(param) = self.param(*number)? {
+                let below = param.minimum.and_then(|minimum| minimum.as_f64()).is_some_and(|minimum| (rpi as f64) < minimum);
+                let above = param.maximum.and_then(|maximum| maximum.as_f64()).is_some_and(|maximum| (rpi as f64) > maximum);
+                if below || above {
+                    return Err(CipError::InvalidValue("RPI is outside the limits in the EDS"));
+                }
+            }
+        }
+
+        return Ok(rpi);
+    }
+
+    /// Data size of one direction or configuration part: stated, or the size of its assembly.
+    fn data_size(&self, size: &EdsReference, format: &EdsReference, options: &ConnectionOptions) -> Result<u32, CipError> {
+        if let Some(size) = self.reference_number(size, options)? {
+            return Ok(size);
+        }
+
+        match format {
+            EdsReference::Assembly(number) => Ok(self.assembly(*number)?.ok_or(CipError::InvalidValue("EDS refers to a missing assembly"))?.byte_size() as u32),
+            _ => Ok(0)
+        }
+    }
+
+    /// Default data of a configuration part, built from the parameters of its assembly.
+    fn configuration_part(&self, size: &EdsReference, format: &EdsReference, options: &ConnectionOptions) -> Result<Vec<u8>, CipError> {
+        let size = self.data_size(size, format, options)? as usize;
+
+        let mut data = Vec::new();
+        if let EdsReference::Assembly(number) = format {
+            let assembly = self.assembly(*number)?.ok_or(CipError::InvalidValue("EDS refers to a missing assembly"))?;
+            for member in &assembly.members {
+                if member.size % 8 != 0 {
+                    return Err(CipError::InvalidValue("configuration members must be whole bytes"));
+                }
+
+                let mut value = match &member.reference {
+                    EdsReference::Param(number) => self.param_value(*number, options)?.encode(),
+                    _ => Vec::new()
+                };
+                value.resize(member.size as usize / 8, 0);
+                data.extend(value);
+            }
+        }
+
+        data.resize(size, 0);
+        return Ok(data);
+    }
+
+    /// Replaces parameter references in a path with the parameter values.
+    fn resolve_path(&self, path: &[EdsPathItem], options: &ConnectionOptions) -> Result<Vec<u8>, CipError> {
+        let mut bytes = Vec::new();
+        for item in path {
+            match item {
+                EdsPathItem::Byte(byte) => bytes.push(*byte),
+                EdsPathItem::Param(number) => {
+                    let mut value = self.param_value(*number, options)?.encode();
+                    if let Some(param) = self.param(*number)? {
+                        value.truncate(param.data_size as usize);
+                    }
+                    bytes.extend(value);
+                }
+            }
+        }
+
+        return Ok(bytes);
+    }
+
+    /// Builds the Forward Open for `ConnectionN`: transport and trigger, intervals,
+    /// network parameters, electronic key and the path with configuration data.
+    pub fn forward_open(&self, connection: u32, options: &ConnectionOptions) -> Result<ForwardOpenRequest, CipError> {
+        let entry = self.connection(connection)?.ok_or(CipError::InvalidValue("EDS has no such connection"))?;
+        let flags = entry.trigger_transport;
+        let parameters = entry.connection_parameters;
+
+        let transport_class = match flags & 0xFFFF {
+            classes if classes & 0b0010 != 0 => 1,
+            classes if classes & 0b0001 != 0 => 0,
+            classes if classes & 0b1000 != 0 => 3,
+            classes if classes & 0b0100 != 0 => 2,
+            _ => return Err(CipError::InvalidValue("connection offers no transport class"))
+        };
+        let trigger = match flags {
+            flags if flags & TRIGGER_CYCLIC != 0 => 0,
+            flags if flags & TRIGGER_CHANGE_OF_STATE != 0 => 1,
+            flags if flags & TRIGGER_APPLICATION != 0 => 2,
+            _ => 0
+        };
+        let direction = (flags & DIRECTION_SERVER != 0) as u8;
+
+        let mut ot_size = self.data_size(&entry.ot_size, &entry.ot_format, options)?;
+        let mut to_size = self.data_size(&entry.to_size, &entry.to_format, options)?;
+        if (parameters >> 8) & 0xF == RUN_IDLE_HEADER_FORMAT {
+            ot_size += RUN_IDLE_HEADER_SIZE;
+        }
+        if (parameters >> 12) & 0xF == RUN_IDLE_HEADER_FORMAT {
+            to_size += RUN_IDLE_HEADER_SIZE;
+        }
+        if transport_class == 1 {
+            ot_size += SEQUENCE_COUNT_SIZE;
+            to_size += SEQUENCE_COUNT_SIZE;
+        }
+
+        let mut connection_path = EPath::new();
+        if !options.skip_electronic_key {
+            let device = self.device()?;
+            connection_path.attributes.push(Box::new(ElectronicKeySegment { vendor_id: device.vendor_id, device_type: device.device_type, product_code: device.product_code, compatibility: true, major_revision: device.major_revision, minor_revision: device.minor_revision }));
+        }
+
+        let (_, path) = EPath::parse(&self.resolve_path(&entry.path, options)?)?;
+        connection_path.attributes.extend(path.attributes);
+
+        let configuration = match &options.configuration {
+            Some(configuration) => configuration.clone(),
+            None => {
+                let mut configuration = self.configuration_part(&entry.config1_size, &entry.config1_format, options)?;
+                configuration.extend(self.configuration_part(&entry.config2_size, &entry.config2_format, options)?);
+                configuration
+            }
+        };
+        if !configuration.is_empty() {
+            connection_path.attributes.push(Box::new(DataSegment::new(configuration)?));
+        }
+
+        let mut request = ForwardOpenRequest::create_null_forward_open(connection_path);
+        request.to_network_connection_id = rand::random();
+        request.connection_timeout_multiplier = options.connection_timeout_multiplier;
+        request.ot_rpi = self.rpi(&entry.ot_rpi, options)?;
+        request.to_rpi = self.rpi(&entry.to_rpi, options)?;
+        request.ot_network_parameters = network_parameters(ot_size, parameters & 0b11, (parameters >> 24) & 0xF, (parameters >> 16) & 0xF, false)?;
+        request.to_network_parameters = network_parameters(to_size, (parameters >> 2) & 0b11, (parameters >> 28) & 0xF, (parameters >> 20) & 0xF, true)?;
+        request.transport_class = direction << 7 | trigger << 4 | transport_class;
+
+        return Ok(request);
+    }
+}
+
+impl CipClient {
+    /// Opens `ConnectionN` of an EDS.
+    pub async fn open_eds_connection(&mut self, eds: &Eds, connection: u32, options: &ConnectionOptions) -> Result<ForwardOpenResponse, CipError> {
+        let request = eds.forward_open(connection, options)?;
+        return self.forward_open(&request).await;
+    }
+}
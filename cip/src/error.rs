@@ -0,0 +1,131 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt;
+
+/// Status code carried in the encapsulation header of a reply.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncapsulationStatus {
+    Success,
+    InvalidCommand,
+    InsufficientMemory,
+    IncorrectData,
+    InvalidSessionHandle,
+    InvalidLength,
+    UnsupportedProtocolRevision,
+    Unknown(u32)
+}
+
+impl EncapsulationStatus {
+    pub fn code(&self) -> u32 {
+        match self {
+            EncapsulationStatus::Success => 0x0000,
+            EncapsulationStatus::InvalidCommand => 0x0001,
+            EncapsulationStatus::InsufficientMemory => 0x0002,
+            EncapsulationStatus::IncorrectData => 0x0003,
+            EncapsulationStatus::InvalidSessionHandle => 0x0064,
+            EncapsulationStatus::InvalidLength => 0x0065,
+            EncapsulationStatus::UnsupportedProtocolRevision => 0x0069,
+            EncapsulationStatus::Unknown(code) => *code
+        }
+    }
+}
+
+impl From<u32> for EncapsulationStatus {
+    fn from(code: u32) -> Self {
+        match code {
+            0x0000 => EncapsulationStatus::Success,
+            0x0001 => EncapsulationStatus::InvalidCommand,
+            0x0002 => EncapsulationStatus::InsufficientMemory,
+            0x0003 => EncapsulationStatus::IncorrectData,
+            0x0064 => EncapsulationStatus::InvalidSessionHandle,
+            0x0065 => EncapsulationStatus::InvalidLength,
+            0x0069 => EncapsulationStatus::UnsupportedProtocolRevision,
+            code => EncapsulationStatus::Unknown(code)
+        }
+    }
+}
+
+/// Electronic key field that does not match the device, in the order the target checks them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyMismatch {
+    VendorOrProduct,
+    DeviceType,
+    Revision
+}
+
+impl KeyMismatch {
+    /// Extended status a target returns with general status 0x01 for this mismatch.
+    pub fn extended_status(&self) -> u16 {
+        match self {
+            KeyMismatch::VendorOrProduct => 0x0114,
+            KeyMismatch::DeviceType => 0x0115,
+            KeyMismatch::Revision => 0x0116
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CipError {
+    /// The underlying transport (socket, stream, ...) reported an error.
+    Transport(Box<dyn core::error::Error + Send + Sync>),
+    /// The remote end closed the connection.
+    ConnectionClosed,
+    /// An encapsulation packet announced more data than we are willing to buffer.
+    PacketTooLarge(usize),
+    /// A received packet could not be parsed.
+    Parse,
+    /// No reply arrived within the request timeout.
+    Timeout,
+    /// The target answered with a non-zero encapsulation status.
+    Encapsulation(EncapsulationStatus),
+    /// The reply carried a different encapsulation command than the request.
+    UnexpectedCommand(u16),
+    /// The target does not speak our encapsulation protocol version; carries the version it offered.
+    UnsupportedProtocolVersion(u16),
+    /// A reply carried a session handle other than the one registered for this connection.
+    SessionMismatch { expected: u32, received: u32 },
+    /// A reply was well formed but its contents are not valid for the request.
+    InvalidReply,
+    /// The target answered a CIP service with a non-zero general status.
+    Service { general_status: u8, additional_status: Vec<u16> },
+    /// A value was rejected before it was sent to the target.
+    InvalidValue(&'static str),
+    /// A configuration change was not confirmed and has not been sent.
+    NotConfirmed,
+    /// Transferred data does not match the checksum sent with it.
+    ChecksumMismatch { expected: u16, computed: u16 },
+    /// An EDS file could not be parsed.
+    Eds { line: usize, reason: &'static str },
+    /// The device identity does not match the expected electronic key.
+    KeyMismatch(KeyMismatch),
+}
+
+impl fmt::Display for CipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CipError::Transport(e) => write!(f, "transport error: {}", e),
+            CipError::ConnectionClosed => write!(f, "connection closed by peer"),
+            CipError::PacketTooLarge(length) => write!(f, "packet length {} exceeds the maximum", length),
+            CipError::Parse => write!(f, "malformed packet"),
+            CipError::Timeout => write!(f, "request timed out"),
+            CipError::Encapsulation(status) => write!(f, "encapsulation error {:?} ({:#06x})", status, status.code()),
+            CipError::UnexpectedCommand(command) => write!(f, "unexpected encapsulation command {:#06x}", command),
+            CipError::UnsupportedProtocolVersion(version) => write!(f, "target only supports encapsulation protocol version {}", version),
+            CipError::SessionMismatch { expected, received } => write!(f, "reply for session {:#010x} on session {:#010x}", received, expected),
+            CipError::InvalidReply => write!(f, "invalid reply"),
+            CipError::Service { general_status, additional_status } => write!(f, "service failed with general status {:#04x} {:?}", general_status, additional_status),
+            CipError::InvalidValue(reason) => write!(f, "invalid value: {}", reason),
+            CipError::NotConfirmed => write!(f, "change was not confirmed"),
+            CipError::ChecksumMismatch { expected, computed } => write!(f, "checksum {:#06x} does not match the data ({:#06x})", expected, computed),
+            CipError::Eds { line, reason } => write!(f, "EDS line {}: {}", line, reason),
+            CipError::KeyMismatch(mismatch) => write!(f, "electronic key mismatch: {:?} ({:#06x})", mismatch, mismatch.extended_status()),
+        }
+    }
+}
+
+impl core::error::Error for CipError {}
+
+impl<E> From<nom::Err<E>> for CipError {
+    fn from(_: nom::Err<E>) -> Self {
+        CipError::Parse
+    }
+}
@@ -3,4 +3,9 @@
 extern crate alloc;
 pub mod common;
 pub mod objects;
-pub mod cip;
\ No newline at end of file
+pub mod cip;
+pub mod error;
+pub mod router;
+pub mod attributes;
+pub mod value;
+pub mod eds;
\ No newline at end of file
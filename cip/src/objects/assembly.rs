@@ -0,0 +1,90 @@
+use alloc::vec::Vec;
+use nom::{bytes::complete::take, error::Error, number::complete::le_u16, sequence::tuple, IResult};
+
+use crate::{cip::{CipClass, CipClient, CipService, EPath}, common::Serializable, error::CipError, objects::optional_attribute};
+
+/// Attribute IDs of an Assembly instance.
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssemblyAttribute {
+    NumberOfMembers = 1,
+    MemberList = 2,
+    Data = 3,
+    Size = 4
+}
+
+/// One entry of the member list, attribute 2.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssemblyMember {
+    /// Size of the member's data in bits.
+    pub data_size: u16,
+    /// Packed EPATH of the member, empty for padding members.
+    pub path: Vec<u8>
+}
+
+impl AssemblyMember {
+    pub fn epath(&self) -> Result<EPath, CipError> {
+        let (_, epath) = EPath::parse(&self.path)?;
+        return Ok(epath);
+    }
+}
+
+impl Serializable for AssemblyMember {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized {
+        let (input, (data_size, path_size)) = tuple((le_u16, le_u16))(input)?;
+        let (input, path) = take(path_size)(input)?;
+
+        return Ok((input, AssemblyMember { data_size, path: path.to_vec() }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        vec.extend_from_slice(&self.data_size.to_le_bytes());
+        vec.extend_from_slice(&(self.path.len() as u16).to_le_bytes());
+        vec.extend_from_slice(&self.path);
+
+        return vec;
+    }
+}
+
+impl CipClient {
+    async fn assembly_request(&mut self, service: CipService, instance: u32, attribute: AssemblyAttribute, data: Vec<u8>) -> Result<Vec<u8>, CipError> {
+        let response = self.attribute_request(service as u8, CipClass::Assembly as u32, instance, attribute as u32, data).await?;
+        response.check()?;
+        return Ok(response.data);
+    }
+
+    /// Reads the data of assembly `instance`.
+    pub async fn get_assembly_data(&mut self, instance: u32) -> Result<Vec<u8>, CipError> {
+        return self.assembly_request(CipService::GetAttributeSingle, instance, AssemblyAttribute::Data, alloc::vec![]).await;
+    }
+
+    /// Writes the data of an output or configuration assembly.
+    /// The device rejects writes that do not match the assembly size.
+    pub async fn set_assembly_data(&mut self, instance: u32, data: &[u8]) -> Result<(), CipError> {
+        self.assembly_request(CipService::SetAttributeSingle, instance, AssemblyAttribute::Data, data.to_vec()).await?;
+        return Ok(());
+    }
+
+    /// Reads the size of assembly `instance` in bytes.
+    pub async fn get_assembly_size(&mut self, instance: u32) -> Result<u16, CipError> {
+        let data = self.assembly_request(CipService::GetAttributeSingle, instance, AssemblyAttribute::Size, alloc::vec![]).await?;
+        let (_, size) = le_u16::<_, Error<&[u8]>>(data.as_slice())?;
+        return Ok(size);
+    }
+
+    /// Reads the member list of assembly `instance`. Assemblies without one return no members.
+    pub async fn get_assembly_members(&mut self, instance: u32) -> Result<Vec<AssemblyMember>, CipError> {
+        let data = optional_attribute(self.assembly_request(CipService::GetAttributeSingle, instance, AssemblyAttribute::MemberList, alloc::vec![]).await)?;
+
+        let mut members = Vec::new();
+        let mut input = data.as_deref().unwrap_or_default();
+        while !input.is_empty() {
+            let (rest, member) = AssemblyMember::deserialize(input)?;
+            members.push(member);
+            input = rest;
+        }
+
+        return Ok(members);
+    }
+}
@@ -1,7 +1,14 @@
 use alloc::vec::Vec;
+use nom::{bytes::complete::take, number::complete::{le_u16, le_u32, le_u8}, sequence::tuple};
 use rand::Rng;
 
-use crate::{cip::{EPath, MessageRouterRequest}, common::Serializable};
+use crate::{cip::{CipClass, CipClient, CipService, EPath, MessageRouterRequest}, common::Serializable, error::CipError};
+
+pub const FORWARD_OPEN: u8 = 0x54;
+pub const GET_CONNECTION_DATA: u8 = 0x56;
+pub const SEARCH_CONNECTION_DATA: u8 = 0x57;
+pub const GET_CONNECTION_OWNER: u8 = 0x5A;
+pub const LARGE_FORWARD_OPEN: u8 = 0x5B;
 
 pub struct ForwardOpenRequest {
     pub priority: u8,
@@ -22,7 +29,13 @@ pub struct ForwardOpenRequest {
 
 impl Serializable for ForwardOpenRequest {
     fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
-        todo!()
+        let (input, (priority, timeout_ticks, ot_network_connection_id, to_network_connection_id, connection_serial_number, original_vendor_id, original_serial_number, connection_timeout_multiplier)) = tuple((le_u8, le_u8, le_u32, le_u32, le_u16, le_u16, le_u32, le_u8))(input)?;
+        let (input, _) = take(3usize)(input)?;
+        let (input, (ot_rpi, ot_network_parameters, to_rpi, to_network_parameters, transport_class, path_size)) = tuple((le_u32, le_u16, le_u32, le_u16, le_u8, le_u8))(input)?;
+        let (input, path) = take(path_size as usize * 2)(input)?;
+        let (_, connection_path) = EPath::parse(path)?;
+
+        return Ok((input, ForwardOpenRequest { priority, timeout_ticks, ot_network_connection_id, to_network_connection_id, connection_serial_number, original_vendor_id, original_serial_number, connection_timeout_multiplier, ot_rpi, ot_network_parameters, to_rpi, to_network_parameters, transport_class, connection_path }));
     }
 
     fn serialize(&self) -> Vec<u8> {
@@ -82,6 +95,49 @@ impl ForwardOpenRequest {
     }
 }
 
+/// Reply of a successful Forward Open.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForwardOpenResponse {
+    pub ot_network_connection_id: u32,
+    pub to_network_connection_id: u32,
+    pub connection_serial_number: u16,
+    pub original_vendor_id: u16,
+    pub original_serial_number: u32,
+    /// Actual packet intervals in microseconds.
+    pub ot_api: u32,
+    pub to_api: u32,
+    pub application_reply: Vec<u8>
+}
+
+impl Serializable for ForwardOpenResponse {
+    fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
+        let (input, (ot_network_connection_id, to_network_connection_id, connection_serial_number, original_vendor_id, original_serial_number, ot_api, to_api)) = tuple((le_u32, le_u32, le_u16, le_u16, le_u32, le_u32, le_u32))(input)?;
+        let (input, (reply_size, _)) = tuple((le_u8, le_u8))(input)?;
+        let (input, application_reply) = take(reply_size as usize * 2)(input)?;
+
+        return Ok((input, ForwardOpenResponse { ot_network_connection_id, to_network_connection_id, connection_serial_number, original_vendor_id, original_serial_number, ot_api, to_api, application_reply: application_reply.to_vec() }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        vec.extend_from_slice(&self.ot_network_connection_id.to_le_bytes());
+        vec.extend_from_slice(&self.to_network_connection_id.to_le_bytes());
+        vec.extend_from_slice(&self.connection_serial_number.to_le_bytes());
+        vec.extend_from_slice(&self.original_vendor_id.to_le_bytes());
+        vec.extend_from_slice(&self.original_serial_number.to_le_bytes());
+        vec.extend_from_slice(&self.ot_api.to_le_bytes());
+        vec.extend_from_slice(&self.to_api.to_le_bytes());
+        vec.push(self.application_reply.len().div_ceil(2) as u8);
+        vec.push(0);
+        vec.extend_from_slice(&self.application_reply);
+        if !self.application_reply.len().is_multiple_of(2) {
+            vec.push(0);
+        }
+
+        return vec;
+    }
+}
+
 pub struct UnconnectedSendRequest {
     pub priority: u8,
     pub timeout_ticks: u8,
@@ -91,7 +147,16 @@ pub struct UnconnectedSendRequest {
 
 impl Serializable for UnconnectedSendRequest {
     fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
-        todo!()
+        let (input, (priority, timeout_ticks, length)) = tuple((le_u8, le_u8, le_u16))(input)?;
+        let (input, embedded_message_request) = take(length)(input)?;
+        let (input, _) = take(length as usize % 2)(input)?;
+        let (_, message_request) = MessageRouterRequest::deserialize(embedded_message_request)?;
+
+        let (input, (path_size, _)) = tuple((le_u8, le_u8))(input)?;
+        let (input, path) = take(path_size as usize * 2)(input)?;
+        let (_, route_path) = EPath::parse(path)?;
+
+        return Ok((input, UnconnectedSendRequest { priority, timeout_ticks, message_request, route_path }));
     }
 
     fn serialize(&self) -> Vec<u8> {
@@ -121,4 +186,224 @@ impl Serializable for UnconnectedSendRequest {
 
         return vec;
     }
-}
\ No newline at end of file
+}
+
+/// State of a connection reported by Get_Connection_Data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    NonExistent,
+    Configuring,
+    WaitingForConnectionId,
+    Established,
+    TimedOut,
+    DeferredDelete,
+    Closing,
+    Other(u16)
+}
+
+impl From<u16> for ConnectionState {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => ConnectionState::NonExistent,
+            1 => ConnectionState::Configuring,
+            2 => ConnectionState::WaitingForConnectionId,
+            3 => ConnectionState::Established,
+            4 => ConnectionState::TimedOut,
+            5 => ConnectionState::DeferredDelete,
+            6 => ConnectionState::Closing,
+            value => ConnectionState::Other(value)
+        }
+    }
+}
+
+/// Reply of Get_Connection_Data and Search_Connection_Data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConnectionData {
+    pub connection_number: u16,
+    pub connection_state: ConnectionState,
+    pub originator_port: u16,
+    pub target_port: u16,
+    pub connection_serial_number: u16,
+    pub originator_vendor_id: u16,
+    pub originator_serial_number: u32,
+    pub originator_ot_connection_id: u32,
+    pub target_ot_connection_id: u32,
+    pub ot_connection_timeout_multiplier: u8,
+    pub originator_ot_rpi: u32,
+    pub originator_ot_api: u32,
+    pub originator_to_connection_id: u32,
+    pub target_to_connection_id: u32,
+    pub to_connection_timeout_multiplier: u8,
+    pub originator_to_rpi: u32,
+    pub originator_to_api: u32
+}
+
+impl Serializable for ConnectionData {
+    fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
+        let (input, (connection_number, connection_state, originator_port, target_port, connection_serial_number, originator_vendor_id, originator_serial_number)) = tuple((le_u16, le_u16, le_u16, le_u16, le_u16, le_u16, le_u32))(input)?;
+        let (input, (originator_ot_connection_id, target_ot_connection_id, ot_connection_timeout_multiplier, _, originator_ot_rpi, originator_ot_api)) = tuple((le_u32, le_u32, le_u8, take(3u8), le_u32, le_u32))(input)?;
+        let (input, (originator_to_connection_id, target_to_connection_id, to_connection_timeout_multiplier, _, originator_to_rpi, originator_to_api)) = tuple((le_u32, le_u32, le_u8, take(3u8), le_u32, le_u32))(input)?;
+
+        return Ok((input, ConnectionData { connection_number, connection_state: ConnectionState::from(connection_state), originator_port, target_port, connection_serial_number, originator_vendor_id, originator_serial_number, originator_ot_connection_id, target_ot_connection_id, ot_connection_timeout_multiplier, originator_ot_rpi, originator_ot_api, originator_to_connection_id, target_to_connection_id, to_connection_timeout_multiplier, originator_to_rpi, originator_to_api }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let state = match self.connection_state {
+            ConnectionState::NonExistent => 0,
+            ConnectionState::Configuring => 1,
+            ConnectionState::WaitingForConnectionId => 2,
+            ConnectionState::Established => 3,
+            ConnectionState::TimedOut => 4,
+            ConnectionState::DeferredDelete => 5,
+            ConnectionState::Closing => 6,
+            ConnectionState::Other(value) => value
+        };
+
+        let mut vec = Vec::new();
+        for value in [self.connection_number, state, self.originator_port, self.target_port, self.connection_serial_number, self.originator_vendor_id] {
+            vec.extend_from_slice(&value.to_le_bytes());
+        }
+        vec.extend_from_slice(&self.originator_serial_number.to_le_bytes());
+        vec.extend_from_slice(&self.originator_ot_connection_id.to_le_bytes());
+        vec.extend_from_slice(&self.target_ot_connection_id.to_le_bytes());
+        vec.push(self.ot_connection_timeout_multiplier);
+        vec.extend_from_slice(&[0; 3]);
+        vec.extend_from_slice(&self.originator_ot_rpi.to_le_bytes());
+        vec.extend_from_slice(&self.originator_ot_api.to_le_bytes());
+        vec.extend_from_slice(&self.originator_to_connection_id.to_le_bytes());
+        vec.extend_from_slice(&self.target_to_connection_id.to_le_bytes());
+        vec.push(self.to_connection_timeout_multiplier);
+        vec.extend_from_slice(&[0; 3]);
+        vec.extend_from_slice(&self.originator_to_rpi.to_le_bytes());
+        vec.extend_from_slice(&self.originator_to_api.to_le_bytes());
+
+        return vec;
+    }
+}
+
+/// Reply of Get_Connection_Owner.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConnectionOwner {
+    /// Connections open to the target path.
+    pub num_connections: u8,
+    /// Connections that claim ownership.
+    pub num_coo_connections: u8,
+    /// Connections that are ready to take ownership.
+    pub num_roo_connections: u8,
+    pub last_action: u8,
+    pub connection_serial_number: u16,
+    pub originator_vendor_id: u16,
+    pub originator_serial_number: u32
+}
+
+impl Serializable for ConnectionOwner {
+    fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
+        let (input, (num_connections, num_coo_connections, num_roo_connections, last_action, connection_serial_number, originator_vendor_id, originator_serial_number)) = tuple((le_u8, le_u8, le_u8, le_u8, le_u16, le_u16, le_u32))(input)?;
+        return Ok((input, ConnectionOwner { num_connections, num_coo_connections, num_roo_connections, last_action, connection_serial_number, originator_vendor_id, originator_serial_number }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = alloc::vec![self.num_connections, self.num_coo_connections, self.num_roo_connections, self.last_action];
+        vec.extend_from_slice(&self.connection_serial_number.to_le_bytes());
+        vec.extend_from_slice(&self.originator_vendor_id.to_le_bytes());
+        vec.extend_from_slice(&self.originator_serial_number.to_le_bytes());
+
+        return vec;
+    }
+}
+
+/// Connection Manager instance attributes 1 to 8.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConnectionManagerCounters {
+    pub open_requests: u16,
+    pub open_format_rejects: u16,
+    pub open_resource_rejects: u16,
+    pub open_other_rejects: u16,
+    pub close_requests: u16,
+    pub close_format_rejects: u16,
+    pub close_other_rejects: u16,
+    pub connection_timeouts: u16
+}
+
+impl Serializable for ConnectionManagerCounters {
+    fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
+        let (input, (open_requests, open_format_rejects, open_resource_rejects, open_other_rejects, close_requests, close_format_rejects, close_other_rejects, connection_timeouts)) = tuple((le_u16, le_u16, le_u16, le_u16, le_u16, le_u16, le_u16, le_u16))(input)?;
+        return Ok((input, ConnectionManagerCounters { open_requests, open_format_rejects, open_resource_rejects, open_other_rejects, close_requests, close_format_rejects, close_other_rejects, connection_timeouts }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        for counter in [self.open_requests, self.open_format_rejects, self.open_resource_rejects, self.open_other_rejects, self.close_requests, self.close_format_rejects, self.close_other_rejects, self.connection_timeouts] {
+            vec.extend_from_slice(&counter.to_le_bytes());
+        }
+
+        return vec;
+    }
+}
+
+impl ConnectionManagerCounters {
+    /// Forward Opens rejected for any reason.
+    pub fn open_rejects(&self) -> u32 {
+        self.open_format_rejects as u32 + self.open_resource_rejects as u32 + self.open_other_rejects as u32
+    }
+}
+
+impl CipClient {
+    async fn connection_manager_request(&mut self, service: u8, data: Vec<u8>) -> Result<Vec<u8>, CipError> {
+        let response = self.call_service(CipClass::ConnectionManager as u32, 1, service, data).await?;
+        response.check()?;
+        return Ok(response.data);
+    }
+
+    /// Opens a connection. A rejected open fails with the extended status in `additional_status`.
+    pub async fn forward_open(&mut self, request: &ForwardOpenRequest) -> Result<ForwardOpenResponse, CipError> {
+        let data = self.connection_manager_request(FORWARD_OPEN, request.serialize()).await?;
+        let (_, response) = ForwardOpenResponse::deserialize(&data)?;
+        return Ok(response);
+    }
+
+    /// Reads the state of connection `connection_number` on the target.
+    pub async fn get_connection_data(&mut self, connection_number: u16) -> Result<ConnectionData, CipError> {
+        let data = self.connection_manager_request(GET_CONNECTION_DATA, connection_number.to_le_bytes().to_vec()).await?;
+        let (_, connection_data) = ConnectionData::deserialize(&data)?;
+        return Ok(connection_data);
+    }
+
+    /// Looks up a connection by the triad its originator opened it with.
+    pub async fn search_connection_data(&mut self, connection_serial_number: u16, originator_vendor_id: u16, originator_serial_number: u32) -> Result<ConnectionData, CipError> {
+        let mut request = Vec::new();
+        request.extend_from_slice(&connection_serial_number.to_le_bytes());
+        request.extend_from_slice(&originator_vendor_id.to_le_bytes());
+        request.extend_from_slice(&originator_serial_number.to_le_bytes());
+
+        let data = self.connection_manager_request(SEARCH_CONNECTION_DATA, request).await?;
+        let (_, connection_data) = ConnectionData::deserialize(&data)?;
+        return Ok(connection_data);
+    }
+
+    /// Asks which connection owns the object at `connection_path`.
+    pub async fn get_connection_owner(&mut self, connection_path: &EPath) -> Result<ConnectionOwner, CipError> {
+        let mut segments = Vec::new();
+        for segment in &connection_path.attributes {
+            segments.extend(segment.as_ref().serialize());
+        }
+
+        if segments.len() % 2 != 0 {
+            return Err(CipError::InvalidValue("connection path is not padded to 16-bit words"));
+        }
+
+        let mut request = alloc::vec![0, (segments.len() / 2) as u8];
+        request.extend(segments);
+
+        let data = self.connection_manager_request(GET_CONNECTION_OWNER, request).await?;
+        let (_, owner) = ConnectionOwner::deserialize(&data)?;
+        return Ok(owner);
+    }
+
+    /// Reads the Connection Manager's open, close and timeout counters.
+    pub async fn get_connection_manager_counters(&mut self) -> Result<ConnectionManagerCounters, CipError> {
+        let data = self.connection_manager_request(CipService::GetAttributesAll as u8, Vec::new()).await?;
+        let (_, counters) = ConnectionManagerCounters::deserialize(&data)?;
+        return Ok(counters);
+    }
+}
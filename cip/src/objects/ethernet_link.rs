@@ -0,0 +1,490 @@
+use alloc::{string::String, vec::Vec};
+use nom::{bytes::complete::take, error::Error, multi::count, number::complete::{le_u16, le_u32, le_u8}, sequence::tuple, IResult};
+
+use crate::{attributes::{Attribute, AttributeObject, AttributeTable}, cip::{CipClass, CipClient, CipDataType, CipService}, common::Serializable, error::CipError, objects::{message_router::MessageRouterResponseStatusCodes, optional_attribute}, router::{CipObject, ObjectPath, ServiceResult}};
+
+/// Class specific service that returns a counter attribute and then resets it.
+pub const GET_AND_CLEAR: u8 = 0x4C;
+
+/// Attribute IDs of an Ethernet Link instance.
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EthernetLinkAttribute {
+    InterfaceSpeed = 1,
+    InterfaceFlags = 2,
+    PhysicalAddress = 3,
+    InterfaceCounters = 4,
+    MediaCounters = 5,
+    InterfaceControl = 6,
+    InterfaceType = 7,
+    InterfaceState = 8,
+    AdminState = 9,
+    InterfaceLabel = 10,
+    InterfaceCapability = 11
+}
+
+/// Result of the speed and duplex auto-negotiation, bits 2 to 4 of the interface flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NegotiationStatus {
+    InProgress,
+    /// Auto-negotiation failed, the default speed and duplex are used.
+    Failed,
+    /// Speed was detected but duplex negotiation failed.
+    DuplexFailed,
+    Successful,
+    NotAttempted,
+    Other(u8)
+}
+
+/// Attribute 2.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InterfaceFlags(pub u32);
+
+impl InterfaceFlags {
+    pub fn link_active(&self) -> bool {
+        self.0 & 0x01 != 0
+    }
+
+    pub fn full_duplex(&self) -> bool {
+        self.0 & 0x02 != 0
+    }
+
+    pub fn negotiation_status(&self) -> NegotiationStatus {
+        match (self.0 >> 2) & 0x07 {
+            0 => NegotiationStatus::InProgress,
+            1 => NegotiationStatus::Failed,
+            2 => NegotiationStatus::DuplexFailed,
+            3 => NegotiationStatus::Successful,
+            4 => NegotiationStatus::NotAttempted,
+            other => NegotiationStatus::Other(other as u8)
+        }
+    }
+
+    /// A changed interface control only takes effect after a reset.
+    pub fn manual_setting_requires_reset(&self) -> bool {
+        self.0 & 0x20 != 0
+    }
+
+    pub fn local_hardware_fault(&self) -> bool {
+        self.0 & 0x40 != 0
+    }
+}
+
+/// Attribute 4.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InterfaceCounters {
+    pub in_octets: u32,
+    pub in_ucast_packets: u32,
+    pub in_nucast_packets: u32,
+    pub in_discards: u32,
+    pub in_errors: u32,
+    pub in_unknown_protos: u32,
+    pub out_octets: u32,
+    pub out_ucast_packets: u32,
+    pub out_nucast_packets: u32,
+    pub out_discards: u32,
+    pub out_errors: u32
+}
+
+impl Serializable for InterfaceCounters {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized {
+        let (input, c) = count(le_u32, 11)(input)?;
+        return Ok((input, InterfaceCounters { in_octets: c[0], in_ucast_packets: c[1], in_nucast_packets: c[2], in_discards: c[3], in_errors: c[4], in_unknown_protos: c[5], out_octets: c[6], out_ucast_packets: c[7], out_nucast_packets: c[8], out_discards: c[9], out_errors: c[10] }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        for counter in [self.in_octets, self.in_ucast_packets, self.in_nucast_packets, self.in_discards, self.in_errors, self.in_unknown_protos, self.out_octets, self.out_ucast_packets, self.out_nucast_packets, self.out_discards, self.out_errors] {
+            vec.extend_from_slice(&counter.to_le_bytes());
+        }
+
+        return vec;
+    }
+}
+
+impl InterfaceCounters {
+    /// Counts accumulated since `earlier`, allowing for counters that wrapped.
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            in_octets: self.in_octets.wrapping_sub(earlier.in_octets),
+            in_ucast_packets: self.in_ucast_packets.wrapping_sub(earlier.in_ucast_packets),
+            in_nucast_packets: self.in_nucast_packets.wrapping_sub(earlier.in_nucast_packets),
+            in_discards: self.in_discards.wrapping_sub(earlier.in_discards),
+            in_errors: self.in_errors.wrapping_sub(earlier.in_errors),
+            in_unknown_protos: self.in_unknown_protos.wrapping_sub(earlier.in_unknown_protos),
+            out_octets: self.out_octets.wrapping_sub(earlier.out_octets),
+            out_ucast_packets: self.out_ucast_packets.wrapping_sub(earlier.out_ucast_packets),
+            out_nucast_packets: self.out_nucast_packets.wrapping_sub(earlier.out_nucast_packets),
+            out_discards: self.out_discards.wrapping_sub(earlier.out_discards),
+            out_errors: self.out_errors.wrapping_sub(earlier.out_errors)
+        }
+    }
+
+    /// Share of received packets that had errors, or `None` if nothing was received.
+    pub fn in_error_rate(&self) -> Option<f64> {
+        let packets = self.in_ucast_packets as u64 + self.in_nucast_packets as u64 + self.in_errors as u64;
+        if packets == 0 {
+            return None;
+        }
+
+        return Some(self.in_errors as f64 / packets as f64);
+    }
+
+    /// Share of packets to send that failed, or `None` if nothing was sent.
+    pub fn out_error_rate(&self) -> Option<f64> {
+        let packets = self.out_ucast_packets as u64 + self.out_nucast_packets as u64 + self.out_errors as u64;
+        if packets == 0 {
+            return None;
+        }
+
+        return Some(self.out_errors as f64 / packets as f64);
+    }
+}
+
+/// Attribute 5.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MediaCounters {
+    pub alignment_errors: u32,
+    pub fcs_errors: u32,
+    pub single_collisions: u32,
+    pub multiple_collisions: u32,
+    pub sqe_test_errors: u32,
+    pub deferred_transmissions: u32,
+    pub late_collisions: u32,
+    pub excessive_collisions: u32,
+    pub mac_transmit_errors: u32,
+    pub carrier_sense_errors: u32,
+    pub frame_too_long: u32,
+    pub mac_receive_errors: u32
+}
+
+impl Serializable for MediaCounters {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized {
+        let (input, c) = count(le_u32, 12)(input)?;
+        return Ok((input, MediaCounters { alignment_errors: c[0], fcs_errors: c[1], single_collisions: c[2], multiple_collisions: c[3], sqe_test_errors: c[4], deferred_transmissions: c[5], late_collisions: c[6], excessive_collisions: c[7], mac_transmit_errors: c[8], carrier_sense_errors: c[9], frame_too_long: c[10], mac_receive_errors: c[11] }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        for counter in [self.alignment_errors, self.fcs_errors, self.single_collisions, self.multiple_collisions, self.sqe_test_errors, self.deferred_transmissions, self.late_collisions, self.excessive_collisions, self.mac_transmit_errors, self.carrier_sense_errors, self.frame_too_long, self.mac_receive_errors] {
+            vec.extend_from_slice(&counter.to_le_bytes());
+        }
+
+        return vec;
+    }
+}
+
+impl MediaCounters {
+    /// Counts accumulated since `earlier`, allowing for counters that wrapped.
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            alignment_errors: self.alignment_errors.wrapping_sub(earlier.alignment_errors),
+            fcs_errors: self.fcs_errors.wrapping_sub(earlier.fcs_errors),
+            single_collisions: self.single_collisions.wrapping_sub(earlier.single_collisions),
+            multiple_collisions: self.multiple_collisions.wrapping_sub(earlier.multiple_collisions),
+            sqe_test_errors: self.sqe_test_errors.wrapping_sub(earlier.sqe_test_errors),
+            deferred_transmissions: self.deferred_transmissions.wrapping_sub(earlier.deferred_transmissions),
+            late_collisions: self.late_collisions.wrapping_sub(earlier.late_collisions),
+            excessive_collisions: self.excessive_collisions.wrapping_sub(earlier.excessive_collisions),
+            mac_transmit_errors: self.mac_transmit_errors.wrapping_sub(earlier.mac_transmit_errors),
+            carrier_sense_errors: self.carrier_sense_errors.wrapping_sub(earlier.carrier_sense_errors),
+            frame_too_long: self.frame_too_long.wrapping_sub(earlier.frame_too_long),
+            mac_receive_errors: self.mac_receive_errors.wrapping_sub(earlier.mac_receive_errors)
+        }
+    }
+}
+
+/// Attribute 6.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InterfaceControl {
+    pub auto_negotiate: bool,
+    /// Only used when auto-negotiation is off.
+    pub forced_full_duplex: bool,
+    /// Speed in Mbps when auto-negotiation is off.
+    pub forced_interface_speed: u16
+}
+
+impl Serializable for InterfaceControl {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized {
+        let (input, (control_bits, forced_interface_speed)) = tuple((le_u16, le_u16))(input)?;
+        return Ok((input, InterfaceControl { auto_negotiate: control_bits & 0x01 != 0, forced_full_duplex: control_bits & 0x02 != 0, forced_interface_speed }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let control_bits = self.auto_negotiate as u16 | (self.forced_full_duplex as u16) << 1;
+
+        let mut vec = Vec::new();
+        vec.extend_from_slice(&control_bits.to_le_bytes());
+        vec.extend_from_slice(&self.forced_interface_speed.to_le_bytes());
+
+        return vec;
+    }
+}
+
+/// A speed and duplex combination from attribute 11.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpeedDuplex {
+    pub speed: u16,
+    pub full_duplex: bool
+}
+
+/// Attribute 11.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InterfaceCapability {
+    pub capability_bits: u32,
+    pub speed_duplex: Vec<SpeedDuplex>
+}
+
+impl InterfaceCapability {
+    pub fn manual_setting_requires_reset(&self) -> bool {
+        self.capability_bits & 0x01 != 0
+    }
+
+    pub fn auto_negotiate(&self) -> bool {
+        self.capability_bits & 0x02 != 0
+    }
+
+    pub fn auto_mdix(&self) -> bool {
+        self.capability_bits & 0x04 != 0
+    }
+
+    pub fn manual_speed_duplex(&self) -> bool {
+        self.capability_bits & 0x08 != 0
+    }
+}
+
+impl Serializable for InterfaceCapability {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized {
+        let (input, (capability_bits, length)) = tuple((le_u32, le_u8))(input)?;
+        let (input, entries) = count(tuple((le_u16, le_u8)), length as usize)(input)?;
+        let speed_duplex = entries.into_iter().map(|(speed, duplex)| SpeedDuplex { speed, full_duplex: duplex == 1 }).collect();
+
+        return Ok((input, InterfaceCapability { capability_bits, speed_duplex }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        vec.extend_from_slice(&self.capability_bits.to_le_bytes());
+        vec.push(self.speed_duplex.len() as u8);
+
+        for entry in &self.speed_duplex {
+            vec.extend_from_slice(&entry.speed.to_le_bytes());
+            vec.push(entry.full_duplex as u8);
+        }
+
+        return vec;
+    }
+}
+
+/// Everything an Ethernet Link instance reports. Optional attributes the
+/// device does not implement are `None`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EthernetLink {
+    /// Speed in Mbps, 0 if unknown.
+    pub interface_speed: u32,
+    pub interface_flags: InterfaceFlags,
+    pub physical_address: [u8; 6],
+    pub interface_counters: Option<InterfaceCounters>,
+    pub media_counters: Option<MediaCounters>,
+    pub interface_control: Option<InterfaceControl>,
+    /// 1: internal, 2: twisted pair, 3: optical fiber.
+    pub interface_type: Option<u8>,
+    /// 1: enabled, 2: disabled, 3: testing.
+    pub interface_state: Option<u8>,
+    /// 1: enabled, 2: disabled.
+    pub admin_state: Option<u8>,
+    pub interface_label: Option<String>,
+    pub interface_capability: Option<InterfaceCapability>
+}
+
+/// Server-side Ethernet Link object. The device keeps the counters up to date
+/// with [`EthernetLinkObject::set_interface_counters`] and
+/// [`EthernetLinkObject::set_media_counters`].
+pub struct EthernetLinkObject {
+    object: AttributeObject
+}
+
+impl EthernetLinkObject {
+    pub fn new() -> Self {
+        let mut object = AttributeObject::new(CipClass::EtherNetLink as u16, 4);
+        object.optional_services.push(GET_AND_CLEAR as u16);
+        return Self { object };
+    }
+
+    /// Adds an instance reporting `link`. Interface control is settable.
+    pub fn with_link(mut self, instance: u32, link: &EthernetLink) -> Self {
+        let mut table = AttributeTable::new()
+            .with(Attribute::get(1, CipDataType::Udint, link.interface_speed.to_le_bytes().to_vec()))
+            .with(Attribute::get(2, CipDataType::Dword, link.interface_flags.0.to_le_bytes().to_vec()))
+            .with(Attribute::get(3, CipDataType::Array, link.physical_address.to_vec()));
+
+        if let Some(counters) = &link.interface_counters {
+            table = table.with(Attribute::get(4, CipDataType::Struct, counters.serialize()).optional());
+        }
+        if let Some(counters) = &link.media_counters {
+            table = table.with(Attribute::get(5, CipDataType::Struct, counters.serialize()).optional());
+        }
+        if let Some(control) = &link.interface_control {
+            table = table.with(Attribute::get(6, CipDataType::Struct, control.serialize()).settable().nonvolatile().optional());
+        }
+        if let Some(interface_type) = link.interface_type {
+            table = table.with(Attribute::get(7, CipDataType::Usint, alloc::vec![interface_type]).optional());
+        }
+        if let Some(interface_state) = link.interface_state {
+            table = table.with(Attribute::get(8, CipDataType::Usint, alloc::vec![interface_state]).optional());
+        }
+        if let Some(admin_state) = link.admin_state {
+            table = table.with(Attribute::get(9, CipDataType::Usint, alloc::vec![admin_state]).optional());
+        }
+        if let Some(label) = &link.interface_label {
+            let mut value = alloc::vec![label.len() as u8];
+            value.extend_from_slice(label.as_bytes());
+            table = table.with(Attribute::get(10, CipDataType::ShortString, value).optional());
+        }
+        if let Some(capability) = &link.interface_capability {
+            table = table.with(Attribute::get(11, CipDataType::Struct, capability.serialize()).optional());
+        }
+
+        self.object = self.object.with_instance(instance, table);
+        return self;
+    }
+
+    pub fn set_interface_counters(&mut self, instance: u32, counters: &InterfaceCounters) {
+        if let Some(table) = self.object.instance_mut(instance) {
+            table.set_value(EthernetLinkAttribute::InterfaceCounters as u16, counters.serialize());
+        }
+    }
+
+    pub fn set_media_counters(&mut self, instance: u32, counters: &MediaCounters) {
+        if let Some(table) = self.object.instance_mut(instance) {
+            table.set_value(EthernetLinkAttribute::MediaCounters as u16, counters.serialize());
+        }
+    }
+}
+
+impl Default for EthernetLinkObject {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CipObject for EthernetLinkObject {
+    fn class_id(&self) -> u16 {
+        self.object.class_id()
+    }
+
+    fn has_instance(&self, instance: u32) -> bool {
+        self.object.has_instance(instance)
+    }
+
+    fn class_service(&mut self, service: u8, path: &ObjectPath, data: &[u8]) -> ServiceResult {
+        return self.object.class_service(service, path, data);
+    }
+
+    fn instance_service(&mut self, service: u8, path: &ObjectPath, data: &[u8]) -> ServiceResult {
+        if service != GET_AND_CLEAR {
+            return self.object.instance_service(service, path, data);
+        }
+
+        let table = self.object.instance_mut(path.instance).ok_or(MessageRouterResponseStatusCodes::PathDestinationUnknown)?;
+        let (attribute, cleared) = match path.attribute {
+            Some(4) => (EthernetLinkAttribute::InterfaceCounters, InterfaceCounters::default().serialize()),
+            Some(5) => (EthernetLinkAttribute::MediaCounters, MediaCounters::default().serialize()),
+            _ => return Err(MessageRouterResponseStatusCodes::AttributeNotSupported.into())
+        };
+
+        let value = table.get_attribute_single(attribute as u16)?;
+        table.set_value(attribute as u16, cleared);
+        return Ok(value);
+    }
+}
+
+impl CipClient {
+    async fn ethernet_link_request(&mut self, service: u8, instance: u32, attribute: EthernetLinkAttribute, data: Vec<u8>) -> Result<Vec<u8>, CipError> {
+        let response = self.attribute_request(service, CipClass::EtherNetLink as u32, instance, attribute as u32, data).await?;
+        response.check()?;
+        return Ok(response.data);
+    }
+
+    async fn get_ethernet_link_attribute(&mut self, instance: u32, attribute: EthernetLinkAttribute) -> Result<Vec<u8>, CipError> {
+        return self.ethernet_link_request(CipService::GetAttributeSingle as u8, instance, attribute, alloc::vec![]).await;
+    }
+
+    /// Reads every attribute of Ethernet Link `instance`.
+    pub async fn get_ethernet_link(&mut self, instance: u32) -> Result<EthernetLink, CipError> {
+        let data = self.get_ethernet_link_attribute(instance, EthernetLinkAttribute::InterfaceSpeed).await?;
+        let (_, interface_speed) = le_u32::<_, Error<&[u8]>>(data.as_slice())?;
+        let data = self.get_ethernet_link_attribute(instance, EthernetLinkAttribute::InterfaceFlags).await?;
+        let (_, interface_flags) = le_u32::<_, Error<&[u8]>>(data.as_slice())?;
+        let data = self.get_ethernet_link_attribute(instance, EthernetLinkAttribute::PhysicalAddress).await?;
+        let (_, physical_address) = take::<_, _, Error<&[u8]>>(6u8)(data.as_slice())?;
+        let physical_address = physical_address.try_into().expect("slice with incorrect length");
+
+        let mut interface_counters = None;
+        if let Some(data) = optional_attribute(self.get_ethernet_link_attribute(instance, EthernetLinkAttribute::InterfaceCounters).await)? {
+            interface_counters = Some(InterfaceCounters::deserialize(&data)?.1);
+        }
+
+        let mut media_counters = None;
+        if let Some(data) = optional_attribute(self.get_ethernet_link_attribute(instance, EthernetLinkAttribute::MediaCounters).await)? {
+            media_counters = Some(MediaCounters::deserialize(&data)?.1);
+        }
+
+        let mut interface_control = None;
+        if let Some(data) = optional_attribute(self.get_ethernet_link_attribute(instance, EthernetLinkAttribute::InterfaceControl).await)? {
+            interface_control = Some(InterfaceControl::deserialize(&data)?.1);
+        }
+
+        let mut interface_type = None;
+        if let Some(data) = optional_attribute(self.get_ethernet_link_attribute(instance, EthernetLinkAttribute::InterfaceType).await)? {
+            interface_type = Some(le_u8::<_, Error<&[u8]>>(data.as_slice())?.1);
+        }
+
+        let mut interface_state = None;
+        if let Some(data) = optional_attribute(self.get_ethernet_link_attribute(instance, EthernetLinkAttribute::InterfaceState).await)? {
+            interface_state = Some(le_u8::<_, Error<&[u8]>>(data.as_slice())?.1);
+        }
+
+        let mut admin_state = None;
+        if let Some(data) = optional_attribute(self.get_ethernet_link_attribute(instance, EthernetLinkAttribute::AdminState).await)? {
+            admin_state = Some(le_u8::<_, Error<&[u8]>>(data.as_slice())?.1);
+        }
+
+        let mut interface_label = None;
+        if let Some(data) = optional_attribute(self.get_ethernet_link_attribute(instance, EthernetLinkAttribute::InterfaceLabel).await)? {
+            let (label, length) = le_u8::<_, Error<&[u8]>>(data.as_slice())?;
+            let (_, label) = take::<_, _, Error<&[u8]>>(length)(label)?;
+            interface_label = Some(String::from_utf8_lossy(label).into());
+        }
+
+        let mut interface_capability = None;
+        if let Some(data) = optional_attribute(self.get_ethernet_link_attribute(instance, EthernetLinkAttribute::InterfaceCapability).await)? {
+            interface_capability = Some(InterfaceCapability::deserialize(&data)?.1);
+        }
+
+        return Ok(EthernetLink { interface_speed, interface_flags: InterfaceFlags(interface_flags), physical_address, interface_counters, media_counters, interface_control, interface_type, interface_state, admin_state, interface_label, interface_capability });
+    }
+
+    /// Reads the interface counters and resets them on the device.
+    pub async fn get_and_clear_interface_counters(&mut self, instance: u32) -> Result<InterfaceCounters, CipError> {
+        let data = self.ethernet_link_request(GET_AND_CLEAR, instance, EthernetLinkAttribute::InterfaceCounters, alloc::vec![]).await?;
+        let (_, counters) = InterfaceCounters::deserialize(&data)?;
+        return Ok(counters);
+    }
+
+    /// Reads the media counters and resets them on the device.
+    pub async fn get_and_clear_media_counters(&mut self, instance: u32) -> Result<MediaCounters, CipError> {
+        let data = self.ethernet_link_request(GET_AND_CLEAR, instance, EthernetLinkAttribute::MediaCounters, alloc::vec![]).await?;
+        let (_, counters) = MediaCounters::deserialize(&data)?;
+        return Ok(counters);
+    }
+
+    pub async fn set_interface_control(&mut self, instance: u32, control: &InterfaceControl) -> Result<(), CipError> {
+        if !control.auto_negotiate && control.forced_interface_speed == 0 {
+            return Err(CipError::InvalidValue("forced interface speed is required without auto-negotiation"));
+        }
+
+        self.ethernet_link_request(CipService::SetAttributeSingle as u8, instance, EthernetLinkAttribute::InterfaceControl, control.serialize()).await?;
+        return Ok(());
+    }
+}
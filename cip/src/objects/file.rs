@@ -0,0 +1,266 @@
+use alloc::vec::Vec;
+use nom::{error::Error, number::complete::{le_u16, le_u32, le_u8}, sequence::tuple};
+
+use crate::{cip::{CipClass, CipClient, CipService}, error::CipError, objects::optional_attribute};
+
+pub const INITIATE_UPLOAD: u8 = 0x4B;
+pub const INITIATE_DOWNLOAD: u8 = 0x4C;
+pub const UPLOAD_TRANSFER: u8 = 0x4F;
+pub const DOWNLOAD_TRANSFER: u8 = 0x50;
+
+/// Instance that holds the device's EDS file.
+pub const EDS_FILE_INSTANCE: u32 = 0xC8;
+
+/// Largest packet we ask for in Initiate_Upload.
+const MAX_TRANSFER_SIZE: u8 = 255;
+
+/// Attribute IDs of a File instance.
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileAttribute {
+    State = 1,
+    InstanceName = 2,
+    InstanceFormatVersion = 3,
+    FileName = 4,
+    FileRevision = 5,
+    FileSize = 6,
+    FileChecksum = 7,
+    InvocationMethod = 8,
+    FileSaveParameters = 9,
+    FileType = 10,
+    FileEncodingFormat = 11
+}
+
+/// Position of a packet within a transfer.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferPacketType {
+    First = 0,
+    Middle = 1,
+    /// Carries the file checksum after the data.
+    Last = 2,
+    Abort = 3,
+    /// The whole file in one packet, followed by the checksum.
+    FirstAndLast = 4
+}
+
+impl TryFrom<u8> for TransferPacketType {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TransferPacketType::First),
+            1 => Ok(TransferPacketType::Middle),
+            2 => Ok(TransferPacketType::Last),
+            3 => Ok(TransferPacketType::Abort),
+            4 => Ok(TransferPacketType::FirstAndLast),
+            value => Err(value)
+        }
+    }
+}
+
+impl TransferPacketType {
+    pub fn is_last(&self) -> bool {
+        *self == TransferPacketType::Last || *self == TransferPacketType::FirstAndLast
+    }
+}
+
+/// Two's complement of the 16-bit sum of all bytes of a file.
+pub fn file_checksum(data: &[u8]) -> u16 {
+    let sum = data.iter().fold(0u16, |sum, byte| sum.wrapping_add(*byte as u16));
+    return 0u16.wrapping_sub(sum);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileRevision {
+    pub major: u8,
+    pub minor: u8
+}
+
+/// Reply of Initiate_Upload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UploadInfo {
+    pub file_size: u32,
+    /// Data bytes the device sends per Upload_Transfer.
+    pub transfer_size: u8
+}
+
+/// Reply of Initiate_Download.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DownloadInfo {
+    /// Bytes the device stores at once, 0 if it stores the whole file at the end.
+    pub incremental_burn: u32,
+    /// Seconds one incremental burn takes.
+    pub incremental_burn_time: u16,
+    /// Data bytes the device accepts per Download_Transfer.
+    pub transfer_size: u8
+}
+
+/// Reply of Upload_Transfer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferPacket {
+    pub transfer_number: u8,
+    pub packet_type: TransferPacketType,
+    pub data: Vec<u8>,
+    /// Sent with the last packet.
+    pub checksum: Option<u16>
+}
+
+/// Encodes `value` as a STRINGI with a single English SHORT_STRING.
+fn encode_stringi(value: &str) -> Vec<u8> {
+    let mut vec = alloc::vec![1];
+    vec.extend_from_slice(b"eng");
+    vec.push(0xDA);
+    vec.extend_from_slice(&4u16.to_le_bytes());
+    vec.push(value.len() as u8);
+    vec.extend_from_slice(value.as_bytes());
+
+    return vec;
+}
+
+impl CipClient {
+    async fn file_request(&mut self, service: u8, instance: u32, data: Vec<u8>) -> Result<Vec<u8>, CipError> {
+        let response = self.call_service(CipClass::File as u32, instance, service, data).await?;
+        response.check()?;
+        return Ok(response.data);
+    }
+
+    /// Starts an upload from File `instance` with packets of at most `max_transfer_size` bytes.
+    pub async fn initiate_upload(&mut self, instance: u32, max_transfer_size: u8) -> Result<UploadInfo, CipError> {
+        let data = self.file_request(INITIATE_UPLOAD, instance, alloc::vec![max_transfer_size]).await?;
+        let (_, (file_size, transfer_size)) = tuple((le_u32::<_, Error<&[u8]>>, le_u8))(data.as_slice())?;
+
+        return Ok(UploadInfo { file_size, transfer_size });
+    }
+
+    /// Requests packet `transfer_number` of an upload. Asking for the previous number again makes the device resend it.
+    pub async fn upload_transfer(&mut self, instance: u32, transfer_number: u8) -> Result<TransferPacket, CipError> {
+        let data = self.file_request(UPLOAD_TRANSFER, instance, alloc::vec![transfer_number]).await?;
+        let (input, (echoed, packet_type)) = tuple((le_u8::<_, Error<&[u8]>>, le_u8))(data.as_slice())?;
+
+        if echoed != transfer_number {
+            return Err(CipError::InvalidReply);
+        }
+        let packet_type = TransferPacketType::try_from(packet_type).map_err(|_| CipError::InvalidReply)?;
+
+        let mut data = input.to_vec();
+        let mut checksum = None;
+        if packet_type.is_last() {
+            if data.len() < 2 {
+                return Err(CipError::Parse);
+            }
+            let bytes = data.split_off(data.len() - 2);
+            checksum = Some(u16::from_le_bytes([bytes[0], bytes[1]]));
+        }
+
+        return Ok(TransferPacket { transfer_number, packet_type, data, checksum });
+    }
+
+    /// Uploads the whole content of File `instance` and verifies its size and checksum.
+    pub async fn upload_file(&mut self, instance: u32) -> Result<Vec<u8>, CipError> {
+        let info = self.initiate_upload(instance, MAX_TRANSFER_SIZE).await?;
+
+        let mut file = Vec::with_capacity(info.file_size as usize);
+        let mut transfer_number = 0u8;
+        loop {
+            let packet = self.upload_transfer(instance, transfer_number).await?;
+
+            let expected_first = transfer_number == 0 && file.is_empty();
+            let is_first = packet.packet_type == TransferPacketType::First || packet.packet_type == TransferPacketType::FirstAndLast;
+            if packet.packet_type == TransferPacketType::Abort || expected_first != is_first {
+                return Err(CipError::InvalidReply);
+            }
+
+            file.extend_from_slice(&packet.data);
+            if file.len() > info.file_size as usize {
+                return Err(CipError::InvalidReply);
+            }
+
+            if let Some(expected) = packet.checksum {
+                if file.len() != info.file_size as usize {
+                    return Err(CipError::InvalidReply);
+                }
+
+                let computed = file_checksum(&file);
+                if computed != expected {
+                    return Err(CipError::ChecksumMismatch { expected, computed });
+                }
+
+                return Ok(file);
+            }
+
+            transfer_number = transfer_number.wrapping_add(1);
+        }
+    }
+
+    /// Starts a download of `file_size` bytes into File `instance`.
+    pub async fn initiate_download(&mut self, instance: u32, file_size: u32, revision: FileRevision, file_name: &str) -> Result<DownloadInfo, CipError> {
+        if file_name.len() > u8::MAX as usize {
+            return Err(CipError::InvalidValue("file name is longer than 255 bytes"));
+        }
+
+        let mut request = Vec::new();
+        request.extend_from_slice(&file_size.to_le_bytes());
+        request.push(revision.major);
+        request.push(revision.minor);
+        request.extend(encode_stringi(file_name));
+
+        let data = self.file_request(INITIATE_DOWNLOAD, instance, request).await?;
+        let (_, (incremental_burn, incremental_burn_time, transfer_size)) = tuple((le_u32::<_, Error<&[u8]>>, le_u16, le_u8))(data.as_slice())?;
+
+        return Ok(DownloadInfo { incremental_burn, incremental_burn_time, transfer_size });
+    }
+
+    /// Sends one packet of a download. The last packet carries `checksum`.
+    pub async fn download_transfer(&mut self, instance: u32, transfer_number: u8, packet_type: TransferPacketType, data: &[u8], checksum: Option<u16>) -> Result<(), CipError> {
+        let mut request = alloc::vec![transfer_number, packet_type as u8];
+        request.extend_from_slice(data);
+        if let Some(checksum) = checksum {
+            request.extend_from_slice(&checksum.to_le_bytes());
+        }
+
+        let data = self.file_request(DOWNLOAD_TRANSFER, instance, request).await?;
+        let (_, echoed) = le_u8::<_, Error<&[u8]>>(data.as_slice())?;
+        if echoed != transfer_number {
+            return Err(CipError::InvalidReply);
+        }
+
+        return Ok(());
+    }
+
+    /// Downloads `file` into File `instance` in packets of the size the device asks for.
+    pub async fn download_file(&mut self, instance: u32, file: &[u8], revision: FileRevision, file_name: &str) -> Result<(), CipError> {
+        let info = self.initiate_download(instance, file.len() as u32, revision, file_name).await?;
+        if info.transfer_size == 0 {
+            return Err(CipError::InvalidReply);
+        }
+
+        let checksum = file_checksum(file);
+        let packets: Vec<&[u8]> = if file.is_empty() { alloc::vec![file] } else { file.chunks(info.transfer_size as usize).collect() };
+        for (i, packet) in packets.iter().enumerate() {
+            let last = i == packets.len() - 1;
+            let packet_type = match (i == 0, last) {
+                (true, true) => TransferPacketType::FirstAndLast,
+                (true, false) => TransferPacketType::First,
+                (false, true) => TransferPacketType::Last,
+                (false, false) => TransferPacketType::Middle
+            };
+
+            self.download_transfer(instance, i as u8, packet_type, packet, last.then_some(checksum)).await?;
+        }
+
+        return Ok(());
+    }
+
+    /// Uploads the EDS file the device stores in File instance 0xC8.
+    pub async fn get_eds_file(&mut self) -> Result<Vec<u8>, CipError> {
+        let response = self.attribute_request(CipService::GetAttributeSingle as u8, CipClass::File as u32, EDS_FILE_INSTANCE, FileAttribute::FileEncodingFormat as u32, alloc::vec![]).await?;
+        if let Some(data) = optional_attribute(response.check().map(|_| response.data))? {
+            if data.first().copied().unwrap_or(0) != 0 {
+                return Err(CipError::InvalidValue("compressed EDS files are not supported"));
+            }
+        }
+
+        return self.upload_file(EDS_FILE_INSTANCE).await;
+    }
+}
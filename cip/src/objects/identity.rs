@@ -0,0 +1,270 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
+use nom::{bytes::complete::take, combinator::opt, number::complete::{le_u16, le_u32, le_u8}, sequence::tuple};
+
+use crate::{attributes::{Attribute, AttributeObject, AttributeTable}, cip::{CipClass, CipDataType, CipService}, common::Serializable, objects::message_router::MessageRouterResponseStatusCodes, router::{CipObject, ObjectPath, ServiceError, ServiceResult}};
+
+/// Bits 4 to 7 of the Identity status word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExtendedDeviceStatus {
+    SelfTestingOrUnknown,
+    FirmwareUpdateInProgress,
+    IoConnectionFault,
+    NoIoConnections,
+    NonVolatileConfigurationBad,
+    MajorFault,
+    IoConnectionRunMode,
+    IoConnectionIdleMode,
+    Other(u8)
+}
+
+impl From<u8> for ExtendedDeviceStatus {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => ExtendedDeviceStatus::SelfTestingOrUnknown,
+            1 => ExtendedDeviceStatus::FirmwareUpdateInProgress,
+            2 => ExtendedDeviceStatus::IoConnectionFault,
+            3 => ExtendedDeviceStatus::NoIoConnections,
+            4 => ExtendedDeviceStatus::NonVolatileConfigurationBad,
+            5 => ExtendedDeviceStatus::MajorFault,
+            6 => ExtendedDeviceStatus::IoConnectionRunMode,
+            7 => ExtendedDeviceStatus::IoConnectionIdleMode,
+            value => ExtendedDeviceStatus::Other(value)
+        }
+    }
+}
+
+/// Identity attribute 5.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IdentityStatus(pub u16);
+
+impl IdentityStatus {
+    /// At least one connection has been allocated by a master.
+    pub fn owned(&self) -> bool {
+        self.0 & 0x0001 != 0
+    }
+
+    /// The device has been configured beyond its out of box defaults.
+    pub fn configured(&self) -> bool {
+        self.0 & 0x0004 != 0
+    }
+
+    pub fn extended_status(&self) -> ExtendedDeviceStatus {
+        ExtendedDeviceStatus::from(((self.0 >> 4) & 0x0F) as u8)
+    }
+
+    pub fn minor_recoverable_fault(&self) -> bool {
+        self.0 & 0x0100 != 0
+    }
+
+    pub fn minor_unrecoverable_fault(&self) -> bool {
+        self.0 & 0x0200 != 0
+    }
+
+    pub fn major_recoverable_fault(&self) -> bool {
+        self.0 & 0x0400 != 0
+    }
+
+    pub fn major_unrecoverable_fault(&self) -> bool {
+        self.0 & 0x0800 != 0
+    }
+}
+
+/// Identity attribute 8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceState {
+    Nonexistent,
+    SelfTesting,
+    Standby,
+    Operational,
+    MajorRecoverableFault,
+    MajorUnrecoverableFault,
+    Default,
+    Other(u8)
+}
+
+impl DeviceState {
+    pub fn code(&self) -> u8 {
+        match self {
+            DeviceState::Nonexistent => 0,
+            DeviceState::SelfTesting => 1,
+            DeviceState::Standby => 2,
+            DeviceState::Operational => 3,
+            DeviceState::MajorRecoverableFault => 4,
+            DeviceState::MajorUnrecoverableFault => 5,
+            DeviceState::Default => 255,
+            DeviceState::Other(code) => *code
+        }
+    }
+}
+
+impl From<u8> for DeviceState {
+    fn from(code: u8) -> Self {
+        match code {
+            0 => DeviceState::Nonexistent,
+            1 => DeviceState::SelfTesting,
+            2 => DeviceState::Standby,
+            3 => DeviceState::Operational,
+            4 => DeviceState::MajorRecoverableFault,
+            5 => DeviceState::MajorUnrecoverableFault,
+            255 => DeviceState::Default,
+            code => DeviceState::Other(code)
+        }
+    }
+}
+
+/// Parameter of the Reset service.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResetType {
+    /// Emulate a power cycle.
+    PowerCycle = 0,
+    /// Return to the out of box configuration, then power cycle.
+    FactoryDefaults = 1,
+    /// Like `FactoryDefaults`, but keep the communication link parameters.
+    FactoryDefaultsExceptCommunication = 2
+}
+
+impl TryFrom<u8> for ResetType {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ResetType::PowerCycle),
+            1 => Ok(ResetType::FactoryDefaults),
+            2 => Ok(ResetType::FactoryDefaultsExceptCommunication),
+            value => Err(value)
+        }
+    }
+}
+
+/// The Identity object instance, in Get_Attributes_All order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Identity {
+    pub vendor_id: u16,
+    pub device_type: u16,
+    pub product_code: u16,
+    pub major_revision: u8,
+    pub minor_revision: u8,
+    pub status: IdentityStatus,
+    pub serial_number: u32,
+    pub product_name: String,
+    /// Only returned by devices that include attribute 8 in Get_Attributes_All.
+    pub state: Option<DeviceState>
+}
+
+impl Serializable for Identity {
+    fn deserialize(input: &[u8]) -> nom::IResult<&[u8], Self> where Self: Sized {
+        let (input, (vendor_id, device_type, product_code, major_revision, minor_revision, status, serial_number, name_length)) = tuple((le_u16, le_u16, le_u16, le_u8, le_u8, le_u16, le_u32, le_u8))(input)?;
+        let (input, name) = take(name_length)(input)?;
+        let (input, state) = opt(le_u8)(input)?;
+        let product_name = String::from_utf8_lossy(name).into();
+
+        return Ok((input, Identity { vendor_id, device_type, product_code, major_revision, minor_revision, status: IdentityStatus(status), serial_number, product_name, state: state.map(DeviceState::from) }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        vec.extend_from_slice(&self.vendor_id.to_le_bytes());
+        vec.extend_from_slice(&self.device_type.to_le_bytes());
+        vec.extend_from_slice(&self.product_code.to_le_bytes());
+        vec.push(self.major_revision);
+        vec.push(self.minor_revision);
+        vec.extend_from_slice(&self.status.0.to_le_bytes());
+        vec.extend_from_slice(&self.serial_number.to_le_bytes());
+        vec.push(self.product_name.len() as u8);
+        vec.extend_from_slice(self.product_name.as_bytes());
+
+        if let Some(state) = self.state {
+            vec.push(state.code());
+        }
+
+        return vec;
+    }
+}
+
+type ResetHandler = Box<dyn FnMut(ResetType) -> Result<(), ServiceError> + Send>;
+
+/// Server-side Identity object with a single instance.
+pub struct IdentityObject {
+    object: AttributeObject,
+    reset: Option<ResetHandler>
+}
+
+impl IdentityObject {
+    pub fn new(identity: &Identity) -> Self {
+        let revision = alloc::vec![identity.major_revision, identity.minor_revision];
+
+        let mut product_name = Vec::new();
+        product_name.push(identity.product_name.len() as u8);
+        product_name.extend_from_slice(identity.product_name.as_bytes());
+
+        let mut table = AttributeTable::new()
+            .with(Attribute::get(1, CipDataType::Uint, identity.vendor_id.to_le_bytes().to_vec()))
+            .with(Attribute::get(2, CipDataType::Uint, identity.device_type.to_le_bytes().to_vec()))
+            .with(Attribute::get(3, CipDataType::Uint, identity.product_code.to_le_bytes().to_vec()))
+            .with(Attribute::get(4, CipDataType::Struct, revision))
+            .with(Attribute::get(5, CipDataType::Word, identity.status.0.to_le_bytes().to_vec()))
+            .with(Attribute::get(6, CipDataType::Udint, identity.serial_number.to_le_bytes().to_vec()))
+            .with(Attribute::get(7, CipDataType::ShortString, product_name));
+
+        if let Some(state) = identity.state {
+            table = table.with(Attribute::get(8, CipDataType::Usint, alloc::vec![state.code()]).optional());
+        }
+
+        let object = AttributeObject::new(CipClass::Identity as u16, 1).with_instance(1, table);
+        return Self { object, reset: None };
+    }
+
+    /// Enables the Reset service. `handler` is called before the reply is sent
+    /// and should schedule the reset rather than perform it right away.
+    pub fn with_reset_handler(mut self, handler: impl FnMut(ResetType) -> Result<(), ServiceError> + Send + 'static) -> Self {
+        if self.reset.is_none() {
+            self.object.optional_services.push(CipService::Reset as u16);
+        }
+        self.reset = Some(Box::new(handler));
+        return self;
+    }
+
+    pub fn set_status(&mut self, status: IdentityStatus) {
+        if let Some(table) = self.object.instance_mut(1) {
+            table.set_value(5, status.0.to_le_bytes().to_vec());
+        }
+    }
+
+    /// Updates attribute 8, if the object was created with a state.
+    pub fn set_state(&mut self, state: DeviceState) {
+        if let Some(table) = self.object.instance_mut(1) {
+            table.set_value(8, alloc::vec![state.code()]);
+        }
+    }
+}
+
+impl CipObject for IdentityObject {
+    fn class_id(&self) -> u16 {
+        self.object.class_id()
+    }
+
+    fn has_instance(&self, instance: u32) -> bool {
+        self.object.has_instance(instance)
+    }
+
+    fn class_service(&mut self, service: u8, path: &ObjectPath, data: &[u8]) -> ServiceResult {
+        return self.object.class_service(service, path, data);
+    }
+
+    fn instance_service(&mut self, service: u8, path: &ObjectPath, data: &[u8]) -> ServiceResult {
+        if service != CipService::Reset as u8 {
+            return self.object.instance_service(service, path, data);
+        }
+
+        let handler = self.reset.as_mut().ok_or(ServiceError::from(MessageRouterResponseStatusCodes::ServiceNotSupported))?;
+        let reset_type = match data {
+            [] => ResetType::PowerCycle,
+            [reset_type] => ResetType::try_from(*reset_type).map_err(|_| ServiceError::from(MessageRouterResponseStatusCodes::InvalidParameter))?,
+            _ => return Err(MessageRouterResponseStatusCodes::TooMuchData.into())
+        };
+
+        handler(reset_type)?;
+        return Ok(Vec::new());
+    }
+}
@@ -26,7 +26,14 @@ impl Serializable for MessageRouter {
     }
 
     fn serialize(&self) -> Vec<u8> {
-        todo!()
+        let mut vec = Vec::new();
+        vec.extend_from_slice(&(self.objects.len() as u16).to_le_bytes());
+
+        for object in &self.objects {
+            vec.extend_from_slice(&object.to_le_bytes());
+        }
+
+        return vec;
     }
 }
 
@@ -1,2 +1,19 @@
 pub mod message_router;
-pub mod connection_manager;
\ No newline at end of file
+pub mod connection_manager;
+pub mod identity;
+pub mod tcpip;
+pub mod ethernet_link;
+pub mod assembly;
+pub mod parameter;
+pub mod file;
+
+use crate::error::CipError;
+
+/// Maps "attribute not supported" to `None` for optional attributes.
+pub(crate) fn optional_attribute<T>(result: Result<T, CipError>) -> Result<Option<T>, CipError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(CipError::Service { general_status: 0x14, .. }) => Ok(None),
+        Err(e) => Err(e)
+    }
+}
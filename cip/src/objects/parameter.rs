@@ -0,0 +1,360 @@
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+use nom::{error::Error, number::complete::{le_i16, le_u16, le_u8}};
+
+use crate::{cip::{CipClass, CipClient, CipDataType, CipService}, error::CipError, objects::optional_attribute, value::CipValue};
+
+/// Attribute IDs of a Parameter instance.
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParameterAttribute {
+    Value = 1,
+    LinkPathSize = 2,
+    LinkPath = 3,
+    Descriptor = 4,
+    DataType = 5,
+    DataSize = 6,
+    Name = 7,
+    Units = 8,
+    Help = 9,
+    MinimumValue = 10,
+    MaximumValue = 11,
+    DefaultValue = 12,
+    ScalingMultiplier = 13,
+    ScalingDivisor = 14,
+    ScalingBase = 15,
+    ScalingOffset = 16,
+    DecimalPrecision = 21
+}
+
+/// Attribute 4.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParameterDescriptor(pub u16);
+
+impl ParameterDescriptor {
+    pub fn settable_path(&self) -> bool {
+        self.0 & 0x0001 != 0
+    }
+
+    pub fn enumerated_strings(&self) -> bool {
+        self.0 & 0x0002 != 0
+    }
+
+    pub fn scaling(&self) -> bool {
+        self.0 & 0x0004 != 0
+    }
+
+    pub fn scaling_links(&self) -> bool {
+        self.0 & 0x0008 != 0
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.0 & 0x0010 != 0
+    }
+
+    /// The value changes on its own and is only meant to be monitored.
+    pub fn monitor(&self) -> bool {
+        self.0 & 0x0020 != 0
+    }
+
+    pub fn extended_precision_scaling(&self) -> bool {
+        self.0 & 0x0040 != 0
+    }
+}
+
+/// Attributes 13 to 16 and 21.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scaling {
+    pub multiplier: u16,
+    pub divisor: u16,
+    pub base: u16,
+    pub offset: i16,
+    pub decimal_precision: u8
+}
+
+impl Scaling {
+    /// Converts an internal value to engineering units, `None` if the divisor is zero.
+    pub fn scale(&self, value: f64) -> Option<f64> {
+        let mut divisor = self.divisor as f64;
+        for _ in 0..self.decimal_precision {
+            divisor *= 10.0;
+        }
+
+        if divisor == 0.0 {
+            return None;
+        }
+
+        return Some((value + self.offset as f64) * self.multiplier as f64 * self.base as f64 / divisor);
+    }
+}
+
+/// A Parameter instance. Stub parameters only carry attributes 1 to 6.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameter {
+    pub instance: u32,
+    pub value: CipValue,
+    /// Packed EPATH of the attribute the parameter is linked to.
+    pub link_path: Vec<u8>,
+    pub descriptor: ParameterDescriptor,
+    pub data_type: CipDataType,
+    pub data_size: u8,
+    pub name: Option<String>,
+    pub units: Option<String>,
+    pub help: Option<String>,
+    pub minimum: Option<CipValue>,
+    pub maximum: Option<CipValue>,
+    pub default: Option<CipValue>,
+    pub scaling: Option<Scaling>
+}
+
+impl Parameter {
+    /// Value in engineering units, if the parameter is numeric and scaled.
+    pub fn scaled_value(&self) -> Option<f64> {
+        return self.scaling?.scale(self.value.as_f64()?);
+    }
+}
+
+/// Why a parameter was or was not written by [`CipClient::restore_parameters`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RestoreOutcome {
+    Written,
+    /// The device reports the parameter as read-only.
+    ReadOnly,
+    /// The device's parameter has a different type than the backup.
+    TypeMismatch { device: CipDataType },
+    /// The device answered with a non-zero general status.
+    Rejected { general_status: u8 }
+}
+
+/// One parameter value in a [`ParameterBackup`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterBackupEntry {
+    pub instance: u32,
+    pub value: CipValue,
+    pub name: String
+}
+
+/// Parameter values of a device, stored as tab separated text:
+/// instance, data type code, value in hex and name, one parameter per line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParameterBackup {
+    pub entries: Vec<ParameterBackupEntry>
+}
+
+const BACKUP_HEADER: &str = "# CIP parameter backup v1";
+
+impl ParameterBackup {
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        text.push_str(BACKUP_HEADER);
+        text.push('\n');
+
+        for entry in &self.entries {
+            let _ = write!(text, "{}\t{:02X}\t", entry.instance, entry.value.data_type() as u16);
+            for byte in entry.value.encode() {
+                let _ = write!(text, "{:02X}", byte);
+            }
+            text.push('\t');
+            text.extend(entry.name.chars().map(|c| if c.is_control() { ' ' } else { c }));
+            text.push('\n');
+        }
+
+        return text;
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, CipError> {
+        let mut lines = text.lines();
+        if lines.next().map(str::trim_end) != Some(BACKUP_HEADER) {
+            return Err(CipError::InvalidValue("not a parameter backup"));
+        }
+
+        let mut entries = Vec::new();
+        for line in lines {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.splitn(4, '\t');
+            let (Some(instance), Some(data_type), Some(value), Some(name)) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+                return Err(CipError::InvalidValue("parameter backup line has missing fields"));
+            };
+
+            let instance = instance.parse().map_err(|_| CipError::InvalidValue("invalid parameter instance"))?;
+            let data_type = u16::from_str_radix(data_type, 16).ok().and_then(|code| CipDataType::try_from(code).ok()).ok_or(CipError::InvalidValue("invalid parameter data type"))?;
+            let data = parse_hex(value).ok_or(CipError::InvalidValue("invalid parameter value"))?;
+
+            let (rest, value) = CipValue::parse(data_type, &data)?;
+            if !rest.is_empty() {
+                return Err(CipError::InvalidValue("parameter value is longer than its data type"));
+            }
+
+            entries.push(ParameterBackupEntry { instance, value, name: name.into() });
+        }
+
+        return Ok(ParameterBackup { entries });
+    }
+}
+
+fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return None;
+    }
+
+    return (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok()).collect();
+}
+
+impl CipClient {
+    async fn parameter_request(&mut self, service: CipService, instance: u32, attribute: u32, data: Vec<u8>) -> Result<Vec<u8>, CipError> {
+        let response = self.attribute_request(service as u8, CipClass::Parameter as u32, instance, attribute, data).await?;
+        response.check()?;
+        return Ok(response.data);
+    }
+
+    async fn get_parameter_attribute(&mut self, instance: u32, attribute: ParameterAttribute) -> Result<Vec<u8>, CipError> {
+        return self.parameter_request(CipService::GetAttributeSingle, instance, attribute as u32, alloc::vec![]).await;
+    }
+
+    async fn get_parameter_data_type(&mut self, instance: u32) -> Result<CipDataType, CipError> {
+        let data = self.get_parameter_attribute(instance, ParameterAttribute::DataType).await?;
+        let (_, code) = le_u8::<_, Error<&[u8]>>(data.as_slice())?;
+        return CipDataType::try_from(code as u16).map_err(|_| CipError::InvalidValue("unknown parameter data type"));
+    }
+
+    async fn get_parameter_value(&mut self, instance: u32, data_type: CipDataType) -> Result<CipValue, CipError> {
+        let data = self.get_parameter_attribute(instance, ParameterAttribute::Value).await?;
+        let (_, value) = CipValue::parse(data_type, &data)?;
+        return Ok(value);
+    }
+
+    async fn get_optional_parameter_value(&mut self, instance: u32, attribute: ParameterAttribute, data_type: CipDataType) -> Result<Option<CipValue>, CipError> {
+        match optional_attribute(self.get_parameter_attribute(instance, attribute).await)? {
+            Some(data) => Ok(Some(CipValue::parse(data_type, &data)?.1)),
+            None => Ok(None)
+        }
+    }
+
+    async fn get_optional_parameter_string(&mut self, instance: u32, attribute: ParameterAttribute) -> Result<Option<String>, CipError> {
+        match self.get_optional_parameter_value(instance, attribute, CipDataType::ShortString).await? {
+            Some(CipValue::ShortString(value)) => Ok(Some(value)),
+            _ => Ok(None)
+        }
+    }
+
+    /// Highest Parameter instance number, read from class attribute 2. Instance numbers may have gaps.
+    pub async fn get_parameter_max_instance(&mut self) -> Result<u16, CipError> {
+        let data = self.parameter_request(CipService::GetAttributeSingle, 0, 2, alloc::vec![]).await?;
+        let (_, max_instance) = le_u16::<_, Error<&[u8]>>(data.as_slice())?;
+        return Ok(max_instance);
+    }
+
+    /// Reads every attribute of Parameter `instance`.
+    pub async fn get_parameter(&mut self, instance: u32) -> Result<Parameter, CipError> {
+        let data_type = self.get_parameter_data_type(instance).await?;
+        let value = self.get_parameter_value(instance, data_type).await?;
+        let link_path = self.get_parameter_attribute(instance, ParameterAttribute::LinkPath).await?;
+        let data = self.get_parameter_attribute(instance, ParameterAttribute::Descriptor).await?;
+        let (_, descriptor) = le_u16::<_, Error<&[u8]>>(data.as_slice())?;
+        let descriptor = ParameterDescriptor(descriptor);
+        let data = self.get_parameter_attribute(instance, ParameterAttribute::DataSize).await?;
+        let (_, data_size) = le_u8::<_, Error<&[u8]>>(data.as_slice())?;
+
+        let name = self.get_optional_parameter_string(instance, ParameterAttribute::Name).await?;
+        let units = self.get_optional_parameter_string(instance, ParameterAttribute::Units).await?;
+        let help = self.get_optional_parameter_string(instance, ParameterAttribute::Help).await?;
+        let minimum = self.get_optional_parameter_value(instance, ParameterAttribute::MinimumValue, data_type).await?;
+        let maximum = self.get_optional_parameter_value(instance, ParameterAttribute::MaximumValue, data_type).await?;
+        let default = self.get_optional_parameter_value(instance, ParameterAttribute::DefaultValue, data_type).await?;
+
+        let mut scaling = None;
+        if descriptor.scaling() {
+            let data = self.get_parameter_attribute(instance, ParameterAttribute::ScalingMultiplier).await?;
+            let (_, multiplier) = le_u16::<_, Error<&[u8]>>(data.as_slice())?;
+            let data = self.get_parameter_attribute(instance, ParameterAttribute::ScalingDivisor).await?;
+            let (_, divisor) = le_u16::<_, Error<&[u8]>>(data.as_slice())?;
+            let data = self.get_parameter_attribute(instance, ParameterAttribute::ScalingBase).await?;
+            let (_, base) = le_u16::<_, Error<&[u8]>>(data.as_slice())?;
+            let data = self.get_parameter_attribute(instance, ParameterAttribute::ScalingOffset).await?;
+            let (_, offset) = le_i16::<_, Error<&[u8]>>(data.as_slice())?;
+
+            let mut decimal_precision = 0;
+            if let Some(data) = optional_attribute(self.get_parameter_attribute(instance, ParameterAttribute::DecimalPrecision).await)? {
+                decimal_precision = le_u8::<_, Error<&[u8]>>(data.as_slice())?.1;
+            }
+
+            scaling = Some(Scaling { multiplier, divisor, base, offset, decimal_precision });
+        }
+
+        return Ok(Parameter { instance, value, link_path, descriptor, data_type, data_size, name, units, help, minimum, maximum, default, scaling });
+    }
+
+    /// Checks a write against the device's descriptor and data type, `None` if it may go ahead.
+    async fn check_parameter_write(&mut self, instance: u32, value: &CipValue) -> Result<Option<RestoreOutcome>, CipError> {
+        let data = self.get_parameter_attribute(instance, ParameterAttribute::Descriptor).await?;
+        let (_, descriptor) = le_u16::<_, Error<&[u8]>>(data.as_slice())?;
+        if ParameterDescriptor(descriptor).read_only() {
+            return Ok(Some(RestoreOutcome::ReadOnly));
+        }
+
+        let data_type = self.get_parameter_data_type(instance).await?;
+        if data_type != value.data_type() {
+            return Ok(Some(RestoreOutcome::TypeMismatch { device: data_type }));
+        }
+
+        return Ok(None);
+    }
+
+    /// Writes the value of Parameter `instance` after checking its type and descriptor.
+    pub async fn set_parameter_value(&mut self, instance: u32, value: &CipValue) -> Result<(), CipError> {
+        match self.check_parameter_write(instance, value).await? {
+            Some(RestoreOutcome::ReadOnly) => return Err(CipError::InvalidValue("parameter is read-only")),
+            Some(_) => return Err(CipError::InvalidValue("value does not match the parameter data type")),
+            None => {}
+        }
+
+        self.parameter_request(CipService::SetAttributeSingle, instance, ParameterAttribute::Value as u32, value.encode()).await?;
+        return Ok(());
+    }
+
+    /// Reads the value and name of every Parameter instance.
+    pub async fn backup_parameters(&mut self) -> Result<ParameterBackup, CipError> {
+        let max_instance = self.get_parameter_max_instance().await?;
+
+        let mut entries = Vec::new();
+        for instance in 1..=max_instance as u32 {
+            let data_type = match self.get_parameter_data_type(instance).await {
+                Ok(data_type) => data_type,
+                // Instance numbers may have gaps.
+                Err(CipError::Service { general_status: 0x05 | 0x16, .. }) => continue,
+                Err(e) => return Err(e)
+            };
+            let value = self.get_parameter_value(instance, data_type).await?;
+            let name = self.get_optional_parameter_string(instance, ParameterAttribute::Name).await?.unwrap_or_default();
+
+            entries.push(ParameterBackupEntry { instance, value, name });
+        }
+
+        return Ok(ParameterBackup { entries });
+    }
+
+    /// Writes back the values of a backup. Read-only parameters and type
+    /// mismatches are skipped and reported rather than written.
+    pub async fn restore_parameters(&mut self, backup: &ParameterBackup) -> Result<Vec<(u32, RestoreOutcome)>, CipError> {
+        let mut outcomes = Vec::new();
+        for entry in &backup.entries {
+            let outcome = match self.check_parameter_write(entry.instance, &entry.value).await {
+                Ok(Some(outcome)) => outcome,
+                Ok(None) => match self.parameter_request(CipService::SetAttributeSingle, entry.instance, ParameterAttribute::Value as u32, entry.value.encode()).await {
+                    Ok(_) => RestoreOutcome::Written,
+                    Err(CipError::Service { general_status, .. }) => RestoreOutcome::Rejected { general_status },
+                    Err(e) => return Err(e)
+                },
+                Err(CipError::Service { general_status, .. }) => RestoreOutcome::Rejected { general_status },
+                Err(e) => return Err(e)
+            };
+
+            outcomes.push((entry.instance, outcome));
+        }
+
+        return Ok(outcomes);
+    }
+}
@@ -0,0 +1,471 @@
+use alloc::{string::String, vec::Vec};
+use core::net::Ipv4Addr;
+use nom::{bytes::complete::take, error::Error, number::complete::{le_u16, le_u32, le_u8}, sequence::tuple, IResult};
+
+use crate::{cip::{CipClass, CipClient, CipService, EPath}, common::Serializable, error::CipError, objects::optional_attribute};
+
+/// Longest host name the object accepts.
+pub const MAX_HOST_NAME_LENGTH: usize = 64;
+/// Longest domain name the object accepts.
+pub const MAX_DOMAIN_NAME_LENGTH: usize = 48;
+/// Highest encapsulation inactivity timeout, in seconds.
+pub const MAX_INACTIVITY_TIMEOUT: u16 = 3600;
+
+/// Attribute IDs of a TCP/IP Interface instance.
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TcpIpAttribute {
+    Status = 1,
+    ConfigurationCapability = 2,
+    ConfigurationControl = 3,
+    PhysicalLinkObject = 4,
+    InterfaceConfiguration = 5,
+    HostName = 6,
+    TimeToLive = 8,
+    MulticastConfig = 9,
+    SelectAcd = 10,
+    LastConflictDetected = 11,
+    QuickConnect = 12,
+    EncapsulationInactivityTimeout = 13
+}
+
+/// A CIP STRING: UINT length and characters, padded to an even length.
+fn parse_string(input: &[u8]) -> IResult<&[u8], String> {
+    let (input, length) = le_u16(input)?;
+    let (input, chars) = take(length)(input)?;
+    let (input, _) = take(length as usize % 2)(input)?;
+
+    return Ok((input, String::from_utf8_lossy(chars).into()));
+}
+
+fn encode_string(value: &str) -> Vec<u8> {
+    let mut vec = Vec::new();
+    vec.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    vec.extend_from_slice(value.as_bytes());
+    if !value.len().is_multiple_of(2) {
+        vec.push(0);
+    }
+
+    return vec;
+}
+
+/// Attribute 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InterfaceStatus(pub u32);
+
+impl InterfaceStatus {
+    /// 0: not configured, 1: configured from BOOTP, DHCP or nonvolatile storage, 2: from hardware settings.
+    pub fn configuration_status(&self) -> u8 {
+        (self.0 & 0x0F) as u8
+    }
+
+    pub fn multicast_pending(&self) -> bool {
+        self.0 & 0x10 != 0
+    }
+
+    /// A new interface configuration takes effect at the next reset.
+    pub fn interface_configuration_pending(&self) -> bool {
+        self.0 & 0x20 != 0
+    }
+
+    pub fn acd_conflict_detected(&self) -> bool {
+        self.0 & 0x40 != 0
+    }
+
+    pub fn acd_fault(&self) -> bool {
+        self.0 & 0x80 != 0
+    }
+}
+
+/// Attribute 2.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConfigurationCapability(pub u32);
+
+impl ConfigurationCapability {
+    pub fn bootp_client(&self) -> bool {
+        self.0 & 0x01 != 0
+    }
+
+    pub fn dns_client(&self) -> bool {
+        self.0 & 0x02 != 0
+    }
+
+    pub fn dhcp_client(&self) -> bool {
+        self.0 & 0x04 != 0
+    }
+
+    /// The interface configuration attribute can be set.
+    pub fn configuration_settable(&self) -> bool {
+        self.0 & 0x10 != 0
+    }
+
+    pub fn hardware_configurable(&self) -> bool {
+        self.0 & 0x20 != 0
+    }
+
+    /// Interface configuration changes need a reset to take effect.
+    pub fn change_requires_reset(&self) -> bool {
+        self.0 & 0x40 != 0
+    }
+
+    pub fn acd_capable(&self) -> bool {
+        self.0 & 0x80 != 0
+    }
+}
+
+/// How the device obtains its configuration at startup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StartupConfiguration {
+    Static,
+    Bootp,
+    Dhcp,
+    Other(u8)
+}
+
+/// Attribute 3.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConfigurationControl {
+    pub startup: StartupConfiguration,
+    pub dns_enable: bool
+}
+
+impl ConfigurationControl {
+    pub fn from_bits(bits: u32) -> Self {
+        let startup = match bits & 0x0F {
+            0 => StartupConfiguration::Static,
+            1 => StartupConfiguration::Bootp,
+            2 => StartupConfiguration::Dhcp,
+            other => StartupConfiguration::Other(other as u8)
+        };
+
+        return Self { startup, dns_enable: bits & 0x10 != 0 };
+    }
+
+    pub fn bits(&self) -> u32 {
+        let startup = match self.startup {
+            StartupConfiguration::Static => 0,
+            StartupConfiguration::Bootp => 1,
+            StartupConfiguration::Dhcp => 2,
+            StartupConfiguration::Other(other) => other as u32
+        };
+
+        return startup | if self.dns_enable { 0x10 } else { 0 };
+    }
+}
+
+/// Attribute 5. Addresses are sent as UDINTs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InterfaceConfiguration {
+    pub ip_address: Ipv4Addr,
+    pub network_mask: Ipv4Addr,
+    pub gateway_address: Ipv4Addr,
+    pub name_server: Ipv4Addr,
+    pub name_server_2: Ipv4Addr,
+    pub domain_name: String
+}
+
+impl Serializable for InterfaceConfiguration {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized {
+        let (input, (ip_address, network_mask, gateway_address, name_server, name_server_2)) = tuple((le_u32, le_u32, le_u32, le_u32, le_u32))(input)?;
+        let (input, domain_name) = parse_string(input)?;
+
+        return Ok((input, InterfaceConfiguration { ip_address: ip_address.into(), network_mask: network_mask.into(), gateway_address: gateway_address.into(), name_server: name_server.into(), name_server_2: name_server_2.into(), domain_name }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        for address in [self.ip_address, self.network_mask, self.gateway_address, self.name_server, self.name_server_2] {
+            vec.extend_from_slice(&u32::from(address).to_le_bytes());
+        }
+        vec.extend(encode_string(&self.domain_name));
+
+        return vec;
+    }
+}
+
+impl InterfaceConfiguration {
+    pub fn validate(&self) -> Result<(), CipError> {
+        let ip = self.ip_address;
+        if ip.is_unspecified() || ip.is_broadcast() || ip.is_multicast() || ip.is_loopback() {
+            return Err(CipError::InvalidValue("IP address is not a unicast host address"));
+        }
+
+        let mask = u32::from(self.network_mask);
+        if mask == 0 || mask.leading_ones() + mask.trailing_zeros() != 32 {
+            return Err(CipError::InvalidValue("network mask is not contiguous"));
+        }
+
+        let host = u32::from(ip) & !mask;
+        if host == 0 || host == !mask {
+            return Err(CipError::InvalidValue("IP address is the network or broadcast address of its subnet"));
+        }
+
+        let gateway = u32::from(self.gateway_address);
+        if gateway != 0 && (gateway & mask != u32::from(ip) & mask || gateway == u32::from(ip)) {
+            return Err(CipError::InvalidValue("gateway is not another host on the local subnet"));
+        }
+
+        for name_server in [self.name_server, self.name_server_2] {
+            if !name_server.is_unspecified() && (name_server.is_broadcast() || name_server.is_multicast()) {
+                return Err(CipError::InvalidValue("name server is not a unicast address"));
+            }
+        }
+
+        if self.domain_name.len() > MAX_DOMAIN_NAME_LENGTH {
+            return Err(CipError::InvalidValue("domain name is too long"));
+        }
+
+        return Ok(());
+    }
+}
+
+/// Attribute 9.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MulticastConfig {
+    /// 0: addresses from the default algorithm, 1: from `num_mcast` and `mcast_start_addr`.
+    pub alloc_control: u8,
+    pub num_mcast: u16,
+    pub mcast_start_addr: Ipv4Addr
+}
+
+impl Serializable for MulticastConfig {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized {
+        let (input, (alloc_control, _, num_mcast, mcast_start_addr)) = tuple((le_u8, le_u8, le_u16, le_u32))(input)?;
+        return Ok((input, MulticastConfig { alloc_control, num_mcast, mcast_start_addr: mcast_start_addr.into() }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        vec.push(self.alloc_control);
+        vec.push(0);
+        vec.extend_from_slice(&self.num_mcast.to_le_bytes());
+        vec.extend_from_slice(&u32::from(self.mcast_start_addr).to_le_bytes());
+
+        return vec;
+    }
+}
+
+impl MulticastConfig {
+    pub fn validate(&self) -> Result<(), CipError> {
+        match self.alloc_control {
+            0 if self.num_mcast == 0 && self.mcast_start_addr.is_unspecified() => Ok(()),
+            0 => Err(CipError::InvalidValue("multicast range must be zero with default allocation")),
+            1 if self.num_mcast == 0 => Err(CipError::InvalidValue("multicast range is empty")),
+            1 if !self.mcast_start_addr.is_multicast() => Err(CipError::InvalidValue("multicast start address is not a multicast address")),
+            1 if u32::from(self.mcast_start_addr).checked_add(self.num_mcast as u32 - 1).is_none_or(|last| !Ipv4Addr::from(last).is_multicast()) => Err(CipError::InvalidValue("multicast range leaves the multicast address space")),
+            1 => Ok(()),
+            _ => Err(CipError::InvalidValue("unknown multicast allocation control"))
+        }
+    }
+}
+
+/// Attribute 11.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LastConflictDetected {
+    pub acd_activity: u8,
+    pub remote_mac: [u8; 6],
+    pub arp_pdu: [u8; 28]
+}
+
+impl Serializable for LastConflictDetected {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized {
+        let (input, (acd_activity, remote_mac, arp_pdu)) = tuple((le_u8, take(6u8), take(28u8)))(input)?;
+        return Ok((input, LastConflictDetected { acd_activity, remote_mac: remote_mac.try_into().expect("slice with incorrect length"), arp_pdu: arp_pdu.try_into().expect("slice with incorrect length") }));
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        vec.push(self.acd_activity);
+        vec.extend_from_slice(&self.remote_mac);
+        vec.extend_from_slice(&self.arp_pdu);
+
+        return vec;
+    }
+}
+
+/// Everything a TCP/IP Interface instance reports. Optional attributes the
+/// device does not implement are `None`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TcpIpInterface {
+    pub status: InterfaceStatus,
+    pub configuration_capability: ConfigurationCapability,
+    pub configuration_control: ConfigurationControl,
+    /// Padded EPATH to the physical link object, see [`TcpIpInterface::physical_link_epath`].
+    pub physical_link_path: Vec<u8>,
+    pub interface_configuration: InterfaceConfiguration,
+    pub host_name: String,
+    pub time_to_live: Option<u8>,
+    pub multicast_config: Option<MulticastConfig>,
+    pub select_acd: Option<bool>,
+    pub last_conflict_detected: Option<LastConflictDetected>,
+    pub quick_connect: Option<bool>,
+    pub encapsulation_inactivity_timeout: Option<u16>
+}
+
+impl TcpIpInterface {
+    pub fn physical_link_epath(&self) -> Result<EPath, CipError> {
+        let (_, epath) = EPath::parse(&self.physical_link_path)?;
+        return Ok(epath);
+    }
+}
+
+/// A settable TCP/IP Interface attribute with its new value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TcpIpSetting {
+    ConfigurationControl(ConfigurationControl),
+    InterfaceConfiguration(InterfaceConfiguration),
+    HostName(String),
+    TimeToLive(u8),
+    MulticastConfig(MulticastConfig),
+    SelectAcd(bool),
+    QuickConnect(bool),
+    EncapsulationInactivityTimeout(u16)
+}
+
+impl TcpIpSetting {
+    pub fn attribute(&self) -> TcpIpAttribute {
+        match self {
+            TcpIpSetting::ConfigurationControl(_) => TcpIpAttribute::ConfigurationControl,
+            TcpIpSetting::InterfaceConfiguration(_) => TcpIpAttribute::InterfaceConfiguration,
+            TcpIpSetting::HostName(_) => TcpIpAttribute::HostName,
+            TcpIpSetting::TimeToLive(_) => TcpIpAttribute::TimeToLive,
+            TcpIpSetting::MulticastConfig(_) => TcpIpAttribute::MulticastConfig,
+            TcpIpSetting::SelectAcd(_) => TcpIpAttribute::SelectAcd,
+            TcpIpSetting::QuickConnect(_) => TcpIpAttribute::QuickConnect,
+            TcpIpSetting::EncapsulationInactivityTimeout(_) => TcpIpAttribute::EncapsulationInactivityTimeout
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), CipError> {
+        match self {
+            TcpIpSetting::ConfigurationControl(control) => match control.startup {
+                StartupConfiguration::Other(_) => Err(CipError::InvalidValue("unknown startup configuration")),
+                _ => Ok(())
+            },
+            TcpIpSetting::InterfaceConfiguration(configuration) => configuration.validate(),
+            TcpIpSetting::HostName(name) => {
+                let valid_chars = name.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-');
+                if name.len() > MAX_HOST_NAME_LENGTH || !valid_chars || name.starts_with('-') || name.ends_with('-') {
+                    return Err(CipError::InvalidValue("host name is not a valid DNS label"));
+                }
+
+                Ok(())
+            }
+            TcpIpSetting::TimeToLive(0) => Err(CipError::InvalidValue("time to live must be at least 1")),
+            TcpIpSetting::MulticastConfig(config) => config.validate(),
+            TcpIpSetting::EncapsulationInactivityTimeout(timeout) if *timeout > MAX_INACTIVITY_TIMEOUT => Err(CipError::InvalidValue("inactivity timeout is longer than 3600 seconds")),
+            _ => Ok(())
+        }
+    }
+
+    /// Encoded attribute value.
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            TcpIpSetting::ConfigurationControl(control) => control.bits().to_le_bytes().to_vec(),
+            TcpIpSetting::InterfaceConfiguration(configuration) => configuration.serialize(),
+            TcpIpSetting::HostName(name) => encode_string(name),
+            TcpIpSetting::TimeToLive(ttl) => alloc::vec![*ttl],
+            TcpIpSetting::MulticastConfig(config) => config.serialize(),
+            TcpIpSetting::SelectAcd(enabled) | TcpIpSetting::QuickConnect(enabled) => alloc::vec![*enabled as u8],
+            TcpIpSetting::EncapsulationInactivityTimeout(timeout) => timeout.to_le_bytes().to_vec()
+        }
+    }
+}
+
+/// A validated change to the IP configuration of a device.
+///
+/// Changing these attributes can make the device unreachable, so a change is
+/// only sent once [`TcpIpChange::confirm`] has been called on it.
+pub struct TcpIpChange {
+    setting: TcpIpSetting,
+    confirmed: bool
+}
+
+impl TcpIpChange {
+    pub fn new(setting: TcpIpSetting) -> Result<Self, CipError> {
+        setting.validate()?;
+        return Ok(Self { setting, confirmed: false });
+    }
+
+    pub fn confirm(mut self) -> Self {
+        self.confirmed = true;
+        return self;
+    }
+
+    pub fn setting(&self) -> &TcpIpSetting {
+        &self.setting
+    }
+}
+
+impl CipClient {
+    async fn get_tcpip_attribute(&mut self, instance: u32, attribute: TcpIpAttribute) -> Result<Vec<u8>, CipError> {
+        let response = self.attribute_request(CipService::GetAttributeSingle as u8, CipClass::TCPIPObject as u32, instance, attribute as u32, alloc::vec![]).await?;
+        response.check()?;
+        return Ok(response.data);
+    }
+
+    /// Reads every attribute of TCP/IP Interface `instance`.
+    pub async fn get_tcpip_interface(&mut self, instance: u32) -> Result<TcpIpInterface, CipError> {
+        let data = self.get_tcpip_attribute(instance, TcpIpAttribute::Status).await?;
+        let (_, status) = le_u32::<_, Error<&[u8]>>(data.as_slice())?;
+        let data = self.get_tcpip_attribute(instance, TcpIpAttribute::ConfigurationCapability).await?;
+        let (_, capability) = le_u32::<_, Error<&[u8]>>(data.as_slice())?;
+        let data = self.get_tcpip_attribute(instance, TcpIpAttribute::ConfigurationControl).await?;
+        let (_, control) = le_u32::<_, Error<&[u8]>>(data.as_slice())?;
+
+        let data = self.get_tcpip_attribute(instance, TcpIpAttribute::PhysicalLinkObject).await?;
+        let (path, size) = le_u16::<_, Error<&[u8]>>(data.as_slice())?;
+        let (_, physical_link_path) = take::<_, _, Error<&[u8]>>(size as usize * 2)(path)?;
+        let physical_link_path = physical_link_path.to_vec();
+
+        let data = self.get_tcpip_attribute(instance, TcpIpAttribute::InterfaceConfiguration).await?;
+        let (_, interface_configuration) = InterfaceConfiguration::deserialize(&data)?;
+        let data = self.get_tcpip_attribute(instance, TcpIpAttribute::HostName).await?;
+        let (_, host_name) = parse_string(&data)?;
+
+        let mut time_to_live = None;
+        if let Some(data) = optional_attribute(self.get_tcpip_attribute(instance, TcpIpAttribute::TimeToLive).await)? {
+            time_to_live = Some(le_u8::<_, Error<&[u8]>>(data.as_slice())?.1);
+        }
+
+        let mut multicast_config = None;
+        if let Some(data) = optional_attribute(self.get_tcpip_attribute(instance, TcpIpAttribute::MulticastConfig).await)? {
+            multicast_config = Some(MulticastConfig::deserialize(&data)?.1);
+        }
+
+        let mut select_acd = None;
+        if let Some(data) = optional_attribute(self.get_tcpip_attribute(instance, TcpIpAttribute::SelectAcd).await)? {
+            select_acd = Some(le_u8::<_, Error<&[u8]>>(data.as_slice())?.1 != 0);
+        }
+
+        let mut last_conflict_detected = None;
+        if let Some(data) = optional_attribute(self.get_tcpip_attribute(instance, TcpIpAttribute::LastConflictDetected).await)? {
+            last_conflict_detected = Some(LastConflictDetected::deserialize(&data)?.1);
+        }
+
+        let mut quick_connect = None;
+        if let Some(data) = optional_attribute(self.get_tcpip_attribute(instance, TcpIpAttribute::QuickConnect).await)? {
+            quick_connect = Some(le_u8::<_, Error<&[u8]>>(data.as_slice())?.1 != 0);
+        }
+
+        let mut encapsulation_inactivity_timeout = None;
+        if let Some(data) = optional_attribute(self.get_tcpip_attribute(instance, TcpIpAttribute::EncapsulationInactivityTimeout).await)? {
+            encapsulation_inactivity_timeout = Some(le_u16::<_, Error<&[u8]>>(data.as_slice())?.1);
+        }
+
+        return Ok(TcpIpInterface { status: InterfaceStatus(status), configuration_capability: ConfigurationCapability(capability), configuration_control: ConfigurationControl::from_bits(control), physical_link_path, interface_configuration, host_name, time_to_live, multicast_config, select_acd, last_conflict_detected, quick_connect, encapsulation_inactivity_timeout });
+    }
+
+    /// Writes a confirmed change to TCP/IP Interface `instance`.
+    pub async fn set_tcpip_attribute(&mut self, instance: u32, change: &TcpIpChange) -> Result<(), CipError> {
+        if !change.confirmed {
+            return Err(CipError::NotConfirmed);
+        }
+
+        let setting = change.setting();
+        setting.validate()?;
+
+        let response = self.attribute_request(CipService::SetAttributeSingle as u8, CipClass::TCPIPObject as u32, instance, setting.attribute() as u32, setting.encode()).await?;
+        return response.check();
+    }
+}
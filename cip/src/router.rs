@@ -0,0 +1,173 @@
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+
+use crate::{cip::{CipClass, CipService, EPath, LogicalType, MessageRouterRequest, MessageRouterResponse}, common::Serializable, objects::{connection_manager::UnconnectedSendRequest, message_router::{MessageRouter, MessageRouterResponseStatusCodes}}};
+
+const UNCONNECTED_SEND: u8 = 0x52;
+
+/// Object a request is addressed to, taken from the logical segments of its path.
+/// Instance 0 addresses the class itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ObjectPath {
+    pub class: u32,
+    pub instance: u32,
+    pub attribute: Option<u32>,
+    pub member: Option<u32>
+}
+
+impl ObjectPath {
+    /// Reads class, instance, attribute and member from `epath`, in that order.
+    /// Returns `None` for any other kind of path.
+    pub fn from_epath(epath: &EPath) -> Option<Self> {
+        let mut segments = Vec::new();
+        for segment in &epath.attributes {
+            let logical = segment.as_logical()?;
+            segments.push((logical.logical_type(), logical.value()));
+        }
+
+        let mut segments = segments.into_iter().peekable();
+        let mut next = |logical_type: LogicalType| segments.next_if(|(segment_type, _)| *segment_type == logical_type as u8).map(|(_, value)| value);
+
+        let class = next(LogicalType::ClassId)?;
+        let instance = next(LogicalType::InstanceId).unwrap_or(0);
+        let attribute = next(LogicalType::AttributeId);
+        let member = next(LogicalType::MemberId);
+
+        if segments.next().is_some() {
+            return None;
+        }
+
+        return Some(ObjectPath { class, instance, attribute, member });
+    }
+}
+
+/// General status and additional status words returned for a failed service.
+/// Some services, such as Get_Attribute_List, still return data on failure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServiceError {
+    pub general_status: u8,
+    pub additional_status: Vec<u16>,
+    pub data: Vec<u8>
+}
+
+impl From<MessageRouterResponseStatusCodes> for ServiceError {
+    fn from(status: MessageRouterResponseStatusCodes) -> Self {
+        Self { general_status: status as u8, additional_status: Vec::new(), data: Vec::new() }
+    }
+}
+
+/// Response data of a successful service, or the status to reply with.
+pub type ServiceResult = Result<Vec<u8>, ServiceError>;
+
+/// A CIP class hosted by a [`Router`].
+///
+/// The router only hands over requests whose path names this class and an
+/// instance the object reports through [`CipObject::has_instance`].
+pub trait CipObject: Send {
+    fn class_id(&self) -> u16;
+
+    fn has_instance(&self, instance: u32) -> bool;
+
+    /// Handles a service addressed to the class (instance 0).
+    fn class_service(&mut self, _service: u8, _path: &ObjectPath, _data: &[u8]) -> ServiceResult {
+        return Err(MessageRouterResponseStatusCodes::ServiceNotSupported.into());
+    }
+
+    /// Handles a service addressed to one of the instances.
+    fn instance_service(&mut self, service: u8, path: &ObjectPath, data: &[u8]) -> ServiceResult;
+}
+
+/// Server-side Message Router that dispatches explicit messages to the
+/// registered objects and answers for the Message Router object itself.
+#[derive(Default)]
+pub struct Router {
+    objects: BTreeMap<u16, Box<dyn CipObject>>
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self { objects: BTreeMap::new() }
+    }
+
+    /// Adds `object`, replacing any object already registered for its class.
+    pub fn register(&mut self, object: impl CipObject + 'static) {
+        self.objects.insert(object.class_id(), Box::new(object));
+    }
+
+    /// Classes the router answers for, including the Message Router.
+    pub fn classes(&self) -> Vec<u16> {
+        let mut classes: Vec<u16> = self.objects.keys().copied().collect();
+        if !classes.contains(&(CipClass::MessageRouter as u16)) {
+            classes.push(CipClass::MessageRouter as u16);
+            classes.sort();
+        }
+
+        return classes;
+    }
+
+    /// Handles a serialized Message Router request and returns the serialized response.
+    pub fn handle(&mut self, request: &[u8]) -> Vec<u8> {
+        let response = match MessageRouterRequest::deserialize(request) {
+            Ok((_, request)) => self.route(&request),
+            Err(_) => {
+                let service = request.first().copied().unwrap_or(0);
+                error_response(service, MessageRouterResponseStatusCodes::PathSegmentError.into())
+            }
+        };
+
+        return response.serialize();
+    }
+
+    /// Dispatches `request` to the object its path addresses.
+    pub fn route(&mut self, request: &MessageRouterRequest) -> MessageRouterResponse {
+        let path = match ObjectPath::from_epath(&request.epath) {
+            Some(path) => path,
+            None => return error_response(request.service, MessageRouterResponseStatusCodes::PathSegmentError.into())
+        };
+
+        // Requests routed to this device through the Connection Manager are executed locally.
+        if request.service == UNCONNECTED_SEND && path.class == CipClass::ConnectionManager as u32 {
+            return match UnconnectedSendRequest::deserialize(&request.data) {
+                Ok((_, unconnected_send)) => self.route(&unconnected_send.message_request),
+                Err(_) => error_response(request.service, MessageRouterResponseStatusCodes::NotEnoughData.into())
+            };
+        }
+
+        let result = match u16::try_from(path.class).ok().and_then(|class| self.objects.get_mut(&class)) {
+            Some(object) if path.instance == 0 => object.class_service(request.service, &path, &request.data),
+            Some(object) if object.has_instance(path.instance) => object.instance_service(request.service, &path, &request.data),
+            Some(_) => Err(MessageRouterResponseStatusCodes::PathDestinationUnknown.into()),
+            None if path.class == CipClass::MessageRouter as u32 => self.message_router_service(request.service, &path),
+            None => Err(MessageRouterResponseStatusCodes::PathDestinationUnknown.into())
+        };
+
+        return match result {
+            Ok(data) => MessageRouterResponse { service: request.service | 0x80, reserved: 0, general_status: MessageRouterResponseStatusCodes::Success as u8, size_of_additional_status: 0, additional_status: Vec::new(), data },
+            Err(error) => error_response(request.service, error)
+        };
+    }
+
+    /// Services of the Message Router object (instance 1), unless an object replaced it.
+    fn message_router_service(&self, service: u8, path: &ObjectPath) -> ServiceResult {
+        if path.instance != 1 {
+            return Err(MessageRouterResponseStatusCodes::PathDestinationUnknown.into());
+        }
+
+        let object_list = MessageRouter { objects: self.classes() };
+        if service == CipService::GetAttributesAll as u8 {
+            return Ok(object_list.serialize());
+        }
+
+        if service != CipService::GetAttributeSingle as u8 {
+            return Err(MessageRouterResponseStatusCodes::ServiceNotSupported.into());
+        }
+
+        return match path.attribute {
+            Some(1) => Ok(object_list.serialize()),
+            _ => Err(MessageRouterResponseStatusCodes::AttributeNotSupported.into())
+        };
+    }
+}
+
+fn error_response(service: u8, error: ServiceError) -> MessageRouterResponse {
+    return MessageRouterResponse { service: service | 0x80, reserved: 0, general_status: error.general_status, size_of_additional_status: error.additional_status.len() as u8, additional_status: error.additional_status, data: error.data };
+}
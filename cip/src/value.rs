@@ -0,0 +1,136 @@
+use alloc::{string::String, vec::Vec};
+use nom::{bytes::complete::take, combinator::rest, number::complete::{le_f32, le_f64, le_i16, le_i32, le_i64, le_i8, le_u16, le_u32, le_u64, le_u8}, IResult};
+
+use crate::cip::CipDataType;
+
+/// A typed attribute value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CipValue {
+    Bool(bool),
+    Sint(i8),
+    Int(i16),
+    Dint(i32),
+    Lint(i64),
+    Usint(u8),
+    Uint(u16),
+    Udint(u32),
+    Ulint(u64),
+    Real(f32),
+    Lreal(f64),
+    Byte(u8),
+    Word(u16),
+    Dword(u32),
+    Lword(u64),
+    ShortString(String),
+    String(String),
+    /// Encoded value of any other type.
+    Other(CipDataType, Vec<u8>)
+}
+
+impl CipValue {
+    pub fn data_type(&self) -> CipDataType {
+        match self {
+            CipValue::Bool(_) => CipDataType::Bool,
+            CipValue::Sint(_) => CipDataType::Sint,
+            CipValue::Int(_) => CipDataType::Int,
+            CipValue::Dint(_) => CipDataType::Dint,
+            CipValue::Lint(_) => CipDataType::Lint,
+            CipValue::Usint(_) => CipDataType::Usint,
+            CipValue::Uint(_) => CipDataType::Uint,
+            CipValue::Udint(_) => CipDataType::Udint,
+            CipValue::Ulint(_) => CipDataType::Ulint,
+            CipValue::Real(_) => CipDataType::Real,
+            CipValue::Lreal(_) => CipDataType::Lreal,
+            CipValue::Byte(_) => CipDataType::Byte,
+            CipValue::Word(_) => CipDataType::Word,
+            CipValue::Dword(_) => CipDataType::Dword,
+            CipValue::Lword(_) => CipDataType::Lword,
+            CipValue::ShortString(_) => CipDataType::ShortString,
+            CipValue::String(_) => CipDataType::String,
+            CipValue::Other(data_type, _) => *data_type
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            CipValue::Bool(value) => alloc::vec![*value as u8],
+            CipValue::Sint(value) => value.to_le_bytes().to_vec(),
+            CipValue::Int(value) => value.to_le_bytes().to_vec(),
+            CipValue::Dint(value) => value.to_le_bytes().to_vec(),
+            CipValue::Lint(value) => value.to_le_bytes().to_vec(),
+            CipValue::Usint(value) | CipValue::Byte(value) => alloc::vec![*value],
+            CipValue::Uint(value) | CipValue::Word(value) => value.to_le_bytes().to_vec(),
+            CipValue::Udint(value) | CipValue::Dword(value) => value.to_le_bytes().to_vec(),
+            CipValue::Ulint(value) | CipValue::Lword(value) => value.to_le_bytes().to_vec(),
+            CipValue::Real(value) => value.to_le_bytes().to_vec(),
+            CipValue::Lreal(value) => value.to_le_bytes().to_vec(),
+            CipValue::ShortString(value) => {
+                let mut vec = alloc::vec![value.len() as u8];
+                vec.extend_from_slice(value.as_bytes());
+                vec
+            }
+            CipValue::String(value) => {
+                let mut vec = (value.len() as u16).to_le_bytes().to_vec();
+                vec.extend_from_slice(value.as_bytes());
+                vec
+            }
+            CipValue::Other(_, data) => data.clone()
+        }
+    }
+
+    /// Numeric value, for scaling and range checks.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            CipValue::Bool(value) => Some(*value as u8 as f64),
+            CipValue::Sint(value) => Some(*value as f64),
+            CipValue::Int(value) => Some(*value as f64),
+            CipValue::Dint(value) => Some(*value as f64),
+            CipValue::Lint(value) => Some(*value as f64),
+            CipValue::Usint(value) | CipValue::Byte(value) => Some(*value as f64),
+            CipValue::Uint(value) | CipValue::Word(value) => Some(*value as f64),
+            CipValue::Udint(value) | CipValue::Dword(value) => Some(*value as f64),
+            CipValue::Ulint(value) | CipValue::Lword(value) => Some(*value as f64),
+            CipValue::Real(value) => Some(*value as f64),
+            CipValue::Lreal(value) => Some(*value),
+            CipValue::ShortString(_) | CipValue::String(_) | CipValue::Other(..) => None
+        }
+    }
+
+    /// Parses one value of `data_type`. Types without a known size take the rest of `input`.
+    pub fn parse(data_type: CipDataType, input: &[u8]) -> IResult<&[u8], Self> {
+        match data_type {
+            CipDataType::Bool => le_u8(input).map(|(input, value)| (input, CipValue::Bool(value != 0))),
+            CipDataType::Sint => le_i8(input).map(|(input, value)| (input, CipValue::Sint(value))),
+            CipDataType::Int => le_i16(input).map(|(input, value)| (input, CipValue::Int(value))),
+            CipDataType::Dint => le_i32(input).map(|(input, value)| (input, CipValue::Dint(value))),
+            CipDataType::Lint => le_i64(input).map(|(input, value)| (input, CipValue::Lint(value))),
+            CipDataType::Usint => le_u8(input).map(|(input, value)| (input, CipValue::Usint(value))),
+            CipDataType::Uint => le_u16(input).map(|(input, value)| (input, CipValue::Uint(value))),
+            CipDataType::Udint => le_u32(input).map(|(input, value)| (input, CipValue::Udint(value))),
+            CipDataType::Ulint => le_u64(input).map(|(input, value)| (input, CipValue::Ulint(value))),
+            CipDataType::Real => le_f32(input).map(|(input, value)| (input, CipValue::Real(value))),
+            CipDataType::Lreal => le_f64(input).map(|(input, value)| (input, CipValue::Lreal(value))),
+            CipDataType::Byte => le_u8(input).map(|(input, value)| (input, CipValue::Byte(value))),
+            CipDataType::Word => le_u16(input).map(|(input, value)| (input, CipValue::Word(value))),
+            CipDataType::Dword => le_u32(input).map(|(input, value)| (input, CipValue::Dword(value))),
+            CipDataType::Lword => le_u64(input).map(|(input, value)| (input, CipValue::Lword(value))),
+            CipDataType::ShortString => {
+                let (input, length) = le_u8(input)?;
+                let (input, chars) = take(length)(input)?;
+                Ok((input, CipValue::ShortString(String::from_utf8_lossy(chars).into())))
+            }
+            CipDataType::String => {
+                let (input, length) = le_u16(input)?;
+                let (input, chars) = take(length)(input)?;
+                Ok((input, CipValue::String(String::from_utf8_lossy(chars).into())))
+            }
+            data_type => {
+                let (input, data) = match data_type.size() {
+                    Some(size) => take(size)(input)?,
+                    None => rest(input)?
+                };
+                Ok((input, CipValue::Other(data_type, data.to_vec())))
+            }
+        }
+    }
+}
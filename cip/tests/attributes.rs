@@ -0,0 +1,70 @@
+use cip::{attributes::{Attribute, AttributeObject, AttributeTable}, cip::CipDataType, router::Router};
+
+fn router() -> Router {
+    let table = AttributeTable::new()
+        .with(Attribute::get(1, CipDataType::Uint, vec![0x2A, 0]))
+        .with(Attribute::get(2, CipDataType::Udint, vec![1, 2, 3, 4]).settable().nonvolatile())
+        .with(Attribute::get(3, CipDataType::ShortString, vec![2, b'o', b'k']).settable_once().optional());
+    let object = AttributeObject::new(0x64, 2).with_instance(1, table.clone()).with_instance(3, table);
+
+    let mut router = Router::new();
+    router.register(object);
+    router
+}
+
+#[test]
+fn table_generates_get_and_set_services() {
+    let mut router = router();
+
+    // Get_Attribute_Single of attribute 1, then Get_Attributes_All.
+    assert_eq!(router.handle(&[0x0E, 0x03, 0x20, 0x64, 0x24, 0x01, 0x30, 0x01]), vec![0x8E, 0, 0, 0, 0x2A, 0]);
+    assert_eq!(router.handle(&[0x01, 0x02, 0x20, 0x64, 0x24, 0x01]), vec![0x81, 0, 0, 0, 0x2A, 0, 1, 2, 3, 4, 2, b'o', b'k']);
+
+    // Read-only, wrong size, then a valid set.
+    assert_eq!(router.handle(&[0x10, 0x03, 0x20, 0x64, 0x24, 0x01, 0x30, 0x01, 0, 0]), vec![0x90, 0, 0x0E, 0]);
+    assert_eq!(router.handle(&[0x10, 0x03, 0x20, 0x64, 0x24, 0x01, 0x30, 0x02, 9, 9]), vec![0x90, 0, 0x13, 0]);
+    assert_eq!(router.handle(&[0x10, 0x03, 0x20, 0x64, 0x24, 0x01, 0x30, 0x02, 9, 9, 9, 9, 9]), vec![0x90, 0, 0x15, 0]);
+    assert_eq!(router.handle(&[0x10, 0x03, 0x20, 0x64, 0x24, 0x01, 0x30, 0x02, 9, 9, 9, 9]), vec![0x90, 0, 0, 0]);
+    assert_eq!(router.handle(&[0x0E, 0x03, 0x20, 0x64, 0x24, 0x01, 0x30, 0x02]), vec![0x8E, 0, 0, 0, 9, 9, 9, 9]);
+
+    // Settable once.
+    assert_eq!(router.handle(&[0x10, 0x03, 0x20, 0x64, 0x24, 0x01, 0x30, 0x03, 1, b'a']), vec![0x90, 0, 0, 0]);
+    assert_eq!(router.handle(&[0x10, 0x03, 0x20, 0x64, 0x24, 0x01, 0x30, 0x03, 1, b'b']), vec![0x90, 0, 0x21, 0]);
+}
+
+#[test]
+fn table_answers_get_attribute_list_per_attribute() {
+    let mut router = router();
+
+    assert_eq!(router.handle(&[0x03, 0x02, 0x20, 0x64, 0x24, 0x03, 0x02, 0x00, 0x01, 0x00, 0x09, 0x00]), vec![0x83, 0, 0x0A, 0, 2, 0, 1, 0, 0, 0, 0x2A, 0, 9, 0, 0x14, 0]);
+}
+
+#[test]
+fn table_answers_set_attribute_list_per_attribute() {
+    let mut router = router();
+
+    // Attribute 2 is written, read-only attribute 1 is rejected.
+    assert_eq!(router.handle(&[0x04, 0x02, 0x20, 0x64, 0x24, 0x01, 0x02, 0x00, 0x02, 0x00, 5, 6, 7, 8, 0x01, 0x00, 0, 0]), vec![0x84, 0, 0x0A, 0, 2, 0, 2, 0, 0, 0, 1, 0, 0x0E, 0]);
+    assert_eq!(router.handle(&[0x0E, 0x03, 0x20, 0x64, 0x24, 0x01, 0x30, 0x02]), vec![0x8E, 0, 0, 0, 5, 6, 7, 8]);
+
+    // The string length tells where the next attribute starts.
+    assert_eq!(router.handle(&[0x04, 0x02, 0x20, 0x64, 0x24, 0x03, 0x02, 0x00, 0x03, 0x00, 1, b'x', 0x02, 0x00, 1, 0, 0, 0]), vec![0x84, 0, 0, 0, 2, 0, 3, 0, 0, 0, 2, 0, 0, 0]);
+}
+
+#[test]
+fn class_attributes_are_generated() {
+    let mut router = router();
+
+    // Revision, max instance, number of instances.
+    assert_eq!(router.handle(&[0x0E, 0x03, 0x20, 0x64, 0x24, 0x00, 0x30, 0x01]), vec![0x8E, 0, 0, 0, 2, 0]);
+    assert_eq!(router.handle(&[0x0E, 0x03, 0x20, 0x64, 0x24, 0x00, 0x30, 0x02]), vec![0x8E, 0, 0, 0, 3, 0]);
+    assert_eq!(router.handle(&[0x0E, 0x03, 0x20, 0x64, 0x24, 0x00, 0x30, 0x03]), vec![0x8E, 0, 0, 0, 2, 0]);
+    // Optional attribute list, max class and instance attribute IDs.
+    assert_eq!(router.handle(&[0x0E, 0x03, 0x20, 0x64, 0x24, 0x00, 0x30, 0x04]), vec![0x8E, 0, 0, 0, 1, 0, 3, 0]);
+    assert_eq!(router.handle(&[0x0E, 0x03, 0x20, 0x64, 0x24, 0x00, 0x30, 0x06]), vec![0x8E, 0, 0, 0, 7, 0]);
+    assert_eq!(router.handle(&[0x0E, 0x03, 0x20, 0x64, 0x24, 0x00, 0x30, 0x07]), vec![0x8E, 0, 0, 0, 3, 0]);
+    assert_eq!(router.handle(&[0x10, 0x03, 0x20, 0x64, 0x24, 0x00, 0x30, 0x01, 0, 0]), vec![0x90, 0, 0x0E, 0]);
+
+    // Instance 2 does not exist.
+    assert_eq!(router.handle(&[0x0E, 0x03, 0x20, 0x64, 0x24, 0x02, 0x30, 0x01]), vec![0x8E, 0, 0x05, 0]);
+}
@@ -0,0 +1,206 @@
+use cip::{cip::{CipDataType, EPath}, common::Serializable, eds::{ConnectionOptions, Eds, EdsField, EdsPathItem, EdsReference}, error::CipError, objects::{file::FileRevision, parameter::Scaling}, value::CipValue};
+
+const EDS: &str = r#"$ Test drive EDS
+[File]
+        DescText = "Test drive";
+        CreateDate = 04-20-2024;          $ created with a text editor
+        CreateTime = 10:15:00;
+        Revision = 1.2;
+        HomeURL = "http://example.com/"
+                  "drive.eds";
+
+[Device]
+        VendCode = 1;
+        VendName = "Example, Inc.";
+        ProdType = 2;
+        ProdTypeStr = "AC Drive";
+        ProdCode = 0x36;
+        MajRev = 3;
+        MinRev = 1;
+        ProdName = "Drive \"A\"";
+        Catalog = "DRV-1";
+
+[Params]
+        Param1 =
+                0,                      $ reserved
+                6, "20 2A 24 01 30 07", $ link path
+                0x0004,                 $ descriptor
+                0xC7, 2,                $ data type and size
+                "Speed", "rpm", "Motor speed setpoint",
+                0, 6000, 1500,          $ min, max, default
+                1, 1, 1, 0,             $ scaling
+                ,,,,                    $ scaling links
+                1;                      $ decimal places
+        Param2 = 0, , , 0x0010, 0xCA, 4, "Current", "A", "", -10.5, 10.5, 0.0;
+        Param3 = 0, , , 0, 0xC6, 1, "Mode", "", "", 0, 2, 0;
+        Param4 = 0, , , 0, 0xC8, 4, "RPI", "us", "", 2000, 100000, 10000;
+        Param5 = 0, , , 0, 0xC6, 1, "Config instance", "", "", 100, 200, 151;
+
+[EnumPar]
+        Enum3 = 0, "Stop", 1, "Run", 2, "Jog";
+
+[Assembly]
+        Object_Name = "Assembly Object";
+        Assem100 = "Input", "20 04 24 64", 4, 0x0000, , ,
+                16, Param1,
+                16, ;
+        Assem150 = "Output", "20 04 24 96", , 0x0000, , ,
+                16, Param1,
+                8, Param3,
+                8, ;
+        Assem151 = "Config", "20 04 24 97", , 0x0000, , ,
+                8, Param3,
+                16, Param1;
+
+[Connection Manager]
+        Connection1 =
+                0x04010002,             $ trigger and transport
+                0x44640405,             $ connection parameters
+                Param4, 2, Assem150,    $ O->T RPI, size, format
+                Param4, 4, Assem100,    $ T->O RPI, size, format
+                , Assem151,             $ config #1
+                , ,                     $ config #2
+                "Exclusive Owner",
+                "",
+                "20 04 24 [Param5] 2C 96 2C 64";
+"#;
+
+#[test]
+fn generic_sections_and_fields() {
+    let eds = Eds::parse(EDS).unwrap();
+    assert_eq!(eds.sections.len(), 6);
+
+    let device = eds.section("device").unwrap();
+    assert_eq!(device.entry("vendname").unwrap().fields, vec![EdsField::Quoted("Example, Inc.".into())]);
+    assert_eq!(device.entry("ProdCode").unwrap().field(0).as_number(), Some(0x36));
+
+    let assembly = eds.section("Assembly").unwrap().entry("Assem100").unwrap();
+    assert_eq!(assembly.fields.len(), 10);
+    assert_eq!(assembly.line, 42);
+}
+
+#[test]
+fn typed_file_and_device() {
+    let eds = Eds::parse(EDS).unwrap();
+
+    let file = eds.file_info().unwrap();
+    assert_eq!(file.description.as_deref(), Some("Test drive"));
+    assert_eq!(file.create_date.as_deref(), Some("04-20-2024"));
+    assert_eq!(file.revision, Some(FileRevision { major: 1, minor: 2 }));
+    assert_eq!(file.home_url.as_deref(), Some("http://example.com/drive.eds"));
+
+    let device = eds.device().unwrap();
+    assert_eq!(device.vendor_id, 1);
+    assert_eq!(device.product_code, 0x36);
+    assert_eq!((device.major_revision, device.minor_revision), (3, 1));
+    assert_eq!(device.product_name, "Drive \"A\"");
+    assert_eq!(device.icon, None);
+}
+
+#[test]
+fn typed_params() {
+    let eds = Eds::parse(EDS).unwrap();
+    let params = eds.params().unwrap();
+    assert_eq!(params.len(), 5);
+
+    let speed = &params[0];
+    assert_eq!(speed.link_path, vec![0x20, 0x2A, 0x24, 0x01, 0x30, 0x07]);
+    assert_eq!(speed.data_type, CipDataType::Uint);
+    assert_eq!(speed.name, "Speed");
+    assert_eq!(speed.maximum, Some(CipValue::Uint(6000)));
+    assert_eq!(speed.default, Some(CipValue::Uint(1500)));
+    assert_eq!(speed.scaling, Some(Scaling { multiplier: 1, divisor: 1, base: 1, offset: 0, decimal_precision: 1 }));
+
+    let current = &params[1];
+    assert!(current.descriptor.read_only());
+    assert_eq!(current.minimum, Some(CipValue::Real(-10.5)));
+    assert_eq!(current.scaling, None);
+
+    let mode = eds.param(3).unwrap().unwrap();
+    assert_eq!(mode.enumeration, vec![(0, "Stop".into()), (1, "Run".into()), (2, "Jog".into())]);
+    assert!(eds.param(9).unwrap().is_none());
+
+    // Older files keep the enumeration next to the parameter.
+    let inline = Eds::parse("[Params]\nParam1 = 0, , , 0, 0xC6, 1, \"Mode\", \"\", \"\", 0, 1, 0;\nEnum1 = 0, \"Off\", 1, \"On\";\n").unwrap();
+    assert_eq!(inline.param(1).unwrap().unwrap().enumeration, vec![(0, "Off".into()), (1, "On".into())]);
+}
+
+#[test]
+fn typed_assemblies_and_connections() {
+    let eds = Eds::parse(EDS).unwrap();
+
+    let input = eds.assembly(100).unwrap().unwrap();
+    assert_eq!(input.name, "Input");
+    assert_eq!(input.path, vec![EdsPathItem::Byte(0x20), EdsPathItem::Byte(0x04), EdsPathItem::Byte(0x24), EdsPathItem::Byte(0x64)]);
+    assert_eq!(input.members.len(), 2);
+    assert_eq!(input.members[0].reference, EdsReference::Param(1));
+    assert_eq!(input.members[1].reference, EdsReference::None);
+    assert_eq!(input.byte_size(), 4);
+
+    let output = eds.assembly(150).unwrap().unwrap();
+    assert_eq!(output.size, None);
+    assert_eq!(output.byte_size(), 4);
+
+    let connection = eds.connection(1).unwrap().unwrap();
+    assert_eq!(connection.trigger_transport, 0x04010002);
+    assert_eq!(connection.ot_rpi, EdsReference::Param(4));
+    assert_eq!(connection.ot_size, EdsReference::Value(2));
+    assert_eq!(connection.to_format, EdsReference::Assembly(100));
+    assert_eq!(connection.config1_size, EdsReference::None);
+    assert_eq!(connection.config1_format, EdsReference::Assembly(151));
+    assert_eq!(connection.name, "Exclusive Owner");
+    assert_eq!(connection.path[3], EdsPathItem::Param(5));
+    assert_eq!(connection.path.len(), 8);
+}
+
+#[test]
+fn forward_open_from_connection_entry() {
+    let eds = Eds::parse(EDS).unwrap();
+    let request = eds.forward_open(1, &ConnectionOptions::default()).unwrap();
+
+    assert_eq!(request.transport_class, 0x01);
+    assert_eq!((request.ot_rpi, request.to_rpi), (10000, 10000));
+    // 2 data bytes, run/idle header and sequence count; point-to-point, scheduled.
+    assert_eq!(request.ot_network_parameters, 0x4808);
+    // 4 data bytes and sequence count; multicast, scheduled.
+    assert_eq!(request.to_network_parameters, 0x2806);
+
+    let path = [
+        0x34, 0x04, 0x01, 0x00, 0x02, 0x00, 0x36, 0x00, 0x83, 0x01,
+        0x20, 0x04, 0x24, 0x97, 0x2C, 0x96, 0x2C, 0x64,
+        0x80, 0x02, 0x00, 0xDC, 0x05, 0x00
+    ];
+    let serialized = request.serialize();
+    assert_eq!(serialized[serialized.len() - path.len() - 1], path.len() as u8 / 2);
+    assert!(serialized.ends_with(&path));
+
+    // The key and data segments read back as written.
+    let (rest, parsed) = EPath::parse(&path).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(parsed.attributes.len(), 6);
+    assert_eq!(parsed.attributes.iter().flat_map(|segment| segment.serialize()).collect::<Vec<u8>>(), path);
+}
+
+#[test]
+fn forward_open_options_and_limits() {
+    let eds = Eds::parse(EDS).unwrap();
+
+    let options = ConnectionOptions { rpi: Some(50000), configuration: Some(vec![1, 2, 3, 4]), params: vec![(5, CipValue::Usint(152))], skip_electronic_key: true, connection_timeout_multiplier: 2 };
+    let request = eds.forward_open(1, &options).unwrap();
+    assert_eq!(request.ot_rpi, 50000);
+    assert_eq!(request.connection_timeout_multiplier, 2);
+    assert!(request.serialize().ends_with(&[7, 0x20, 0x04, 0x24, 0x98, 0x2C, 0x96, 0x2C, 0x64, 0x80, 0x02, 1, 2, 3, 4]));
+
+    let too_fast = ConnectionOptions { rpi: Some(500), ..Default::default() };
+    assert!(matches!(eds.forward_open(1, &too_fast), Err(CipError::InvalidValue(_))));
+    assert!(matches!(eds.forward_open(2, &ConnectionOptions::default()), Err(CipError::InvalidValue(_))));
+}
+
+#[test]
+fn syntax_errors_report_the_line() {
+    assert!(matches!(Eds::parse("Key = 1;"), Err(CipError::Eds { line: 1, .. })));
+    assert!(matches!(Eds::parse("[File]\n  DescText = \"open;\n"), Err(CipError::Eds { line: 2, .. })));
+    assert!(matches!(Eds::parse("[File]\n\n  Revision = 1.0\n"), Err(CipError::Eds { line: 4, .. })));
+    assert!(matches!(Eds::parse("[File\n"), Err(CipError::Eds { line: 1, .. })));
+    assert!(matches!(Eds::parse("[Device]\n  VendCode = abc;\n").unwrap().device(), Err(CipError::Eds { .. })));
+}
@@ -0,0 +1,133 @@
+use cip::{cip::{AnsiSymbolSegment, DataSegment, EPath, ElectronicKeySegment, LogicalSegment, LogicalType, NetworkSegment}, common::Serializable, error::{CipError, KeyMismatch}, objects::{connection_manager::ForwardOpenRequest, identity::{Identity, IdentityStatus}}};
+
+fn identity() -> Identity {
+    Identity { vendor_id: 1, device_type: 0x0C, product_code: 0x36, major_revision: 2, minor_revision: 7, status: IdentityStatus(0), serial_number: 1, product_name: "Gateway".into(), state: None }
+}
+
+fn serialize(path: &EPath) -> Vec<u8> {
+    path.attributes.iter().flat_map(|segment| segment.serialize()).collect()
+}
+
+#[test]
+fn electronic_key_is_parsed_between_logical_segments() {
+    let bytes = [0x34, 0x04, 0x01, 0x00, 0x0C, 0x00, 0x36, 0x00, 0x82, 0x07, 0x20, 0x04, 0x24, 0x64];
+    let (rest, path) = EPath::parse(&bytes).unwrap();
+
+    assert!(rest.is_empty());
+    assert_eq!(path.attributes.len(), 3);
+    assert_eq!(path.attributes[2].as_logical().unwrap().value(), 0x64);
+    assert_eq!(serialize(&path), bytes);
+
+    let (_, key) = ElectronicKeySegment::deserialize(&bytes).unwrap();
+    assert_eq!(key, ElectronicKeySegment::compatible(&identity()));
+}
+
+#[test]
+fn exact_key_needs_every_field_to_match() {
+    let key = ElectronicKeySegment::exact(&identity());
+    assert_eq!(key.check(&identity()), Ok(()));
+
+    let newer = Identity { minor_revision: 8, ..identity() };
+    assert_eq!(key.check(&newer), Err(KeyMismatch::Revision));
+    assert_eq!(key.check(&Identity { product_code: 0x37, ..identity() }), Err(KeyMismatch::VendorOrProduct));
+    assert_eq!(key.check(&Identity { device_type: 0x2B, ..identity() }), Err(KeyMismatch::DeviceType));
+
+    // Zero fields are not checked.
+    let wildcard = ElectronicKeySegment { product_code: 0, major_revision: 0, minor_revision: 0, ..key };
+    assert_eq!(wildcard.check(&Identity { product_code: 0x37, major_revision: 5, ..newer }), Ok(()));
+}
+
+#[test]
+fn compatible_key_accepts_later_minor_revisions() {
+    let key = ElectronicKeySegment::compatible(&identity());
+
+    assert_eq!(key.check(&Identity { minor_revision: 9, ..identity() }), Ok(()));
+    assert_eq!(key.check(&Identity { minor_revision: 6, ..identity() }), Err(KeyMismatch::Revision));
+    assert_eq!(key.check(&Identity { major_revision: 3, ..identity() }), Err(KeyMismatch::Revision));
+}
+
+#[test]
+fn data_and_symbol_segments_round_trip() {
+    let bytes = [0x91, 0x05, b'M', b'o', b't', b'o', b'r', 0x00, 0x80, 0x02, 0x0A, 0x0B, 0x0C, 0x0D];
+    let (rest, path) = EPath::parse(&bytes).unwrap();
+
+    assert!(rest.is_empty());
+    assert_eq!(path.attributes[0].as_symbol().unwrap().symbol(), b"Motor");
+    assert_eq!(path.attributes[1].as_data().unwrap().data(), [0x0A, 0x0B, 0x0C, 0x0D]);
+    assert_eq!(serialize(&path), bytes);
+
+    // Odd lengths are padded to whole words.
+    assert_eq!(AnsiSymbolSegment::new("Tag").unwrap().serialize(), [0x91, 0x03, b'T', b'a', b'g', 0x00]);
+    assert_eq!(DataSegment::new(vec![1, 2, 3]).unwrap().serialize(), [0x80, 0x02, 1, 2, 3, 0]);
+}
+
+#[test]
+fn oversized_data_segment_is_rejected() {
+    assert_eq!(DataSegment::new(vec![0; 510]).unwrap().serialize()[1], 255);
+    assert!(matches!(DataSegment::new(vec![0; 511]), Err(CipError::InvalidValue(_))));
+}
+
+#[test]
+fn oversized_symbol_is_rejected() {
+    assert_eq!(AnsiSymbolSegment::new(&"A".repeat(255)).unwrap().serialize()[1], 255);
+    assert!(matches!(AnsiSymbolSegment::new(&"A".repeat(256)), Err(CipError::InvalidValue(_))));
+}
+
+#[test]
+fn forward_open_path_keeps_config_data() {
+    let mut path = EPath::new();
+    path.attributes.push(Box::new(ElectronicKeySegment::exact(&identity())));
+    path.attributes.push(Box::new(LogicalSegment::init(LogicalType::ClassId as u8, 0x04)));
+    path.attributes.push(Box::new(LogicalSegment::init(LogicalType::InstanceId as u8, 0x97)));
+    path.attributes.push(Box::new(LogicalSegment::init(LogicalType::ConnectionPoint as u8, 0x64)));
+    path.attributes.push(Box::new(DataSegment::new(vec![0x10, 0x20, 0x30, 0x40]).unwrap()));
+
+    let mut request = ForwardOpenRequest::create_null_forward_open(path);
+    request.ot_rpi = 10000;
+    request.to_network_parameters = 0x2806;
+    request.transport_class = 0x01;
+
+    let bytes = request.serialize();
+    let (rest, parsed) = ForwardOpenRequest::deserialize(&bytes).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(parsed.connection_serial_number, request.connection_serial_number);
+    assert_eq!((parsed.ot_rpi, parsed.to_network_parameters, parsed.transport_class), (10000, 0x2806, 0x01));
+    assert_eq!(parsed.connection_path.attributes.len(), 5);
+    assert_eq!(parsed.connection_path.attributes[4].as_data().unwrap().data(), [0x10, 0x20, 0x30, 0x40]);
+    assert_eq!(parsed.serialize(), bytes);
+}
+
+#[test]
+fn network_segments_round_trip() {
+    let bytes = [
+        0x43, 0x0A,
+        0x51, 0x02, 0xA0, 0x86, 0x01, 0x00,
+        0x5F, 0x02, 0x01, 0x00, 0xAA, 0xBB,
+        0x50, 0x01, 0x12, 0x34
+    ];
+    let (rest, path) = EPath::parse(&bytes).unwrap();
+
+    assert!(rest.is_empty());
+    let segments: Vec<_> = path.attributes.iter().map(|segment| segment.as_network().unwrap().clone()).collect();
+    assert_eq!(segments, [
+        NetworkSegment::ProductionInhibitTime(10),
+        NetworkSegment::ProductionInhibitTimeMicroseconds(100000),
+        NetworkSegment::Extended { subtype: 1, data: vec![0xAA, 0xBB] },
+        NetworkSegment::Safety(vec![0x12, 0x34])
+    ]);
+    assert_eq!(serialize(&path), bytes);
+}
+
+#[test]
+fn forward_open_path_carries_network_segments() {
+    let mut path = EPath::new();
+    path.attributes.push(Box::new(NetworkSegment::ProductionInhibitTime(25)));
+    path.attributes.push(Box::new(LogicalSegment::init(LogicalType::ClassId as u8, 0x04)));
+    path.attributes.push(Box::new(LogicalSegment::init(LogicalType::ConnectionPoint as u8, 0x64)));
+
+    let bytes = ForwardOpenRequest::create_null_forward_open(path).serialize();
+    assert!(bytes.ends_with(&[3, 0x43, 25, 0x20, 0x04, 0x2C, 0x64]));
+
+    let (_, parsed) = ForwardOpenRequest::deserialize(&bytes).unwrap();
+    assert_eq!(parsed.connection_path.attributes[0].as_network(), Some(&NetworkSegment::ProductionInhibitTime(25)));
+}
@@ -14,7 +14,7 @@ async fn main() {
 
     let enip_client = TcpEnipClient::new(tcp);
     let mut client = CipClient::new(enip_client);
-    client.connect().await;
+    client.connect().await.unwrap();
     let mut response_status = 0;
 
     while response_status == 0 {
@@ -46,13 +46,13 @@ async fn main() {
             connection_path: epath 
         };
     
-        let response  = client.call_service(CipClass::ConnectionManager as u32, 0x1, 0x54, forward_open.serialize()).await;
+        let response  = client.call_service(CipClass::ConnectionManager as u32, 0x1, 0x54, forward_open.serialize()).await.unwrap();
         response_status = response.general_status;
         rng = rng + 1;
     }
 
     loop {
-        client.send_nop().await;
+        client.send_nop().await.unwrap();
 
         let one_minute = time::Duration::from_secs(60);
         thread::sleep(one_minute);
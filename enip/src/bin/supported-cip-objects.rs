@@ -15,8 +15,8 @@ async fn main() {
 
     let enip_client = TcpEnipClient::new(tcp);
     let mut client = CipClient::new(enip_client);
-    client.connect().await;
+    client.connect().await.unwrap();
 
-    let classes = client.get_supported_classes().await;
+    let classes = client.get_supported_classes().await.unwrap();
     println!("Following objects are implemented {:#04X?}", classes);
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+use alloc::vec::Vec;
+use cip::error::CipError;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::common::io_error;
+
+/// Size of the fixed encapsulation header that precedes every packet.
+pub const HEADER_LENGTH: usize = 24;
+
+/// Largest data length allowed after the header by the encapsulation protocol.
+pub const MAX_DATA_LENGTH: u16 = 65511;
+
+/// Splits a stream of bytes into whole encapsulation packets.
+///
+/// TCP gives no guarantee that one `read` returns exactly one packet: packets
+/// can arrive split over several reads or several packets can arrive in one.
+/// The codec buffers data until the 24 byte header and the `length` bytes it
+/// announces are available, and keeps any extra bytes for the next packet.
+pub struct EnipCodec {
+    buffer: Vec<u8>,
+    max_length: u16
+}
+
+impl Default for EnipCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnipCodec {
+    pub fn new() -> Self {
+        Self { buffer: Vec::new(), max_length: MAX_DATA_LENGTH }
+    }
+
+    /// Creates a codec that rejects packets announcing more than `max_length` data bytes.
+    pub fn with_max_length(max_length: u16) -> Self {
+        Self { buffer: Vec::new(), max_length }
+    }
+
+    /// Appends received bytes to the internal buffer.
+    pub fn extend(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Number of bytes buffered but not yet returned as a packet.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the next whole packet (header included) if one is buffered.
+    ///
+    /// An oversized length leaves the stream out of sync, so the buffer is
+    /// dropped and the caller should close the connection.
+    pub fn next_packet(&mut self) -> Result<Option<Vec<u8>>, CipError> {
+        if self.buffer.len() < HEADER_LENGTH {
+            return Ok(None);
+        }
+
+        let length = u16::from_le_bytes([self.buffer[2], self.buffer[3]]);
+        if length > self.max_length {
+            self.buffer.clear();
+            return Err(CipError::PacketTooLarge(length.into()));
+        }
+
+        let total = HEADER_LENGTH + length as usize;
+        if self.buffer.len() < total {
+            return Ok(None);
+        }
+
+        let remaining = self.buffer.split_off(total);
+        let packet = core::mem::replace(&mut self.buffer, remaining);
+
+        return Ok(Some(packet));
+    }
+
+    /// Reads from `reader` until a whole packet is available.
+    pub async fn read_packet<R: AsyncRead + Unpin>(&mut self, reader: &mut R) -> Result<Vec<u8>, CipError> {
+        let mut data: Vec<u8> = alloc::vec![0; 8192];
+
+        loop {
+            if let Some(packet) = self.next_packet()? {
+                return Ok(packet);
+            }
+
+            let n = reader.read(&mut data).await.map_err(io_error)?;
+            if n == 0 {
+                return Err(CipError::ConnectionClosed);
+            }
+
+            self.extend(&data[..n]);
+        }
+    }
+}
@@ -1,7 +1,12 @@
-use alloc::vec::Vec;
+use alloc::{boxed::Box, vec::Vec};
+use cip::error::CipError;
 use nom::IResult;
 
 pub trait Serializable {
     fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized;
     fn serialize(&self) -> Vec<u8>;
-}
\ No newline at end of file
+}
+
+pub(crate) fn io_error(e: tokio::io::Error) -> CipError {
+    CipError::Transport(Box::new(e))
+}
@@ -1,8 +1,10 @@
-use alloc::vec::Vec;
-use nom::{bytes::streaming::take, number::complete::{be_u32, le_u16, le_u32}, sequence::tuple, IResult, InputTake};
+use alloc::{string::String, vec::Vec};
+use core::net::{Ipv4Addr, SocketAddrV4};
+use nom::{bytes::streaming::take, error::ErrorKind, number::complete::{be_u16, be_u32, le_u16, le_u32, u8 as le_u8}, sequence::tuple, IResult};
 
 use crate::common::Serializable;
 
+#[derive(Clone)]
 pub struct CommonPacketHeader {
     pub type_id: u16,
     pub length: u16
@@ -31,16 +33,19 @@ pub struct CommonPacketList
     pub null_address_item: Vec<NullAddressItem>,
     pub connected_addr_item: Vec<ConnectedAddressItem>,
     pub connected_data_item: Vec<ConnectedDataItem>,
-    pub unconnected_data_item: Vec<UnconnectedDataItem>
+    pub unconnected_data_item: Vec<UnconnectedDataItem>,
+    pub identity_item: Vec<CipIdentityItem>,
+    pub services_item: Vec<ListServicesItem>,
+    pub unknown_item: Vec<UnknownItem>
 }
 
 impl CommonPacketList {
     pub fn new() -> Self {
-        Self { connected_addr_item: Vec::new(), connected_data_item: Vec::new(), null_address_item: Vec::new(), unconnected_data_item: Vec::new() }
+        Self { connected_addr_item: Vec::new(), connected_data_item: Vec::new(), null_address_item: Vec::new(), unconnected_data_item: Vec::new(), identity_item: Vec::new(), services_item: Vec::new(), unknown_item: Vec::new() }
     }
 
     pub fn len(&self) -> u16 {
-        (self.connected_addr_item.len() + self.connected_data_item.len() + self.unconnected_data_item.len() + self.null_address_item.len() ) as u16
+        (self.connected_addr_item.len() + self.connected_data_item.len() + self.unconnected_data_item.len() + self.null_address_item.len() + self.identity_item.len() + self.services_item.len() + self.unknown_item.len()) as u16
     } 
 }
 
@@ -55,7 +60,7 @@ impl Serializable for CommonPacketList {
                 let item_length = le_u16(item_type.0)?;
 
                 if item_length.0.len() < item_length.1.into() {
-                    panic!("Not enough data to create Common Packet Item!")
+                    return Err(nom::Err::Error(nom::error::Error::new(remaining_data, ErrorKind::Eof)));
                 }
 
                 match item_type.1 {
@@ -78,12 +83,27 @@ impl Serializable for CommonPacketList {
                         let result = ConnectedDataItem::deserialize(remaining_data)?;
                         items.connected_data_item.push(result.1);
                         remaining_data = result.0;
+                    },
+                    0x0C => {
+                        let result = CipIdentityItem::deserialize(remaining_data)?;
+                        items.identity_item.push(result.1);
+                        remaining_data = result.0;
+                    },
+                    0x100 => {
+                        let result = ListServicesItem::deserialize(remaining_data)?;
+                        items.services_item.push(result.1);
+                        remaining_data = result.0;
+                    },
+                    _ => {
+                        // Keep items we do not model instead of failing the whole packet.
+                        let result = UnknownItem::deserialize(remaining_data)?;
+                        items.unknown_item.push(result.1);
+                        remaining_data = result.0;
                     }
-                    _ => panic!("Unknown Common Packet Item")
                 }
             }
 
-            return Ok((input, CommonPacketList { connected_addr_item: Vec::new(), null_address_item: Vec::new(), unconnected_data_item: Vec::new(), connected_data_item: Vec::new() }));
+            return Ok((remaining_data, items));
         }
     
         fn serialize(&self) -> Vec<u8> {
@@ -107,6 +127,18 @@ impl Serializable for CommonPacketList {
                 vec.extend(item.serialize());
             }
 
+            for item in &self.identity_item {
+                vec.extend(item.serialize());
+            }
+
+            for item in &self.services_item {
+                vec.extend(item.serialize());
+            }
+
+            for item in &self.unknown_item {
+                vec.extend(item.serialize());
+            }
+
             return vec;  
         }
     }
@@ -152,9 +184,9 @@ pub struct ConnectedDataItem {
 impl Serializable for ConnectedDataItem {
     fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized {
         let (input, (type_id, length)) = tuple((le_u16, le_u16))(input)?;
-        let data= input.take(length.into()).to_vec();
+        let (input, data) = take(length)(input)?;
+        let data = data.to_vec();
 
-        // TODO: Fix input as it still has "data" field when we return
         return Ok((input, ConnectedDataItem { header: CommonPacketHeader { type_id, length }, data }))
     }
 
@@ -179,9 +211,9 @@ pub struct UnconnectedDataItem {
 impl Serializable for UnconnectedDataItem {
     fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized {
         let (input, (type_id, length)) = tuple((le_u16, le_u16))(input)?;
-        let data= input.take(length.into()).to_vec();
+        let (input, data) = take(length)(input)?;
+        let data = data.to_vec();
 
-        // TODO: Fix input as it still has "data" field when we return
         return Ok((input, UnconnectedDataItem { header: CommonPacketHeader { type_id, length }, data }))
     }
 
@@ -219,4 +251,148 @@ impl Serializable for ConnectedAddressItem {
         return vec;  
     }
 }
-    
\ No newline at end of file
+
+#[derive(Clone)]
+pub struct CipIdentityItem {
+    pub header: CommonPacketHeader,
+    pub encapsulation_version: u16,
+    pub sin_family: u16,
+    pub sin_port: u16,
+    pub sin_addr: u32,
+    pub sin_zero: [u8;8],
+    pub vendor_id: u16,
+    pub device_type: u16,
+    pub product_code: u16,
+    pub major_revision: u8,
+    pub minor_revision: u8,
+    pub status: u16,
+    pub serial_number: u32,
+    pub product_name: String,
+    pub state: u8
+}
+
+impl CipIdentityItem {
+    /// Address the device accepts encapsulation connections on, as it reports it.
+    pub fn socket_addr(&self) -> SocketAddrV4 {
+        SocketAddrV4::new(Ipv4Addr::from(self.sin_addr), self.sin_port)
+    }
+}
+
+impl Serializable for CipIdentityItem {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized {
+        let (input, (type_id, length)) = tuple((le_u16, le_u16))(input)?;
+        let (remaining, item) = take(length)(input)?;
+
+        // The socket address is sent in network byte order, everything else little endian.
+        let (item, (encapsulation_version, sin_family, sin_port, sin_addr, sin_zero_context)) = tuple((le_u16, be_u16, be_u16, be_u32, take(8u8)))(item)?;
+        let (item, (vendor_id, device_type, product_code, major_revision, minor_revision, status, serial_number, name_length)) = tuple((le_u16, le_u16, le_u16, le_u8, le_u8, le_u16, le_u32, le_u8))(item)?;
+        let (item, name) = take(name_length)(item)?;
+        let (_, state) = le_u8(item)?;
+        let sin_zero = sin_zero_context.try_into().expect("slice with incorrect length");
+        let product_name = String::from_utf8_lossy(name).into();
+
+        return Ok((remaining, CipIdentityItem { header: CommonPacketHeader { type_id, length }, encapsulation_version, sin_family, sin_port, sin_addr, sin_zero, vendor_id, device_type, product_code, major_revision, minor_revision, status, serial_number, product_name, state }))
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&self.encapsulation_version.to_le_bytes());
+        body.extend_from_slice(&self.sin_family.to_be_bytes());
+        body.extend_from_slice(&self.sin_port.to_be_bytes());
+        body.extend_from_slice(&self.sin_addr.to_be_bytes());
+        body.extend_from_slice(&self.sin_zero);
+        body.extend_from_slice(&self.vendor_id.to_le_bytes());
+        body.extend_from_slice(&self.device_type.to_le_bytes());
+        body.extend_from_slice(&self.product_code.to_le_bytes());
+        body.push(self.major_revision);
+        body.push(self.minor_revision);
+        body.extend_from_slice(&self.status.to_le_bytes());
+        body.extend_from_slice(&self.serial_number.to_le_bytes());
+        body.push(self.product_name.len() as u8);
+        body.extend_from_slice(self.product_name.as_bytes());
+        body.push(self.state);
+
+        let mut vec = Vec::new();
+        vec.extend_from_slice(&self.header.type_id.to_le_bytes());
+        vec.extend_from_slice(&(body.len() as u16).to_le_bytes());
+        vec.extend(body);
+
+        return vec;
+    }
+}
+
+/// Capability flag: the service supports CIP encapsulation over TCP.
+pub const CAPABILITY_CIP_TCP: u16 = 1 << 5;
+/// Capability flag: the service supports CIP class 0 and 1 connected data over UDP.
+pub const CAPABILITY_CLASS_0_1_UDP: u16 = 1 << 8;
+
+pub struct ListServicesItem {
+    pub header: CommonPacketHeader,
+    pub version: u16,
+    pub capability_flags: u16,
+    pub name: String
+}
+
+impl ListServicesItem {
+    pub fn supports_cip_tcp(&self) -> bool {
+        self.capability_flags & CAPABILITY_CIP_TCP != 0
+    }
+
+    pub fn supports_class_0_1_udp(&self) -> bool {
+        self.capability_flags & CAPABILITY_CLASS_0_1_UDP != 0
+    }
+}
+
+impl Serializable for ListServicesItem {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized {
+        // The name should fill 16 bytes, but `length` decides where the next item starts.
+        let (input, (type_id, length)) = tuple((le_u16, le_u16))(input)?;
+        let (input, body) = take(length)(input)?;
+        let (name, (version, capability_flags)) = tuple((le_u16, le_u16))(body)?;
+        let name = name.split(|c| *c == 0).next().unwrap_or_default();
+        let name = String::from_utf8_lossy(name).into();
+
+        return Ok((input, ListServicesItem { header: CommonPacketHeader { type_id, length }, version, capability_flags, name }))
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        vec.extend_from_slice(&self.header.type_id.to_le_bytes());
+        vec.extend_from_slice(&20u16.to_le_bytes());
+        vec.extend_from_slice(&self.version.to_le_bytes());
+        vec.extend_from_slice(&self.capability_flags.to_le_bytes());
+
+        // The name is a NUL terminated string in a fixed 16 byte field.
+        let mut name = [0u8; 16];
+        let length = self.name.len().min(15);
+        name[..length].copy_from_slice(&self.name.as_bytes()[..length]);
+        vec.extend_from_slice(&name);
+
+        return vec;
+    }
+}
+
+/// An item whose type we do not model, kept as raw bytes.
+pub struct UnknownItem {
+    pub header: CommonPacketHeader,
+    pub data: Vec<u8>
+}
+
+impl Serializable for UnknownItem {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], Self> where Self: Sized {
+        let (input, (type_id, length)) = tuple((le_u16, le_u16))(input)?;
+        let (input, data) = take(length)(input)?;
+        let data = data.to_vec();
+
+        return Ok((input, UnknownItem { header: CommonPacketHeader { type_id, length }, data }))
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        vec.extend_from_slice(&self.header.type_id.to_le_bytes());
+        vec.extend_from_slice(&self.header.length.to_le_bytes());
+        vec.extend_from_slice(&self.data);
+
+        return vec;
+    }
+}
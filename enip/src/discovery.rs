@@ -0,0 +1,55 @@
+use alloc::vec::Vec;
+use core::{net::{Ipv4Addr, SocketAddr}, time::Duration};
+use cip::error::CipError;
+use tokio::{net::UdpSocket, time::{timeout_at, Instant}};
+
+use crate::{common::{io_error, Serializable}, cpf::CipIdentityItem, encapsulation::{EncapsulationCommand, ListIdentity, ENIP_PORT}};
+
+/// A device that answered a ListIdentity request.
+pub struct DiscoveredDevice {
+    /// Address the reply came from.
+    pub addr: SocketAddr,
+    pub identity: CipIdentityItem
+}
+
+/// Broadcasts ListIdentity on the local network and collects replies for `wait`.
+pub async fn discover(wait: Duration) -> Result<Vec<DiscoveredDevice>, CipError> {
+    return discover_at(SocketAddr::from((Ipv4Addr::BROADCAST, ENIP_PORT)), wait).await;
+}
+
+/// Sends ListIdentity to `target`, which can be a broadcast or a unicast
+/// address, and collects every reply that arrives within `wait`. Failed
+/// receives and malformed replies are skipped.
+pub async fn discover_at(target: SocketAddr, wait: Duration) -> Result<Vec<DiscoveredDevice>, CipError> {
+    let socket = UdpSocket::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))).await.map_err(io_error)?;
+    socket.set_broadcast(true).map_err(io_error)?;
+    socket.send_to(&ListIdentity::request().serialize(), target).await.map_err(io_error)?;
+
+    let deadline = Instant::now() + wait;
+    let mut devices: Vec<DiscoveredDevice> = Vec::new();
+    let mut data: Vec<u8> = alloc::vec![0; 65535];
+
+    while let Ok(received) = timeout_at(deadline, socket.recv_from(&mut data)).await {
+        // Some platforms report an ICMP port unreachable from an earlier send on
+        // the next receive; that only concerns one host, so keep collecting.
+        let (n, addr) = match received {
+            Ok(received) => received,
+            Err(_) => continue
+        };
+
+        let reply = match ListIdentity::deserialize(&data[..n]) {
+            Ok((_, reply)) if reply.header.expect(EncapsulationCommand::ListIdentity).is_ok() => reply,
+            _ => continue
+        };
+
+        if devices.iter().any(|device| device.addr == addr) {
+            continue;
+        }
+
+        for identity in reply.items.identity_item {
+            devices.push(DiscoveredDevice { addr, identity });
+        }
+    }
+
+    return Ok(devices);
+}
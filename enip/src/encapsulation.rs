@@ -1,7 +1,47 @@
 use alloc::vec::Vec;
+use cip::error::CipError;
 use nom::{number::complete::{le_u16, le_u32, le_u64}, sequence::tuple, IResult};
 use crate::{cpf::CommonPacketList, common::Serializable};
 
+pub use cip::error::EncapsulationStatus;
+
+/// Registered TCP and UDP port for encapsulation messages.
+pub const ENIP_PORT: u16 = 44818;
+
+/// Encapsulation protocol version spoken by this library.
+pub const PROTOCOL_VERSION: u16 = 1;
+
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncapsulationCommand {
+    Nop = 0x0000,
+    ListServices = 0x0004,
+    ListIdentity = 0x0063,
+    ListInterfaces = 0x0064,
+    RegisterSession = 0x0065,
+    UnregisterSession = 0x0066,
+    SendRRData = 0x006F,
+    SendUnitData = 0x0070
+}
+
+impl TryFrom<u16> for EncapsulationCommand {
+    type Error = CipError;
+
+    fn try_from(command: u16) -> Result<Self, Self::Error> {
+        match command {
+            0x0000 => Ok(EncapsulationCommand::Nop),
+            0x0004 => Ok(EncapsulationCommand::ListServices),
+            0x0063 => Ok(EncapsulationCommand::ListIdentity),
+            0x0064 => Ok(EncapsulationCommand::ListInterfaces),
+            0x0065 => Ok(EncapsulationCommand::RegisterSession),
+            0x0066 => Ok(EncapsulationCommand::UnregisterSession),
+            0x006F => Ok(EncapsulationCommand::SendRRData),
+            0x0070 => Ok(EncapsulationCommand::SendUnitData),
+            _ => Err(CipError::UnexpectedCommand(command))
+        }
+    }
+}
+
 pub trait ENIPPacket: Serializable + Sized {
     fn set_session(&mut self, session_handle: u32);
 }
@@ -35,6 +75,48 @@ impl Serializable for EtherNetIPHeader {
     }
 }
 
+impl EtherNetIPHeader {
+    pub fn new(command: EncapsulationCommand, length: u16, session_handle: u32) -> Self {
+        Self { command: command as u16, length, session_handle, status: 0, sender_context: 0, options: 0 }
+    }
+
+    pub fn command(&self) -> Result<EncapsulationCommand, CipError> {
+        EncapsulationCommand::try_from(self.command)
+    }
+
+    pub fn status(&self) -> EncapsulationStatus {
+        EncapsulationStatus::from(self.status)
+    }
+
+    /// Fails if the packet carries a non-zero status.
+    pub fn check(&self) -> Result<(), CipError> {
+        match self.status() {
+            EncapsulationStatus::Success => Ok(()),
+            status => Err(CipError::Encapsulation(status))
+        }
+    }
+
+    /// Fails if the packet belongs to another session than `session_handle`.
+    pub fn expect_session(&self, session_handle: u32) -> Result<(), CipError> {
+        if self.session_handle != session_handle {
+            return Err(CipError::SessionMismatch { expected: session_handle, received: self.session_handle });
+        }
+
+        return Ok(());
+    }
+
+    /// Fails if the packet carries a non-zero status or is not a reply to `command`.
+    pub fn expect(&self, command: EncapsulationCommand) -> Result<(), CipError> {
+        self.check()?;
+
+        if self.command != command as u16 {
+            return Err(CipError::UnexpectedCommand(self.command));
+        }
+
+        return Ok(());
+    }
+}
+
 impl ENIPPacket for EtherNetIPHeader {
     fn set_session(&mut self, session_handle: u32) {
         self.session_handle = session_handle;
@@ -67,6 +149,34 @@ impl Serializable for RegisterSession {
     }
 }
 
+impl RegisterSession {
+    pub fn request() -> Self {
+        Self { header: EtherNetIPHeader::new(EncapsulationCommand::RegisterSession, 4, 0), version: PROTOCOL_VERSION, options: 0 }
+    }
+
+    /// Validates a RegisterSession reply and returns the session handle it assigns.
+    ///
+    /// A target that does not support our protocol version answers with
+    /// `UnsupportedProtocolRevision` and the highest version it does support.
+    pub fn session_from_reply(&self) -> Result<u32, CipError> {
+        if self.header.command != EncapsulationCommand::RegisterSession as u16 {
+            return Err(CipError::UnexpectedCommand(self.header.command));
+        }
+
+        if self.header.status() == EncapsulationStatus::UnsupportedProtocolRevision || (self.header.status == 0 && self.version != PROTOCOL_VERSION) {
+            return Err(CipError::UnsupportedProtocolVersion(self.version));
+        }
+
+        self.header.check()?;
+
+        if self.options != 0 || self.header.session_handle == 0 {
+            return Err(CipError::InvalidReply);
+        }
+
+        return Ok(self.header.session_handle);
+    }
+}
+
 impl ENIPPacket for RegisterSession {
     fn set_session(&mut self, session_handle: u32) {
         self.header.session_handle = session_handle;
@@ -96,8 +206,6 @@ impl Serializable for SendUnitData {
         vec.extend_from_slice(&self.timeout.to_le_bytes());
 
         if self.items.len() > 0 {
-            vec.extend_from_slice(&self.items.len().to_le_bytes());
-
             vec.extend(self.items.serialize());
         }
 
@@ -134,8 +242,6 @@ impl Serializable for SendRRData {
         vec.extend_from_slice(&self.timeout.to_le_bytes());
 
         if self.items.len() > 0 {
-            vec.extend_from_slice(&self.items.len().to_le_bytes());
-
             vec.extend(self.items.serialize());
         }
 
@@ -176,4 +282,125 @@ impl ENIPPacket for NOP {
     fn set_session(&mut self, session_handle: u32) {
         self.header.session_handle = session_handle;
     }
-}
\ No newline at end of file
+}
+
+pub struct ListIdentity {
+    pub header: EtherNetIPHeader,
+    pub items: CommonPacketList
+}
+
+impl ListIdentity {
+    pub fn request() -> Self {
+        Self { header: EtherNetIPHeader::new(EncapsulationCommand::ListIdentity, 0, 0), items: CommonPacketList::new() }
+    }
+}
+
+impl Serializable for ListIdentity {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], ListIdentity> {
+        let (input, header) = EtherNetIPHeader::deserialize(input)?;
+
+        // Requests carry no data at all, not even an item count.
+        if input.is_empty() {
+            return Ok((input, ListIdentity { header, items: CommonPacketList::new() }));
+        }
+
+        let (input, items) = CommonPacketList::deserialize(input)?;
+        return Ok((input, ListIdentity { header, items }))
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = self.header.serialize();
+
+        if self.items.len() > 0 {
+            vec.extend(self.items.serialize());
+        }
+
+        return vec;
+    }
+}
+
+impl ENIPPacket for ListIdentity {
+    fn set_session(&mut self, session_handle: u32) {
+        self.header.session_handle = session_handle;
+    }
+}
+
+pub struct ListServices {
+    pub header: EtherNetIPHeader,
+    pub items: CommonPacketList
+}
+
+impl ListServices {
+    pub fn request() -> Self {
+        Self { header: EtherNetIPHeader::new(EncapsulationCommand::ListServices, 0, 0), items: CommonPacketList::new() }
+    }
+}
+
+impl Serializable for ListServices {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], ListServices> {
+        let (input, header) = EtherNetIPHeader::deserialize(input)?;
+
+        if input.is_empty() {
+            return Ok((input, ListServices { header, items: CommonPacketList::new() }));
+        }
+
+        let (input, items) = CommonPacketList::deserialize(input)?;
+        return Ok((input, ListServices { header, items }))
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = self.header.serialize();
+
+        if self.items.len() > 0 {
+            vec.extend(self.items.serialize());
+        }
+
+        return vec;
+    }
+}
+
+impl ENIPPacket for ListServices {
+    fn set_session(&mut self, session_handle: u32) {
+        self.header.session_handle = session_handle;
+    }
+}
+
+pub struct ListInterfaces {
+    pub header: EtherNetIPHeader,
+    pub items: CommonPacketList
+}
+
+impl ListInterfaces {
+    pub fn request() -> Self {
+        Self { header: EtherNetIPHeader::new(EncapsulationCommand::ListInterfaces, 0, 0), items: CommonPacketList::new() }
+    }
+}
+
+impl Serializable for ListInterfaces {
+    fn deserialize(input: &[u8]) -> IResult<&[u8], ListInterfaces> {
+        let (input, header) = EtherNetIPHeader::deserialize(input)?;
+
+        if input.is_empty() {
+            return Ok((input, ListInterfaces { header, items: CommonPacketList::new() }));
+        }
+
+        let (input, items) = CommonPacketList::deserialize(input)?;
+        return Ok((input, ListInterfaces { header, items }))
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut vec = self.header.serialize();
+
+        if self.items.len() > 0 {
+            vec.extend(self.items.serialize());
+        }
+
+        return vec;
+    }
+}
+
+impl ENIPPacket for ListInterfaces {
+    fn set_session(&mut self, session_handle: u32) {
+        self.header.session_handle = session_handle;
+    }
+}
@@ -0,0 +1,120 @@
+use alloc::vec::Vec;
+use cip::{cip::{CipClient, MessageRouterResponse}, error::CipError};
+use tokio::sync::{mpsc, oneshot};
+
+type Reply<T> = oneshot::Sender<Result<T, CipError>>;
+
+enum Command {
+    CallService { class_id: u32, instance_id: u32, service: u8, data: Vec<u8>, reply: Reply<MessageRouterResponse> },
+    GetAttributeSingle { class_id: u32, instance_id: u32, attribute_id: u32, reply: Reply<MessageRouterResponse> },
+    SetAttributeSingle { class_id: u32, instance_id: u32, attribute_id: u32, data: Vec<u8>, reply: Reply<MessageRouterResponse> },
+    GetSupportedClasses { reply: Reply<Vec<u16>> },
+    SendNop { reply: Reply<()> },
+    Shutdown { reply: Reply<()> }
+}
+
+impl Command {
+    fn is_cancelled(&self) -> bool {
+        match self {
+            Command::CallService { reply, .. } => reply.is_closed(),
+            Command::GetAttributeSingle { reply, .. } => reply.is_closed(),
+            Command::SetAttributeSingle { reply, .. } => reply.is_closed(),
+            Command::GetSupportedClasses { reply } => reply.is_closed(),
+            Command::SendNop { reply } => reply.is_closed(),
+            Command::Shutdown { .. } => false
+        }
+    }
+}
+
+/// Cloneable handle to a [`CipClient`] owned by a background task.
+///
+/// Requests from every clone are queued to the task, which runs them one at
+/// a time on the transport. The queue is bounded, so callers wait when it is
+/// full. Dropping a request future before the task picks it up cancels it; a
+/// request already on the wire runs to completion and its reply is dropped.
+#[derive(Clone)]
+pub struct CipHandle {
+    sender: mpsc::Sender<Command>
+}
+
+impl CipHandle {
+    /// Moves `client` into a new task that accepts up to `capacity` queued requests.
+    /// A `capacity` of 0 is treated as 1.
+    pub fn spawn(client: CipClient, capacity: usize) -> Self {
+        let (sender, commands) = mpsc::channel(capacity.max(1));
+        tokio::spawn(run(client, commands));
+
+        return Self { sender };
+    }
+
+    async fn request<T>(&self, command: impl FnOnce(Reply<T>) -> Command) -> Result<T, CipError> {
+        let (reply, receiver) = oneshot::channel();
+        self.sender.send(command(reply)).await.map_err(|_| CipError::ConnectionClosed)?;
+
+        return receiver.await.unwrap_or(Err(CipError::ConnectionClosed));
+    }
+
+    pub async fn call_service(&self, class_id: u32, instance_id: u32, service: u8, data: Vec<u8>) -> Result<MessageRouterResponse, CipError> {
+        return self.request(|reply| Command::CallService { class_id, instance_id, service, data, reply }).await;
+    }
+
+    pub async fn get_attribute_single(&self, class_id: u32, instance_id: u32, attribute_id: u32) -> Result<MessageRouterResponse, CipError> {
+        return self.request(|reply| Command::GetAttributeSingle { class_id, instance_id, attribute_id, reply }).await;
+    }
+
+    pub async fn set_attribute_single(&self, class_id: u32, instance_id: u32, attribute_id: u32, data: Vec<u8>) -> Result<MessageRouterResponse, CipError> {
+        return self.request(|reply| Command::SetAttributeSingle { class_id, instance_id, attribute_id, data, reply }).await;
+    }
+
+    pub async fn get_supported_classes(&self) -> Result<Vec<u16>, CipError> {
+        return self.request(|reply| Command::GetSupportedClasses { reply }).await;
+    }
+
+    pub async fn send_nop(&self) -> Result<(), CipError> {
+        return self.request(|reply| Command::SendNop { reply }).await;
+    }
+
+    /// Unregisters the session and stops the task. Requests still queued fail
+    /// with [`CipError::ConnectionClosed`].
+    pub async fn shutdown(&self) -> Result<(), CipError> {
+        return self.request(|reply| Command::Shutdown { reply }).await;
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+}
+
+async fn run(mut client: CipClient, mut commands: mpsc::Receiver<Command>) {
+    while let Some(command) = commands.recv().await {
+        if command.is_cancelled() {
+            continue;
+        }
+
+        match command {
+            Command::CallService { class_id, instance_id, service, data, reply } => {
+                let _ = reply.send(client.call_service(class_id, instance_id, service, data).await);
+            }
+            Command::GetAttributeSingle { class_id, instance_id, attribute_id, reply } => {
+                let _ = reply.send(client.get_attribute_single(class_id, instance_id, attribute_id).await);
+            }
+            Command::SetAttributeSingle { class_id, instance_id, attribute_id, data, reply } => {
+                let _ = reply.send(client.set_attribute_single(class_id, instance_id, attribute_id, data).await);
+            }
+            Command::GetSupportedClasses { reply } => {
+                let _ = reply.send(client.get_supported_classes().await);
+            }
+            Command::SendNop { reply } => {
+                let _ = reply.send(client.send_nop().await);
+            }
+            Command::Shutdown { reply } => {
+                commands.close();
+                let _ = reply.send(client.disconnect().await);
+                return;
+            }
+        }
+    }
+
+    // Every handle was dropped without an explicit shutdown.
+    let _ = client.disconnect().await;
+}
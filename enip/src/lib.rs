@@ -2,8 +2,18 @@
 
 #[cfg(feature = "tcp-client")]
 pub mod tcp;
+#[cfg(feature = "tcp-client")]
+pub mod session;
+#[cfg(feature = "tcp-client")]
+pub mod reconnect;
 #[cfg(feature = "udp-client")]
 pub mod udp;
+#[cfg(feature = "udp-client")]
+pub mod discovery;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod codec;
+pub mod handle;
 pub mod encapsulation;
 pub mod cpf;
 mod common;
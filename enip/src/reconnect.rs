@@ -0,0 +1,308 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{net::SocketAddr, time::Duration};
+use async_trait::async_trait;
+use cip::{cip::{CipClass, CipService, Client, DataResult, MessageRouterResponse}, common::Serializable, error::CipError, objects::connection_manager::ForwardOpenResponse};
+use tokio::{net::TcpStream, sync::watch, time::timeout};
+
+use crate::{common::io_error, session::EnipSession};
+
+const GET_ATTRIBUTES_ALL: u8 = CipService::GetAttributesAll as u8;
+const GET_ATTRIBUTE_LIST: u8 = CipService::GetAttributeList as u8;
+const GET_ATTRIBUTE_SINGLE: u8 = CipService::GetAttributeSingle as u8;
+const READ_TAG: u8 = 0x4C;
+const READ_TAG_FRAGMENTED: u8 = 0x52;
+const UNCONNECTED_SEND: u8 = 0x52;
+const FORWARD_CLOSE: u8 = 0x4E;
+const FORWARD_OPEN: u8 = 0x54;
+const LARGE_FORWARD_OPEN: u8 = 0x5B;
+const CONNECTION_MANAGER: u32 = CipClass::ConnectionManager as u32;
+const SYMBOL: u32 = 0x6B;
+
+/// How requests are timed out and retried, and how lost connections are re-established.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Time allowed for a reply (and for each connection attempt).
+    pub request_timeout: Duration,
+    /// Extra attempts made for idempotent requests. Other requests are never resent.
+    pub max_retries: u32,
+    /// Reconnect attempts made before a request fails.
+    pub max_reconnect_attempts: u32,
+    /// Delay before the first reconnect attempt, doubled on every failure.
+    pub initial_backoff: Duration,
+    /// Upper bound for the reconnect delay.
+    pub max_backoff: Duration
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            request_timeout: Duration::from_secs(5),
+            max_retries: 3,
+            max_reconnect_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10)
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay to wait after the `attempt`th failed connection attempt (starting at 1).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        return self.initial_backoff.saturating_mul(factor).min(self.max_backoff);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Reconnecting { attempt: u32 },
+    Closed
+}
+
+/// Outcome of sending a remembered Forward Open again after a reconnect.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReopenResult {
+    /// The target accepted it; the reply carries the connection IDs it assigned this time.
+    Reopened(ForwardOpenResponse),
+    /// The target rejected it and the connection is forgotten.
+    Rejected { connection_serial_number: u16, general_status: u8, additional_status: Vec<u16> }
+}
+
+/// Splits a Message Router request into its service, target class and request data.
+fn split_request(packet: &[u8]) -> Option<(u8, Option<u32>, &[u8])> {
+    let service = *packet.first()?;
+    let path_length = *packet.get(1)? as usize * 2;
+    let path = packet.get(2..2 + path_length)?;
+    let data = packet.get(2 + path_length..)?;
+
+    let class = match path {
+        [0x20, class, ..] => Some(*class as u32),
+        [0x21, _, low, high, ..] => Some(u16::from_le_bytes([*low, *high]) as u32),
+        _ => None
+    };
+
+    return Some((service, class, data));
+}
+
+/// Returns the request the target will execute, looking inside an Unconnected Send.
+fn target_request(packet: &[u8]) -> Option<&[u8]> {
+    let (service, class, data) = split_request(packet)?;
+
+    if service == UNCONNECTED_SEND && class == Some(CONNECTION_MANAGER) {
+        let length = u16::from_le_bytes([*data.get(2)?, *data.get(3)?]) as usize;
+        return data.get(4..4 + length);
+    }
+
+    return Some(packet);
+}
+
+/// Whether `packet` only reads data, so it is safe to send again after a failure.
+pub fn is_idempotent(packet: &[u8]) -> bool {
+    match target_request(packet).and_then(split_request) {
+        Some((GET_ATTRIBUTES_ALL | GET_ATTRIBUTE_LIST | GET_ATTRIBUTE_SINGLE, _, _)) => true,
+        // Other classes reuse these service codes for requests that change state, like Get_and_Clear.
+        Some((READ_TAG | READ_TAG_FRAGMENTED, class, _)) => class.is_none() || class == Some(SYMBOL),
+        _ => false
+    }
+}
+
+/// A client that times out requests, retries idempotent ones and reconnects
+/// with exponential backoff when the connection to the target is lost.
+///
+/// Successful Forward Opens are remembered and sent again after a reconnect,
+/// until the matching Forward Close succeeds or the target rejects them.
+/// The outcome of each reopen is published through [`ReconnectingClient::subscribe_reopened`].
+pub struct ReconnectingClient {
+    addr: SocketAddr,
+    policy: RetryPolicy,
+    session: Option<EnipSession>,
+    connections: Vec<(Vec<u8>, Vec<u8>)>,
+    state: watch::Sender<ConnectionState>,
+    reopened: watch::Sender<Vec<ReopenResult>>,
+    connected_once: bool,
+    /// Counts the sessions opened so far.
+    generation: u64
+}
+
+impl ReconnectingClient {
+    pub fn new(addr: SocketAddr, policy: RetryPolicy) -> Self {
+        let (state, _) = watch::channel(ConnectionState::Disconnected);
+        let (reopened, _) = watch::channel(Vec::new());
+        Self { addr, policy, session: None, connections: Vec::new(), state, reopened, connected_once: false, generation: 0 }
+    }
+
+    /// Receives every change of the connection state.
+    pub fn subscribe(&self) -> watch::Receiver<ConnectionState> {
+        self.state.subscribe()
+    }
+
+    /// Receives the reopen results of every reconnect, in the order the connections were opened.
+    pub fn subscribe_reopened(&self) -> watch::Receiver<Vec<ReopenResult>> {
+        self.reopened.subscribe()
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        *self.state.borrow()
+    }
+
+    /// Returns the current session, connecting again if it was lost.
+    async fn session(&mut self) -> Result<&mut EnipSession, CipError> {
+        let alive = self.session.as_ref().is_some_and(|session| !session.is_closed());
+        if alive {
+            return self.session.as_mut().ok_or(CipError::ConnectionClosed);
+        }
+        self.session = None;
+
+        let mut attempt = 0;
+        loop {
+            if self.connected_once {
+                self.state.send_replace(ConnectionState::Reconnecting { attempt: attempt + 1 });
+            } else {
+                self.state.send_replace(ConnectionState::Connecting);
+            }
+
+            match self.open().await {
+                Ok((session, results)) => {
+                    if !results.is_empty() {
+                        let connections = core::mem::take(&mut self.connections);
+                        self.connections = connections.into_iter().zip(&results).filter(|(_, result)| matches!(result, ReopenResult::Reopened(_))).map(|(connection, _)| connection).collect();
+                        self.reopened.send_replace(results);
+                    }
+
+                    self.connected_once = true;
+                    self.generation += 1;
+                    self.state.send_replace(ConnectionState::Connected);
+                    return Ok(self.session.insert(session));
+                }
+                Err(e) => {
+                    attempt += 1;
+                    if attempt > self.policy.max_reconnect_attempts {
+                        self.state.send_replace(ConnectionState::Disconnected);
+                        return Err(e);
+                    }
+
+                    tokio::time::sleep(self.policy.backoff(attempt)).await;
+                }
+            }
+        }
+    }
+
+    /// Connects, registers a session and reopens the remembered connections.
+    /// Returns one result per remembered connection.
+    async fn open(&self) -> Result<(EnipSession, Vec<ReopenResult>), CipError> {
+        let request_timeout = self.policy.request_timeout;
+        let stream = timeout(request_timeout, TcpStream::connect(self.addr)).await.map_err(|_| CipError::Timeout)?.map_err(io_error)?;
+        let _ = stream.set_nodelay(true);
+        let session = timeout(request_timeout, EnipSession::connect(stream)).await.map_err(|_| CipError::Timeout)??;
+
+        let mut results = Vec::new();
+        for (triad, forward_open) in &self.connections {
+            let reply = timeout(request_timeout, session.send_rr_data(forward_open.clone())).await.map_err(|_| CipError::Timeout)??;
+            let (_, response) = MessageRouterResponse::deserialize(&reply.data)?;
+
+            if response.general_status != 0 {
+                let connection_serial_number = u16::from_le_bytes([triad[0], triad[1]]);
+                results.push(ReopenResult::Rejected { connection_serial_number, general_status: response.general_status, additional_status: response.additional_status });
+                continue;
+            }
+
+            let (_, reopened) = ForwardOpenResponse::deserialize(&response.data)?;
+            results.push(ReopenResult::Reopened(reopened));
+        }
+
+        return Ok((session, results));
+    }
+
+    /// Remembers successful Forward Opens and forgets them again on Forward Close.
+    fn track_connection(&mut self, packet: &[u8], reply: &DataResult) {
+        if reply.data.get(2) != Some(&0) {
+            return;
+        }
+
+        let (service, class, data) = match target_request(packet).and_then(split_request) {
+            Some(request) => request,
+            None => return
+        };
+        if class != Some(CONNECTION_MANAGER) {
+            return;
+        }
+
+        // Connection serial number, originator vendor ID and originator serial number.
+        let triad = match service {
+            FORWARD_OPEN | LARGE_FORWARD_OPEN => data.get(10..18),
+            FORWARD_CLOSE => data.get(2..10),
+            _ => None
+        };
+
+        if let Some(triad) = triad {
+            self.connections.retain(|(known, _)| known != triad);
+            if service != FORWARD_CLOSE {
+                self.connections.push((triad.to_vec(), packet.to_vec()));
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Client for ReconnectingClient {
+    async fn begin_session(&mut self) -> Result<(), CipError> {
+        self.session().await?;
+        return Ok(());
+    }
+
+    async fn close_session(&mut self) -> Result<(), CipError> {
+        let result = match self.session.take() {
+            Some(session) => session.close().await,
+            None => Ok(())
+        };
+
+        self.state.send_replace(ConnectionState::Closed);
+        return result;
+    }
+
+    async fn send_unconnected(&mut self, packet: Vec<u8>) -> Result<(), CipError> {
+        return self.session().await?.send_unconnected(packet).await;
+    }
+
+    async fn send_connected(&mut self, packet: Vec<u8>) -> Result<(), CipError> {
+        return self.session().await?.send_connected(packet).await;
+    }
+
+    async fn read_data(&mut self) -> Result<DataResult, CipError> {
+        let request_timeout = self.policy.request_timeout;
+        let session = self.session().await?;
+        return timeout(request_timeout, session.read_data()).await.map_err(|_| CipError::Timeout)?;
+    }
+
+    async fn send_nop(&mut self) -> Result<(), CipError> {
+        return self.session().await?.send_nop().await;
+    }
+
+    async fn session_generation(&mut self) -> Result<u64, CipError> {
+        self.session().await?;
+        return Ok(self.generation);
+    }
+
+    async fn request_unconnected(&mut self, packet: Vec<u8>) -> Result<DataResult, CipError> {
+        let retries = if is_idempotent(&packet) { self.policy.max_retries } else { 0 };
+        let mut attempt = 0;
+
+        loop {
+            let request_timeout = self.policy.request_timeout;
+            let session = self.session().await?.clone();
+            let result = timeout(request_timeout, session.send_rr_data(packet.clone())).await.unwrap_or(Err(CipError::Timeout));
+
+            match result {
+                Ok(reply) => {
+                    self.track_connection(&packet, &reply);
+                    return Ok(reply);
+                }
+                Err(e) if attempt >= retries => return Err(e),
+                Err(_) => attempt += 1
+            }
+        }
+    }
+}
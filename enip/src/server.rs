@@ -0,0 +1,251 @@
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc, vec::Vec};
+use core::{net::SocketAddr, sync::atomic::{AtomicU32, Ordering}, time::Duration};
+use async_trait::async_trait;
+use cip::{error::CipError, router::Router};
+use tokio::{io::AsyncWriteExt, net::{TcpListener, TcpStream}, sync::Mutex, time::timeout};
+
+use crate::{codec::EnipCodec, common::{io_error, Serializable}, cpf::{CipIdentityItem, CommonPacketHeader, CommonPacketList, ConnectedAddressItem, ConnectedDataItem, ListServicesItem, NullAddressItem, UnconnectedDataItem, CAPABILITY_CIP_TCP}, encapsulation::{EncapsulationCommand, EncapsulationStatus, EtherNetIPHeader, ListIdentity, ListInterfaces, ListServices, RegisterSession, SendRRData, SendUnitData, PROTOCOL_VERSION}};
+
+/// Receives the CIP messages carried by SendRRData and SendUnitData.
+#[async_trait]
+pub trait MessageHandler: Send + Sync {
+    /// Handles an unconnected explicit message and returns the Message Router response.
+    async fn handle_unconnected(&self, request: Vec<u8>) -> Vec<u8>;
+
+    /// Handles data sent on connection `connection_id`. Returns the connection
+    /// ID and data to answer with, if any.
+    async fn handle_connected(&self, _connection_id: u32, _data: Vec<u8>) -> Option<(u32, Vec<u8>)> {
+        None
+    }
+}
+
+#[async_trait]
+impl MessageHandler for Mutex<Router> {
+    async fn handle_unconnected(&self, request: Vec<u8>) -> Vec<u8> {
+        return self.lock().await.handle(&request);
+    }
+}
+
+pub struct ServerConfig {
+    /// Connections without traffic for this long are closed.
+    pub inactivity_timeout: Duration,
+    /// RegisterSession is refused once this many sessions are open.
+    pub max_sessions: usize
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self { inactivity_timeout: Duration::from_secs(120), max_sessions: 32 }
+    }
+}
+
+struct ServerInner {
+    handler: Box<dyn MessageHandler>,
+    identity: CipIdentityItem,
+    config: ServerConfig,
+    sessions: Mutex<BTreeMap<u32, SocketAddr>>,
+    next_handle: AtomicU32
+}
+
+/// Encapsulation server that accepts TCP connections and hands the CIP
+/// messages it receives to a [`MessageHandler`].
+#[derive(Clone)]
+pub struct EnipServer {
+    inner: Arc<ServerInner>
+}
+
+/// Sets the header length of a serialized packet to the size of its data.
+fn finish(mut packet: Vec<u8>) -> Vec<u8> {
+    let length = (packet.len() - 24) as u16;
+    packet[2..4].copy_from_slice(&length.to_le_bytes());
+    return packet;
+}
+
+/// A reply without data, used to report a non-zero status.
+fn status_reply(request: &EtherNetIPHeader, status: EncapsulationStatus) -> Vec<u8> {
+    let header = EtherNetIPHeader { command: request.command, length: 0, session_handle: request.session_handle, status: status.code(), sender_context: request.sender_context, options: 0 };
+    return header.serialize();
+}
+
+impl EnipServer {
+    /// `identity` is reported by ListIdentity; its socket address is filled in per connection.
+    pub fn new(handler: impl MessageHandler + 'static, identity: CipIdentityItem, config: ServerConfig) -> Self {
+        let inner = ServerInner { handler: Box::new(handler), identity, config, sessions: Mutex::new(BTreeMap::new()), next_handle: AtomicU32::new(1) };
+        return Self { inner: Arc::new(inner) };
+    }
+
+    pub async fn session_count(&self) -> usize {
+        self.inner.sessions.lock().await.len()
+    }
+
+    /// Accepts connections on `listener` until accepting fails.
+    pub async fn serve(&self, listener: TcpListener) -> Result<(), CipError> {
+        loop {
+            let (stream, _) = listener.accept().await.map_err(io_error)?;
+            let _ = stream.set_nodelay(true);
+            let server = self.clone();
+            tokio::spawn(async move { server.serve_connection(stream).await });
+        }
+    }
+
+    /// Serves one connection until the peer leaves, unregisters or is idle too long.
+    pub async fn serve_connection(&self, mut stream: TcpStream) {
+        let mut codec = EnipCodec::new();
+        let mut session: Option<u32> = None;
+
+        loop {
+            let packet = match timeout(self.inner.config.inactivity_timeout, codec.read_packet(&mut stream)).await {
+                Ok(Ok(packet)) => packet,
+                _ => break
+            };
+
+            let reply = match self.handle_packet(&stream, &mut session, &packet).await {
+                Ok(reply) => reply,
+                Err(_) => break
+            };
+
+            if let Some(reply) = reply {
+                if stream.write_all(&reply).await.is_err() {
+                    break;
+                }
+            }
+        }
+
+        if let Some(session_handle) = session {
+            self.inner.sessions.lock().await.remove(&session_handle);
+        }
+        let _ = stream.shutdown().await;
+    }
+
+    /// Handles one packet and returns the reply to send. An error closes the connection.
+    async fn handle_packet(&self, stream: &TcpStream, session: &mut Option<u32>, packet: &[u8]) -> Result<Option<Vec<u8>>, CipError> {
+        let (_, header) = EtherNetIPHeader::deserialize(packet)?;
+
+        let command = match header.command() {
+            Ok(command) => command,
+            Err(_) => return Ok(Some(status_reply(&header, EncapsulationStatus::InvalidCommand)))
+        };
+
+        let session_valid = session.is_some() && *session == Some(header.session_handle);
+        match command {
+            EncapsulationCommand::Nop => Ok(None),
+            EncapsulationCommand::ListIdentity => {
+                let mut identity = self.inner.identity.clone();
+                if let Ok(SocketAddr::V4(local)) = stream.local_addr() {
+                    identity.sin_family = 2;
+                    identity.sin_port = local.port();
+                    identity.sin_addr = (*local.ip()).into();
+                }
+
+                let mut reply = ListIdentity::request();
+                reply.header.sender_context = header.sender_context;
+                reply.items.identity_item.push(identity);
+                Ok(Some(finish(reply.serialize())))
+            }
+            EncapsulationCommand::ListServices => {
+                let mut reply = ListServices::request();
+                reply.header.sender_context = header.sender_context;
+                reply.items.services_item.push(ListServicesItem { header: CommonPacketHeader { type_id: 0x100, length: 20 }, version: 1, capability_flags: CAPABILITY_CIP_TCP, name: "Communications".into() });
+                Ok(Some(finish(reply.serialize())))
+            }
+            EncapsulationCommand::ListInterfaces => {
+                let mut reply = ListInterfaces::request();
+                reply.header.sender_context = header.sender_context;
+                let mut packet = reply.serialize();
+                packet.extend_from_slice(&0u16.to_le_bytes());
+                Ok(Some(finish(packet)))
+            }
+            EncapsulationCommand::RegisterSession => self.register(stream, session, packet).await.map(Some),
+            EncapsulationCommand::UnregisterSession => {
+                if session_valid {
+                    Err(CipError::ConnectionClosed)
+                } else {
+                    Ok(None)
+                }
+            }
+            EncapsulationCommand::SendRRData => {
+                if !session_valid {
+                    return Ok(Some(status_reply(&header, EncapsulationStatus::InvalidSessionHandle)));
+                }
+
+                let request = match SendRRData::deserialize(packet) {
+                    Ok((_, request)) => request,
+                    Err(_) => return Ok(Some(status_reply(&header, EncapsulationStatus::IncorrectData)))
+                };
+                let data = match request.items.unconnected_data_item.into_iter().next() {
+                    Some(item) => item.data,
+                    None => return Ok(Some(status_reply(&header, EncapsulationStatus::IncorrectData)))
+                };
+
+                let response = self.inner.handler.handle_unconnected(data).await;
+                let mut list = CommonPacketList::new();
+                list.null_address_item.push(NullAddressItem { type_id: 0, length: 0 });
+                list.unconnected_data_item.push(UnconnectedDataItem { header: CommonPacketHeader { type_id: 0xB2, length: response.len() as u16 }, data: response });
+                let reply_header = EtherNetIPHeader { command: header.command, length: 0, session_handle: header.session_handle, status: 0, sender_context: header.sender_context, options: 0 };
+                let reply = SendRRData { header: reply_header, interface_handle: 0, timeout: 0, items: list };
+                Ok(Some(finish(reply.serialize())))
+            }
+            EncapsulationCommand::SendUnitData => {
+                if !session_valid {
+                    return Ok(Some(status_reply(&header, EncapsulationStatus::InvalidSessionHandle)));
+                }
+
+                // SendUnitData has no reply at the encapsulation level, malformed packets are dropped.
+                let request = match SendUnitData::deserialize(packet) {
+                    Ok((_, request)) => request,
+                    Err(_) => return Ok(None)
+                };
+                let (connection_id, data) = match (request.items.connected_addr_item.first(), request.items.connected_data_item.into_iter().next()) {
+                    (Some(addr), Some(item)) => (addr.addr, item.data),
+                    _ => return Ok(None)
+                };
+
+                let (connection_id, response) = match self.inner.handler.handle_connected(connection_id, data).await {
+                    Some(response) => response,
+                    None => return Ok(None)
+                };
+                let mut list = CommonPacketList::new();
+                list.connected_addr_item.push(ConnectedAddressItem { header: CommonPacketHeader { type_id: 0xA1, length: 4 }, addr: connection_id });
+                list.connected_data_item.push(ConnectedDataItem { header: CommonPacketHeader { type_id: 0xB1, length: response.len() as u16 }, data: response });
+                let reply_header = EtherNetIPHeader { command: header.command, length: 0, session_handle: header.session_handle, status: 0, sender_context: 0, options: 0 };
+                let reply = SendUnitData { header: reply_header, interface_handle: 0, timeout: 0, items: list };
+                Ok(Some(finish(reply.serialize())))
+            }
+        }
+    }
+
+    async fn register(&self, stream: &TcpStream, session: &mut Option<u32>, packet: &[u8]) -> Result<Vec<u8>, CipError> {
+        let (_, request) = RegisterSession::deserialize(packet)?;
+        let mut reply = RegisterSession::request();
+        reply.header.sender_context = request.header.sender_context;
+
+        if request.version != PROTOCOL_VERSION {
+            reply.header.status = EncapsulationStatus::UnsupportedProtocolRevision.code();
+            return Ok(reply.serialize());
+        }
+
+        // Only one session may be registered per connection.
+        if session.is_some() || request.options != 0 {
+            reply.header.status = EncapsulationStatus::IncorrectData.code();
+            return Ok(reply.serialize());
+        }
+
+        let mut sessions = self.inner.sessions.lock().await;
+        if sessions.len() >= self.inner.config.max_sessions {
+            reply.header.status = EncapsulationStatus::InsufficientMemory.code();
+            return Ok(reply.serialize());
+        }
+
+        let mut session_handle = self.inner.next_handle.fetch_add(1, Ordering::Relaxed);
+        while session_handle == 0 || sessions.contains_key(&session_handle) {
+            session_handle = self.inner.next_handle.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let peer = stream.peer_addr().map_err(io_error)?;
+        sessions.insert(session_handle, peer);
+        *session = Some(session_handle);
+
+        reply.header.session_handle = session_handle;
+        return Ok(reply.serialize());
+    }
+}
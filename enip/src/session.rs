@@ -0,0 +1,258 @@
+use alloc::{boxed::Box, collections::{BTreeMap, VecDeque}, sync::Arc, vec::Vec};
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use async_trait::async_trait;
+use cip::{cip::{Client, DataResult}, error::CipError};
+use tokio::{io::AsyncWriteExt, net::{tcp::{OwnedReadHalf, OwnedWriteHalf}, TcpStream}, sync::{mpsc, oneshot, Mutex}, task::JoinHandle};
+
+use crate::{codec::EnipCodec, common::{io_error, Serializable}, cpf::{CommonPacketHeader, CommonPacketList, ConnectedAddressItem, ConnectedDataItem, NullAddressItem, UnconnectedDataItem}, encapsulation::{EncapsulationCommand, EtherNetIPHeader, RegisterSession, SendRRData, SendUnitData, UnregisterSession, NOP}};
+
+type Reply = Result<DataResult, CipError>;
+
+/// Number of outgoing packets that may be queued before senders have to wait.
+const WRITE_QUEUE_LENGTH: usize = 64;
+/// Number of packets without a sender context (e.g. SendUnitData) kept for `read_data`.
+const UNSOLICITED_QUEUE_LENGTH: usize = 64;
+
+enum Outgoing {
+    Packet(Vec<u8>),
+    Close
+}
+
+/// State shared between the session handles and the reader/writer tasks.
+struct Shared {
+    pending: Mutex<BTreeMap<u64, oneshot::Sender<Reply>>>,
+    closed: AtomicBool
+}
+
+impl Shared {
+    /// Marks the session closed and fails every request still waiting for a reply.
+    async fn fail_all(&self) {
+        let mut pending = self.pending.lock().await;
+        self.closed.store(true, Ordering::SeqCst);
+
+        while let Some((_, sender)) = pending.pop_first() {
+            let _ = sender.send(Err(CipError::ConnectionClosed));
+        }
+    }
+}
+
+struct SessionInner {
+    session_handle: u32,
+    next_context: AtomicU64,
+    connection_id: AtomicU32,
+    writer: mpsc::Sender<Outgoing>,
+    unsolicited: Mutex<mpsc::Receiver<Vec<u8>>>,
+    shared: Arc<Shared>,
+    reader: JoinHandle<()>
+}
+
+impl Drop for SessionInner {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// A registered encapsulation session over TCP that supports pipelined requests.
+///
+/// Every SendRRData gets a unique sender context and the reply with the same
+/// context is routed back to the caller, so any number of tasks can share one
+/// session by cloning it and have requests in flight at the same time.
+pub struct EnipSession {
+    inner: Arc<SessionInner>,
+    awaiting: VecDeque<oneshot::Receiver<Reply>>
+}
+
+impl Clone for EnipSession {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone(), awaiting: VecDeque::new() }
+    }
+}
+
+impl EnipSession {
+    /// Registers a session on `stream` and starts the background reader and writer.
+    pub async fn connect(stream: TcpStream) -> Result<Self, CipError> {
+        let (mut read_half, mut write_half) = stream.into_split();
+        let mut codec = EnipCodec::new();
+
+        write_half.write_all(&RegisterSession::request().serialize()).await.map_err(io_error)?;
+        let buf = codec.read_packet(&mut read_half).await?;
+        let (_, reply) = RegisterSession::deserialize(&buf)?;
+        let session_handle = reply.session_from_reply()?;
+
+        let shared = Arc::new(Shared { pending: Mutex::new(BTreeMap::new()), closed: AtomicBool::new(false) });
+        let (writer, outgoing) = mpsc::channel(WRITE_QUEUE_LENGTH);
+        let (unsolicited_sender, unsolicited) = mpsc::channel(UNSOLICITED_QUEUE_LENGTH);
+
+        tokio::spawn(write_loop(write_half, outgoing, shared.clone()));
+        let reader = tokio::spawn(read_loop(read_half, codec, session_handle, shared.clone(), unsolicited_sender));
+
+        let inner = SessionInner { session_handle, next_context: AtomicU64::new(1), connection_id: AtomicU32::new(0), writer, unsolicited: Mutex::new(unsolicited), shared, reader };
+        return Ok(Self { inner: Arc::new(inner), awaiting: VecDeque::new() });
+    }
+
+    pub fn session_handle(&self) -> u32 {
+        self.inner.session_handle
+    }
+
+    pub fn set_connection_id(&self, connection_id: u32) {
+        self.inner.connection_id.store(connection_id, Ordering::Relaxed);
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.inner.shared.closed.load(Ordering::SeqCst)
+    }
+
+    /// Sends `packet` as unconnected data in a SendRRData and waits for its reply.
+    pub async fn send_rr_data(&self, packet: Vec<u8>) -> Reply {
+        let receiver = self.submit(packet).await?;
+        return receiver.await.unwrap_or(Err(CipError::ConnectionClosed));
+    }
+
+    /// Queues a SendRRData and returns the receiver its reply will be delivered on.
+    async fn submit(&self, packet: Vec<u8>) -> Result<oneshot::Receiver<Reply>, CipError> {
+        let sender_context = self.inner.next_context.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+
+        {
+            let mut pending = self.inner.shared.pending.lock().await;
+            if self.is_closed() {
+                return Err(CipError::ConnectionClosed);
+            }
+
+            // Callers that gave up on their reply leave a closed sender behind.
+            pending.retain(|_, sender| !sender.is_closed());
+            pending.insert(sender_context, sender);
+        }
+
+        let header = EtherNetIPHeader { command: EncapsulationCommand::SendRRData as u16, session_handle: self.inner.session_handle, length: (packet.len() as u16 + 16), status: 0, sender_context, options: 0 };
+        let mut list = CommonPacketList::new();
+        list.null_address_item.push(NullAddressItem { type_id: 0, length: 0 });
+        list.unconnected_data_item.push(UnconnectedDataItem { header: CommonPacketHeader { type_id: 0xB2, length: packet.len() as u16 }, data: packet });
+        let packet = SendRRData { header, interface_handle: 0, timeout: 0, items: list };
+
+        if let Err(e) = self.write(packet.serialize()).await {
+            self.inner.shared.pending.lock().await.remove(&sender_context);
+            return Err(e);
+        }
+
+        return Ok(receiver);
+    }
+
+    async fn write(&self, packet: Vec<u8>) -> Result<(), CipError> {
+        return self.inner.writer.send(Outgoing::Packet(packet)).await.map_err(|_| CipError::ConnectionClosed);
+    }
+
+    /// Unregisters the session and closes the connection.
+    pub async fn close(&self) -> Result<(), CipError> {
+        let unregister = UnregisterSession { command: EncapsulationCommand::UnregisterSession as u16, length: 0, session_handle: self.inner.session_handle, status: 0, sender_context: 0, options: 0 };
+        self.write(unregister.serialize()).await?;
+        return self.inner.writer.send(Outgoing::Close).await.map_err(|_| CipError::ConnectionClosed);
+    }
+}
+
+async fn write_loop(mut stream: OwnedWriteHalf, mut outgoing: mpsc::Receiver<Outgoing>, shared: Arc<Shared>) {
+    while let Some(message) = outgoing.recv().await {
+        match message {
+            Outgoing::Packet(packet) => {
+                if stream.write_all(&packet).await.is_err() {
+                    break;
+                }
+            }
+            Outgoing::Close => {
+                let _ = stream.shutdown().await;
+                break;
+            }
+        }
+    }
+
+    shared.fail_all().await;
+}
+
+async fn read_loop(mut stream: OwnedReadHalf, mut codec: EnipCodec, session_handle: u32, shared: Arc<Shared>, unsolicited: mpsc::Sender<Vec<u8>>) {
+    while let Ok(packet) = codec.read_packet(&mut stream).await {
+        let header = match EtherNetIPHeader::deserialize(&packet) {
+            Ok((_, header)) => header,
+            Err(_) => break
+        };
+
+        if header.command != EncapsulationCommand::SendRRData as u16 {
+            // Nothing is waiting on these by context, keep them for `read_data`.
+            let _ = unsolicited.try_send(packet);
+            continue;
+        }
+
+        let sender = shared.pending.lock().await.remove(&header.sender_context);
+        if let Some(sender) = sender {
+            let reply = header.check().and_then(|_| header.expect_session(session_handle)).and_then(|_| SendRRData::deserialize(&packet).map_err(CipError::from)).map(|(_, rrdata)| {
+                let mut data = Vec::new();
+                for item in rrdata.items.unconnected_data_item {
+                    data.extend_from_slice(&item.data);
+                }
+
+                DataResult { status: header.status, data }
+            });
+            let _ = sender.send(reply);
+        }
+    }
+
+    shared.fail_all().await;
+}
+
+#[async_trait]
+impl Client for EnipSession {
+    async fn begin_session(&mut self) -> Result<(), CipError> {
+        // The session is registered by `EnipSession::connect`.
+        return Ok(());
+    }
+
+    async fn close_session(&mut self) -> Result<(), CipError> {
+        return self.close().await;
+    }
+
+    async fn send_unconnected(&mut self, packet: Vec<u8>) -> Result<(), CipError> {
+        let receiver = self.submit(packet).await?;
+        self.awaiting.push_back(receiver);
+        return Ok(());
+    }
+
+    async fn send_connected(&mut self, packet: Vec<u8>) -> Result<(), CipError> {
+        let header = EtherNetIPHeader { command: EncapsulationCommand::SendUnitData as u16, session_handle: self.inner.session_handle, length: (packet.len() as u16 + 16), status: 0, sender_context: 0, options: 0 };
+        let mut list = CommonPacketList::new();
+        list.connected_addr_item.push(ConnectedAddressItem { header: CommonPacketHeader { type_id: 0xA1, length: 4 }, addr: self.inner.connection_id.load(Ordering::Relaxed) });
+        list.connected_data_item.push(ConnectedDataItem { header: CommonPacketHeader { type_id: 0xB1, length: packet.len() as u16 }, data: packet });
+        let packet = SendUnitData { header, interface_handle: 0, timeout: 0, items: list };
+        return self.write(packet.serialize()).await;
+    }
+
+    async fn send_nop(&mut self) -> Result<(), CipError> {
+        let header = EtherNetIPHeader { command: EncapsulationCommand::Nop as u16, session_handle: self.inner.session_handle, length: 0, status: 0, sender_context: 0, options: 0 };
+        let packet = NOP { header, data: Vec::new() };
+        return self.write(packet.serialize()).await;
+    }
+
+    async fn read_data(&mut self) -> Result<DataResult, CipError> {
+        if let Some(receiver) = self.awaiting.pop_front() {
+            return receiver.await.unwrap_or(Err(CipError::ConnectionClosed));
+        }
+
+        let packet = self.inner.unsolicited.lock().await.recv().await.ok_or(CipError::ConnectionClosed)?;
+        let (_, header) = EtherNetIPHeader::deserialize(&packet)?;
+        header.check()?;
+        header.expect_session(self.inner.session_handle)?;
+        let mut data = Vec::new();
+
+        if header.command == EncapsulationCommand::SendUnitData as u16 {
+            let unit_data = SendUnitData::deserialize(&packet)?;
+
+            for item in unit_data.1.items.connected_data_item {
+                data.extend_from_slice(&item.data);
+            }
+        }
+
+        return Ok(DataResult { status: header.status, data });
+    }
+
+    async fn request_unconnected(&mut self, packet: Vec<u8>) -> Result<DataResult, CipError> {
+        return self.send_rr_data(packet).await;
+    }
+}
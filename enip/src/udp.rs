@@ -1,114 +1,220 @@
 use alloc::vec::Vec;
+use core::{future::Future, net::{Ipv4Addr, SocketAddr}, time::Duration};
 use async_trait::async_trait;
-use cip::cip::{Client, DataResult};
-use tokio::{io::{self, Interest}, net::UdpSocket};
+use cip::{cip::{Client, DataResult}, error::CipError};
+use tokio::{net::UdpSocket, time::{timeout_at, Instant}};
 use alloc::boxed::Box;
 
-use crate::{common::Serializable, cpf::{CommonPacketHeader, CommonPacketList, ConnectedAddressItem, ConnectedDataItem, NullAddressItem, UnconnectedDataItem}, encapsulation::{EtherNetIPHeader, SendRRData, SendUnitData, NOP}};
+use crate::{common::{io_error, Serializable}, cpf::{CipIdentityItem, CommonPacketHeader, CommonPacketList, ConnectedAddressItem, ConnectedDataItem, ListServicesItem, NullAddressItem, UnconnectedDataItem, UnknownItem}, encapsulation::{EncapsulationCommand, EtherNetIPHeader, ListIdentity, ListInterfaces, ListServices, SendRRData, SendUnitData, NOP}};
+
+/// Largest payload a UDP datagram can carry.
+const MAX_DATAGRAM_LENGTH: usize = 65535;
 
 pub struct UdpENIPClient {
     udp: UdpSocket,
-    connection_id: u32
+    connection_id: u32,
+    next_context: u64,
+    timeout: Option<Duration>,
+    awaiting: Option<u64>
 }
 
 impl UdpENIPClient {
-    fn new(stream: UdpSocket) -> Self {
-        Self { udp: stream, connection_id: 0 }
-    }
-
-    async fn read_packet(&self) -> Vec<u8> {
-        let _ready = self.udp.readable().await.unwrap();
-    
-        let mut data: Vec<u8> = alloc::vec![0; 512];
-
-        match self.udp.recv(&mut data).await {
-            Ok(n) => {
-                if n >= 24 {
-                    let mut local = alloc::vec![0; n];
-                    for i in 0..n {
-                        local[i] = data[i]
-                    }
-                    return local;
-                }
-
-                return Vec::new();
-            }
-            Err(e) => {
-                return Vec::new();
-            }
+    pub fn new(stream: UdpSocket) -> Self {
+        Self { udp: stream, connection_id: 0, next_context: 1, timeout: None, awaiting: None }
+    }
+
+    /// Binds an ephemeral local port and sends everything to `addr`.
+    pub async fn connect(addr: SocketAddr) -> Result<Self, CipError> {
+        let socket = UdpSocket::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))).await.map_err(io_error)?;
+        socket.connect(addr).await.map_err(io_error)?;
+
+        return Ok(Self::new(socket));
+    }
+
+    /// Binds `local` without a default peer, for one-shot requests with [`UdpENIPClient::send_rr_data_to`].
+    pub async fn bind(local: SocketAddr) -> Result<Self, CipError> {
+        let socket = UdpSocket::bind(local).await.map_err(io_error)?;
+        socket.set_broadcast(true).map_err(io_error)?;
+
+        return Ok(Self::new(socket));
+    }
+
+    /// Limits how long a read waits for a reply. `None` waits forever.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, CipError> {
+        self.udp.local_addr().map_err(io_error)
+    }
 
+    fn deadline(&self) -> Option<Instant> {
+        self.timeout.map(|timeout| Instant::now() + timeout)
+    }
+
+    async fn until<T>(deadline: Option<Instant>, future: impl Future<Output = Result<T, CipError>>) -> Result<T, CipError> {
+        match deadline {
+            Some(deadline) => timeout_at(deadline, future).await.map_err(|_| CipError::Timeout)?,
+            None => future.await
         }
     }
 
-    pub async fn send_packet(&mut self, packet: Vec<u8>) {
-        let ready = self.udp.ready(Interest::WRITABLE).await.unwrap();
-        if ready.is_writable() {
-            match &self.udp.send(&packet).await {
-                Ok(_) => {
-                }
-                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
-                }
-                Err(_) => {
-                    //x.push(work_item).await;
-                }
+    async fn read_packet_from(&self, deadline: Option<Instant>) -> Result<(Vec<u8>, SocketAddr), CipError> {
+        let mut data: Vec<u8> = alloc::vec![0; MAX_DATAGRAM_LENGTH];
+
+        let (n, addr) = Self::until(deadline, async { self.udp.recv_from(&mut data).await.map_err(io_error) }).await?;
+        if n < 24 {
+            return Err(CipError::Parse);
+        }
+        data.truncate(n);
+
+        return Ok((data, addr));
+    }
+
+    async fn read_packet(&self) -> Result<Vec<u8>, CipError> {
+        let (data, _) = self.read_packet_from(self.deadline()).await?;
+        return Ok(data);
+    }
+
+    /// Wraps `packet` in a SendRRData with a fresh sender context.
+    fn rr_data(&mut self, packet: Vec<u8>) -> (u64, Vec<u8>) {
+        let sender_context = self.next_context;
+        self.next_context = self.next_context.wrapping_add(1);
+
+        let header = EtherNetIPHeader { command: EncapsulationCommand::SendRRData as u16, session_handle: 0, length: (packet.len() as u16 + 16), status: 0, sender_context, options: 0 };
+        let mut list = CommonPacketList::new();
+        list.null_address_item.push(NullAddressItem{ type_id: 0, length: 0 });
+        list.unconnected_data_item.push(UnconnectedDataItem { header: CommonPacketHeader { type_id: 0xb2, length: packet.len() as u16 }, data: packet });
+        let packet = SendRRData { header: header, interface_handle: 0, timeout: 0, items: list };
+
+        return (sender_context, packet.serialize());
+    }
+
+    /// Waits for the SendRRData reply carrying `sender_context`, dropping stale replies
+    /// and datagrams too short to hold a header.
+    async fn read_rr_data(&self, sender_context: u64, deadline: Option<Instant>) -> Result<(SocketAddr, DataResult), CipError> {
+        loop {
+            let (packet, addr) = match self.read_packet_from(deadline).await {
+                Err(CipError::Parse) => continue,
+                result => result?
+            };
+            let rrdata = match SendRRData::deserialize(&packet) {
+                Ok((_, rrdata)) if rrdata.header.command == EncapsulationCommand::SendRRData as u16 && rrdata.header.sender_context == sender_context => rrdata,
+                _ => continue
+            };
+            rrdata.header.check()?;
+
+            let mut data = Vec::new();
+            for item in rrdata.items.unconnected_data_item {
+                data.extend_from_slice(&item.data);
             }
+
+            return Ok((addr, DataResult { status: rrdata.header.status, data }));
         }
+    }
+
+    /// Sends one unconnected message to `target` and returns the first reply
+    /// and where it came from. `target` may be a unicast or broadcast address.
+    pub async fn send_rr_data_to(&mut self, target: SocketAddr, packet: Vec<u8>) -> Result<(SocketAddr, DataResult), CipError> {
+        let (sender_context, packet) = self.rr_data(packet);
+        self.udp.send_to(&packet, target).await.map_err(io_error)?;
+
+        return self.read_rr_data(sender_context, self.deadline()).await;
+    }
+
+    pub async fn send_packet(&mut self, packet: Vec<u8>) -> Result<(), CipError> {
+        self.udp.send(&packet).await.map_err(io_error)?;
+        return Ok(());
     } 
+
+    /// Asks the target which encapsulation services it supports. No session is needed.
+    pub async fn list_services(&mut self) -> Result<Vec<ListServicesItem>, CipError> {
+        self.send_packet(ListServices::request().serialize()).await?;
+        let buf = self.read_packet().await?;
+        let (_, reply) = ListServices::deserialize(&buf)?;
+        reply.header.expect(EncapsulationCommand::ListServices)?;
+
+        return Ok(reply.items.services_item);
+    }
+
+    /// Lists the target's non-CIP interfaces. No session is needed.
+    pub async fn list_interfaces(&mut self) -> Result<Vec<UnknownItem>, CipError> {
+        self.send_packet(ListInterfaces::request().serialize()).await?;
+        let buf = self.read_packet().await?;
+        let (_, reply) = ListInterfaces::deserialize(&buf)?;
+        reply.header.expect(EncapsulationCommand::ListInterfaces)?;
+
+        return Ok(reply.items.unknown_item);
+    }
+
+    /// Reads the target's identity. No session is needed.
+    pub async fn list_identity(&mut self) -> Result<Vec<CipIdentityItem>, CipError> {
+        self.send_packet(ListIdentity::request().serialize()).await?;
+        let buf = self.read_packet().await?;
+        let (_, reply) = ListIdentity::deserialize(&buf)?;
+        reply.header.expect(EncapsulationCommand::ListIdentity)?;
+
+        return Ok(reply.items.identity_item);
+    }
 }
 
 #[async_trait]
 impl Client for UdpENIPClient {
-    async fn begin_session(&mut self) {
-        return;
+    async fn begin_session(&mut self) -> Result<(), CipError> {
+        return Ok(());
     }
 
-    async fn close_session(&mut self) {
-        return;
+    async fn close_session(&mut self) -> Result<(), CipError> {
+        return Ok(());
     }
 
-    async fn send_unconnected(&mut self, packet: Vec<u8>) {
-        let header = EtherNetIPHeader { command: 0x6F, session_handle: 0, length: (packet.len() as u16 + 16), status: 0, sender_context: 0, options: 0 };
-        let mut list = CommonPacketList::new();
-        list.null_address_item.push(NullAddressItem{ type_id: 0, length: 0 });
-        list.unconnected_data_item.push(UnconnectedDataItem { header: CommonPacketHeader { type_id: 0xb2, length: packet.len() as u16 }, data: packet });
-        let packet = SendRRData { header: header, interface_handle: 0, timeout: 0, items: list };
-        self.send_packet(packet.serialize()).await;
+    async fn send_unconnected(&mut self, packet: Vec<u8>) -> Result<(), CipError> {
+        let (sender_context, packet) = self.rr_data(packet);
+        self.send_packet(packet).await?;
+        self.awaiting = Some(sender_context);
+        return Ok(());
     }
 
-    async fn send_connected(&mut self, packet: Vec<u8>) {
-        let header = EtherNetIPHeader { command: 0x70, session_handle: 0, length: (packet.len() as u16 + 16), status: 0, sender_context: 0, options: 0 };
+    async fn send_connected(&mut self, packet: Vec<u8>) -> Result<(), CipError> {
+        let header = EtherNetIPHeader { command: EncapsulationCommand::SendUnitData as u16, session_handle: 0, length: (packet.len() as u16 + 16), status: 0, sender_context: 0, options: 0 };
         let mut list = CommonPacketList::new();
         list.connected_addr_item.push(ConnectedAddressItem{ header: CommonPacketHeader { type_id: 0xA1, length: 4 }, addr: self.connection_id  });
         list.connected_data_item.push(ConnectedDataItem { header: CommonPacketHeader { type_id: 0xB1, length: packet.len() as u16 }, data: packet });
         let packet = SendUnitData { header: header, interface_handle: 0, timeout: 0, items: list };
-        self.send_packet(packet.serialize()).await;
+        self.send_packet(packet.serialize()).await
     }
 
-    async fn send_nop(&mut self) {
-        let header = EtherNetIPHeader { command: 0x00, session_handle: self.connection_id, length: 0, status: 0, sender_context: 0, options: 0 };
+    async fn send_nop(&mut self) -> Result<(), CipError> {
+        let header = EtherNetIPHeader { command: EncapsulationCommand::Nop as u16, session_handle: self.connection_id, length: 0, status: 0, sender_context: 0, options: 0 };
         let packet = NOP { header: header, data: Vec::new() };
-        self.send_packet(packet.serialize()).await;
+        self.send_packet(packet.serialize()).await
     }
 
-    async fn read_data(&mut self) -> DataResult {
-        let result = self.read_packet().await;
-        let enip = EtherNetIPHeader::deserialize(&result).unwrap();
+    async fn read_data(&mut self) -> Result<DataResult, CipError> {
+        if let Some(sender_context) = self.awaiting.take() {
+            let (_, result) = self.read_rr_data(sender_context, self.deadline()).await?;
+            return Ok(result);
+        }
+
+        let result = self.read_packet().await?;
+        let enip = EtherNetIPHeader::deserialize(&result)?;
+        enip.1.check()?;
         let mut data = Vec::new();
 
-        if enip.1.command == 0x006F {
-            let rrdata = SendRRData::deserialize(&result).unwrap();
+        if enip.1.command == EncapsulationCommand::SendRRData as u16 {
+            let rrdata = SendRRData::deserialize(&result)?;
             
             for item in rrdata.1.items.unconnected_data_item {
                 data.extend_from_slice(&item.data);
             }
-        } else if enip.1.command == 0x0070 {
-            let rrdata= SendUnitData::deserialize(&result).unwrap();
+        } else if enip.1.command == EncapsulationCommand::SendUnitData as u16 {
+            let rrdata= SendUnitData::deserialize(&result)?;
             
             for item in rrdata.1.items.connected_data_item {
                 data.extend_from_slice(&item.data);
             }
         }
 
-        return DataResult { status: enip.1.status, data };
+        return Ok(DataResult { status: enip.1.status, data });
     }
 }
\ No newline at end of file
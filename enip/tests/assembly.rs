@@ -0,0 +1,56 @@
+use cip::{attributes::{Attribute, AttributeObject, AttributeTable}, cip::{CipClient, CipDataType}, common::Serializable, error::CipError, objects::assembly::AssemblyMember, router::Router};
+
+mod common;
+
+fn members() -> Vec<AssemblyMember> {
+    vec![
+        AssemblyMember { data_size: 16, path: vec![0x20, 0x0F, 0x24, 0x01, 0x30, 0x01] },
+        AssemblyMember { data_size: 16, path: Vec::new() }
+    ]
+}
+
+/// An input assembly with a member list and a configuration assembly.
+fn assembly_object() -> AttributeObject {
+    let member_list: Vec<u8> = members().iter().flat_map(|member| member.serialize()).collect();
+
+    let input = AttributeTable::new()
+        .with(Attribute::get(1, CipDataType::Uint, vec![2, 0]))
+        .with(Attribute::get(2, CipDataType::Struct, member_list))
+        .with(Attribute::get(3, CipDataType::Array, vec![0x10, 0x27, 0, 0]))
+        .with(Attribute::get(4, CipDataType::Uint, vec![4, 0]));
+    let configuration = AttributeTable::new()
+        .with(Attribute::get(3, CipDataType::Array, vec![0; 6]).settable())
+        .with(Attribute::get(4, CipDataType::Uint, vec![6, 0]));
+
+    AttributeObject::new(0x04, 2).with_instance(100, input).with_instance(151, configuration)
+}
+
+async fn connect() -> CipClient {
+    let mut router = Router::new();
+    router.register(assembly_object());
+
+    common::connect(router, "Drive").await
+}
+
+#[tokio::test]
+async fn client_reads_assembly() {
+    let mut client = connect().await;
+
+    assert_eq!(client.get_assembly_data(100).await.unwrap(), vec![0x10, 0x27, 0, 0]);
+    assert_eq!(client.get_assembly_size(100).await.unwrap(), 4);
+
+    let members = client.get_assembly_members(100).await.unwrap();
+    assert_eq!(members, self::members());
+    assert_eq!(members[0].epath().unwrap().attributes.len(), 3);
+    assert!(client.get_assembly_members(151).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn client_writes_configuration_assembly() {
+    let mut client = connect().await;
+
+    client.set_assembly_data(151, &[1, 2, 3, 4, 5, 6]).await.unwrap();
+    assert_eq!(client.get_assembly_data(151).await.unwrap(), vec![1, 2, 3, 4, 5, 6]);
+
+    assert!(matches!(client.set_assembly_data(100, &[0; 4]).await, Err(CipError::Service { general_status: 0x0E, .. })));
+}
@@ -0,0 +1,44 @@
+use cip::{attributes::{Attribute, AttributeObject, AttributeTable}, cip::{CipClient, CipDataType}, router::Router, value::CipValue};
+
+mod common;
+
+async fn connect() -> CipClient {
+    let table = AttributeTable::new()
+        .with(Attribute::get(1, CipDataType::Uint, vec![0x2A, 0]))
+        .with(Attribute::get(2, CipDataType::Real, 1.5f32.to_le_bytes().to_vec()).settable())
+        .with(Attribute::get(3, CipDataType::ShortString, vec![3, b'f', b'a', b'n']).settable());
+    let mut router = Router::new();
+    router.register(AttributeObject::new(0x64, 1).with_instance(1, table));
+
+    common::connect(router, "Drive").await
+}
+
+#[tokio::test]
+async fn set_attribute_single_sends_data() {
+    let mut client = connect().await;
+
+    client.set_attribute_single(0x64, 1, 2, 2.5f32.to_le_bytes().to_vec()).await.unwrap().check().unwrap();
+    assert_eq!(client.get_attribute_single(0x64, 1, 2).await.unwrap().data, 2.5f32.to_le_bytes());
+}
+
+#[tokio::test]
+async fn client_reads_attribute_list() {
+    let mut client = connect().await;
+
+    let entries = client.get_attribute_list(0x64, 1, &[(1, CipDataType::Uint), (9, CipDataType::Uint), (3, CipDataType::ShortString), (2, CipDataType::Real)]).await.unwrap();
+    assert_eq!(entries.iter().map(|entry| entry.value.clone()).collect::<Vec<_>>(), vec![Some(CipValue::Uint(42)), None, Some(CipValue::ShortString("fan".into())), Some(CipValue::Real(1.5))]);
+    assert_eq!(entries[1].status, 0x14);
+}
+
+#[tokio::test]
+async fn client_writes_attribute_list() {
+    let mut client = connect().await;
+
+    let entries = client.set_attribute_list(0x64, 1, &[(3, CipValue::ShortString("pump".into())), (2, CipValue::Real(-3.0)), (1, CipValue::Uint(7))]).await.unwrap();
+    assert_eq!(entries.iter().map(|entry| (entry.id, entry.status)).collect::<Vec<_>>(), vec![(3, 0), (2, 0), (1, 0x0E)]);
+    assert!(!entries[2].is_ok());
+
+    let entries = client.get_attribute_list(0x64, 1, &[(2, CipDataType::Real), (3, CipDataType::ShortString)]).await.unwrap();
+    assert_eq!(entries[0].value, Some(CipValue::Real(-3.0)));
+    assert_eq!(entries[1].value, Some(CipValue::ShortString("pump".into())));
+}
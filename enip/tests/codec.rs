@@ -0,0 +1,65 @@
+use cip::error::CipError;
+use enip::codec::EnipCodec;
+
+fn packet(command: u16, data: &[u8]) -> Vec<u8> {
+    let mut vec = Vec::new();
+    vec.extend_from_slice(&command.to_le_bytes());
+    vec.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    vec.extend_from_slice(&[0; 20]);
+    vec.extend_from_slice(data);
+    vec
+}
+
+#[test]
+fn codec_waits_for_split_packet() {
+    let full = packet(0x6F, &[1, 2, 3, 4]);
+    let mut codec = EnipCodec::new();
+
+    codec.extend(&full[..10]);
+    assert!(codec.next_packet().unwrap().is_none());
+    codec.extend(&full[10..26]);
+    assert!(codec.next_packet().unwrap().is_none());
+    codec.extend(&full[26..]);
+    assert_eq!(codec.next_packet().unwrap().unwrap(), full);
+    assert_eq!(codec.buffered(), 0);
+}
+
+#[test]
+fn codec_splits_coalesced_packets() {
+    let first = packet(0x65, &[1, 0, 0, 0]);
+    let second = packet(0x6F, &[9; 30]);
+    let mut codec = EnipCodec::new();
+
+    let mut data = first.clone();
+    data.extend_from_slice(&second);
+    data.extend_from_slice(&[0x70, 0x00]);
+    codec.extend(&data);
+
+    assert_eq!(codec.next_packet().unwrap().unwrap(), first);
+    assert_eq!(codec.next_packet().unwrap().unwrap(), second);
+    assert!(codec.next_packet().unwrap().is_none());
+    assert_eq!(codec.buffered(), 2);
+}
+
+#[test]
+fn codec_rejects_oversized_length() {
+    let mut codec = EnipCodec::with_max_length(16);
+    codec.extend(&packet(0x6F, &[0; 17]));
+
+    assert!(matches!(codec.next_packet(), Err(CipError::PacketTooLarge(17))));
+    assert_eq!(codec.buffered(), 0);
+}
+
+#[tokio::test]
+async fn codec_reads_packets_from_stream() {
+    let first = packet(0x6F, &[1, 2, 3]);
+    let second = packet(0x70, &[4, 5]);
+    let mut data = first.clone();
+    data.extend_from_slice(&second);
+
+    let mut reader = &data[..];
+    let mut codec = EnipCodec::new();
+    assert_eq!(codec.read_packet(&mut reader).await.unwrap(), first);
+    assert_eq!(codec.read_packet(&mut reader).await.unwrap(), second);
+    assert!(matches!(codec.read_packet(&mut reader).await, Err(CipError::ConnectionClosed)));
+}
@@ -0,0 +1,20 @@
+#![allow(dead_code)]
+
+use cip::{cip::CipClient, router::Router};
+use enip::{cpf::{CipIdentityItem, CommonPacketHeader}, server::{EnipServer, ServerConfig}, session::EnipSession};
+use tokio::{net::{TcpListener, TcpStream}, sync::Mutex};
+
+/// Identity the test servers advertise.
+pub fn item(product_name: &str) -> CipIdentityItem {
+    CipIdentityItem { header: CommonPacketHeader { type_id: 0x0C, length: 0 }, encapsulation_version: 1, sin_family: 0, sin_port: 0, sin_addr: 0, sin_zero: [0; 8], vendor_id: 1, device_type: 0x0C, product_code: 1, major_revision: 1, minor_revision: 1, status: 0, serial_number: 1, product_name: product_name.into(), state: 3 }
+}
+
+/// Serves `router` on a local port and returns a client with a registered session.
+pub async fn connect(router: Router, product_name: &str) -> CipClient {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = EnipServer::new(Mutex::new(router), item(product_name), ServerConfig::default());
+    tokio::spawn(async move { server.serve(listener).await });
+
+    CipClient::new(EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await.unwrap())
+}
@@ -0,0 +1,72 @@
+use cip::{cip::{CipClient, EPath, LogicalSegment, LogicalType}, error::CipError, objects::{connection_manager::{ConnectionData, ConnectionManagerCounters, ConnectionOwner, ConnectionState}, message_router::MessageRouterResponseStatusCodes}, router::{CipObject, ObjectPath, Router, ServiceResult}, common::Serializable};
+
+mod common;
+
+fn connection() -> ConnectionData {
+    ConnectionData { connection_number: 3, connection_state: ConnectionState::Established, originator_port: 2, target_port: 1, connection_serial_number: 0x1234, originator_vendor_id: 0x0001, originator_serial_number: 0xCAFEBABE, originator_ot_connection_id: 0x1000, target_ot_connection_id: 0x2000, ot_connection_timeout_multiplier: 2, originator_ot_rpi: 10_000, originator_ot_api: 10_000, originator_to_connection_id: 0x3000, target_to_connection_id: 0x4000, to_connection_timeout_multiplier: 2, originator_to_rpi: 20_000, originator_to_api: 20_000 }
+}
+
+/// Connection Manager that knows one established connection.
+struct ConnectionManager;
+
+impl CipObject for ConnectionManager {
+    fn class_id(&self) -> u16 {
+        0x06
+    }
+
+    fn has_instance(&self, instance: u32) -> bool {
+        instance == 1
+    }
+
+    fn instance_service(&mut self, service: u8, _path: &ObjectPath, data: &[u8]) -> ServiceResult {
+        let connection = connection();
+        match (service, data) {
+            (0x01, []) => Ok(ConnectionManagerCounters { open_requests: 12, open_format_rejects: 1, open_resource_rejects: 2, open_other_rejects: 3, close_requests: 4, connection_timeouts: 5, ..Default::default() }.serialize()),
+            (0x56, [3, 0]) => Ok(connection.serialize()),
+            (0x57, [0x34, 0x12, 0x01, 0x00, 0xBE, 0xBA, 0xFE, 0xCA]) => Ok(connection.serialize()),
+            (0x5A, [0, 2, 0x20, 0x04, 0x24, 0x65]) => Ok(ConnectionOwner { num_connections: 2, num_coo_connections: 1, num_roo_connections: 1, last_action: 0, connection_serial_number: 0x1234, originator_vendor_id: 1, originator_serial_number: 0xCAFEBABE }.serialize()),
+            (0x56 | 0x57 | 0x5A, _) => Err(MessageRouterResponseStatusCodes::ObjectDoesNotExist.into()),
+            _ => Err(MessageRouterResponseStatusCodes::ServiceNotSupported.into())
+        }
+    }
+}
+
+async fn connect() -> CipClient {
+    let mut router = Router::new();
+    router.register(ConnectionManager);
+
+    common::connect(router, "Adapter").await
+}
+
+#[tokio::test]
+async fn client_reads_connection_data() {
+    let mut client = connect().await;
+
+    assert_eq!(client.get_connection_data(3).await.unwrap(), connection());
+    assert_eq!(client.search_connection_data(0x1234, 1, 0xCAFEBABE).await.unwrap(), connection());
+    assert!(matches!(client.get_connection_data(4).await, Err(CipError::Service { general_status: 0x16, .. })));
+}
+
+#[tokio::test]
+async fn client_reads_connection_owner() {
+    let mut client = connect().await;
+
+    let mut path = EPath::new();
+    path.attributes.push(Box::new(LogicalSegment::init(LogicalType::ClassId as u8, 0x04)));
+    path.attributes.push(Box::new(LogicalSegment::init(LogicalType::InstanceId as u8, 0x65)));
+
+    let owner = client.get_connection_owner(&path).await.unwrap();
+    assert_eq!(owner.num_connections, 2);
+    assert_eq!(owner.num_coo_connections, 1);
+    assert_eq!(owner.originator_serial_number, 0xCAFEBABE);
+}
+
+#[tokio::test]
+async fn client_reads_counters() {
+    let mut client = connect().await;
+
+    let counters = client.get_connection_manager_counters().await.unwrap();
+    assert_eq!(counters.open_requests, 12);
+    assert_eq!(counters.open_rejects(), 6);
+    assert_eq!(counters.connection_timeouts, 5);
+}
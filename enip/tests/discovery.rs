@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use enip::discovery::discover_at;
+use tokio::net::UdpSocket;
+
+fn list_identity_reply(product_name: &str) -> Vec<u8> {
+    let mut item = Vec::new();
+    item.extend_from_slice(&1u16.to_le_bytes());
+    item.extend_from_slice(&2u16.to_be_bytes());
+    item.extend_from_slice(&44818u16.to_be_bytes());
+    item.extend_from_slice(&[192, 168, 1, 10]);
+    item.extend_from_slice(&[0; 8]);
+    item.extend_from_slice(&0x01u16.to_le_bytes());
+    item.extend_from_slice(&0x0Eu16.to_le_bytes());
+    item.extend_from_slice(&0x55u16.to_le_bytes());
+    item.extend_from_slice(&[20, 11]);
+    item.extend_from_slice(&0x0030u16.to_le_bytes());
+    item.extend_from_slice(&0xC0FFEEu32.to_le_bytes());
+    item.push(product_name.len() as u8);
+    item.extend_from_slice(product_name.as_bytes());
+    item.push(3);
+
+    let mut vec = Vec::new();
+    vec.extend_from_slice(&0x63u16.to_le_bytes());
+    vec.extend_from_slice(&((item.len() + 6) as u16).to_le_bytes());
+    vec.extend_from_slice(&[0; 20]);
+    vec.extend_from_slice(&1u16.to_le_bytes());
+    vec.extend_from_slice(&0x0Cu16.to_le_bytes());
+    vec.extend_from_slice(&(item.len() as u16).to_le_bytes());
+    vec.extend(item);
+    vec
+}
+
+#[tokio::test]
+async fn discovers_loopback_responder() {
+    let responder = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let target = responder.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let mut data = [0u8; 512];
+        let (n, from) = responder.recv_from(&mut data).await.unwrap();
+        assert_eq!(n, 24);
+        assert_eq!(u16::from_le_bytes([data[0], data[1]]), 0x63);
+
+        // Garbage must be ignored, and a second reply from the same address deduplicated.
+        responder.send_to(&[1, 2, 3], from).await.unwrap();
+        responder.send_to(&list_identity_reply("1756-L81E"), from).await.unwrap();
+        responder.send_to(&list_identity_reply("1756-L81E"), from).await.unwrap();
+    });
+
+    let devices = discover_at(target, Duration::from_millis(200)).await.unwrap();
+    assert_eq!(devices.len(), 1);
+
+    let identity = &devices[0].identity;
+    assert_eq!(devices[0].addr, target);
+    assert_eq!(identity.vendor_id, 1);
+    assert_eq!(identity.device_type, 0x0E);
+    assert_eq!(identity.product_code, 0x55);
+    assert_eq!((identity.major_revision, identity.minor_revision), (20, 11));
+    assert_eq!(identity.serial_number, 0xC0FFEE);
+    assert_eq!(identity.product_name, "1756-L81E");
+    assert_eq!(identity.state, 3);
+    assert_eq!(identity.socket_addr().to_string(), "192.168.1.10:44818");
+}
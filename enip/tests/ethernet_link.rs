@@ -0,0 +1,60 @@
+use cip::{cip::CipClient, error::CipError, objects::ethernet_link::{EthernetLink, EthernetLinkObject, InterfaceControl, InterfaceCounters, InterfaceFlags, NegotiationStatus}, router::Router};
+
+mod common;
+
+fn link() -> EthernetLink {
+    let counters = InterfaceCounters { in_ucast_packets: 90, in_nucast_packets: 5, in_errors: 5, out_ucast_packets: 50, ..Default::default() };
+    EthernetLink { interface_speed: 100, interface_flags: InterfaceFlags(0x0F), physical_address: [0, 0x1D, 0x9C, 1, 2, 3], interface_counters: Some(counters), media_counters: None, interface_control: Some(InterfaceControl { auto_negotiate: true, forced_full_duplex: false, forced_interface_speed: 0 }), interface_type: Some(2), interface_state: None, admin_state: None, interface_label: Some("Port 1".into()), interface_capability: None }
+}
+
+async fn connect() -> CipClient {
+    let mut router = Router::new();
+    router.register(EthernetLinkObject::new().with_link(1, &link()));
+
+    common::connect(router, "Switch").await
+}
+
+#[tokio::test]
+async fn client_reads_ethernet_link() {
+    let mut client = connect().await;
+    let link = client.get_ethernet_link(1).await.unwrap();
+
+    assert_eq!(link, self::link());
+    assert!(link.interface_flags.link_active());
+    assert!(link.interface_flags.full_duplex());
+    assert_eq!(link.interface_flags.negotiation_status(), NegotiationStatus::Successful);
+}
+
+#[tokio::test]
+async fn get_and_clear_resets_counters() {
+    let mut client = connect().await;
+
+    let counters = client.get_and_clear_interface_counters(1).await.unwrap();
+    assert_eq!(counters.in_error_rate(), Some(0.05));
+    assert_eq!(counters.out_error_rate(), Some(0.0));
+
+    let counters = client.get_and_clear_interface_counters(1).await.unwrap();
+    assert_eq!(counters, InterfaceCounters::default());
+    assert_eq!(counters.in_error_rate(), None);
+
+    assert!(matches!(client.get_and_clear_media_counters(1).await, Err(CipError::Service { general_status: 0x14, .. })));
+}
+
+#[tokio::test]
+async fn interface_control_is_settable() {
+    let mut client = connect().await;
+
+    let control = InterfaceControl { auto_negotiate: false, forced_full_duplex: true, forced_interface_speed: 10 };
+    client.set_interface_control(1, &control).await.unwrap();
+    assert_eq!(client.get_ethernet_link(1).await.unwrap().interface_control, Some(control));
+
+    let invalid = InterfaceControl { auto_negotiate: false, forced_full_duplex: true, forced_interface_speed: 0 };
+    assert!(matches!(client.set_interface_control(1, &invalid).await, Err(CipError::InvalidValue(_))));
+}
+
+#[test]
+fn counter_deltas_wrap() {
+    let earlier = InterfaceCounters { in_octets: u32::MAX - 9, ..Default::default() };
+    let later = InterfaceCounters { in_octets: 10, ..Default::default() };
+    assert_eq!(later.since(&earlier).in_octets, 20);
+}
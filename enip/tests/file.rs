@@ -0,0 +1,128 @@
+use cip::{cip::CipClient, error::CipError, objects::{file::{file_checksum, FileRevision, TransferPacketType, EDS_FILE_INSTANCE}, message_router::MessageRouterResponseStatusCodes}, router::{CipObject, ObjectPath, Router, ServiceResult}};
+
+mod common;
+
+const EDS: &[u8] = b"[File]\r\n    DescText = \"Test drive\";\r\n    CreateDate = 01-01-2024;\r\n[Device]\r\n    VendCode = 1;\r\n    ProdName = \"Drive\";\r\n";
+
+/// File object with the EDS in instance 0xC8 and a writable instance 1.
+struct FileObject {
+    eds: Vec<u8>,
+    user_file: Vec<u8>,
+    download: Vec<u8>,
+    transfer_size: usize,
+    corrupt_checksum: bool
+}
+
+impl FileObject {
+    fn file(&self, instance: u32) -> &[u8] {
+        if instance == EDS_FILE_INSTANCE { &self.eds } else { &self.user_file }
+    }
+}
+
+impl CipObject for FileObject {
+    fn class_id(&self) -> u16 {
+        0x37
+    }
+
+    fn has_instance(&self, instance: u32) -> bool {
+        instance == 1 || instance == EDS_FILE_INSTANCE
+    }
+
+    fn instance_service(&mut self, service: u8, path: &ObjectPath, data: &[u8]) -> ServiceResult {
+        let file = self.file(path.instance).to_vec();
+        match service {
+            0x0E if path.attribute == Some(11) => Ok(vec![0]),
+            0x4B => {
+                self.transfer_size = self.transfer_size.min(data[0] as usize);
+                let mut reply = (file.len() as u32).to_le_bytes().to_vec();
+                reply.push(self.transfer_size as u8);
+                Ok(reply)
+            }
+            0x4F => {
+                let chunks: Vec<&[u8]> = file.chunks(self.transfer_size).collect();
+                let number = data[0] as usize;
+                let packet_type = match (number == 0, number == chunks.len() - 1) {
+                    (true, true) => 4,
+                    (true, false) => 0,
+                    (false, true) => 2,
+                    (false, false) => 1
+                };
+
+                let mut reply = vec![data[0], packet_type];
+                reply.extend_from_slice(chunks[number]);
+                if packet_type == 2 || packet_type == 4 {
+                    let checksum = file_checksum(&file).wrapping_add(self.corrupt_checksum as u16);
+                    reply.extend_from_slice(&checksum.to_le_bytes());
+                }
+                Ok(reply)
+            }
+            0x4C => {
+                self.download.clear();
+                Ok(vec![0, 0, 0, 0, 0, 0, 8])
+            }
+            0x50 => {
+                let (packet_type, payload) = (data[1], &data[2..]);
+                if packet_type == TransferPacketType::Last as u8 || packet_type == TransferPacketType::FirstAndLast as u8 {
+                    let (payload, checksum) = payload.split_at(payload.len() - 2);
+                    self.download.extend_from_slice(payload);
+                    if file_checksum(&self.download) != u16::from_le_bytes([checksum[0], checksum[1]]) {
+                        return Err(MessageRouterResponseStatusCodes::InvalidParameterValue.into());
+                    }
+                    self.user_file = std::mem::take(&mut self.download);
+                } else {
+                    self.download.extend_from_slice(payload);
+                }
+                Ok(vec![data[0]])
+            }
+            _ => Err(MessageRouterResponseStatusCodes::ServiceNotSupported.into())
+        }
+    }
+}
+
+async fn connect(corrupt_checksum: bool) -> CipClient {
+    let mut router = Router::new();
+    router.register(FileObject { eds: EDS.to_vec(), user_file: Vec::new(), download: Vec::new(), transfer_size: 32, corrupt_checksum });
+
+    common::connect(router, "Drive").await
+}
+
+#[test]
+fn checksum_is_twos_complement_of_sum() {
+    assert_eq!(file_checksum(&[]), 0);
+    assert_eq!(file_checksum(&[1, 2, 3]), 0xFFFA);
+    assert_eq!(file_checksum(&[0xFF; 300]).wrapping_add((0xFF * 300u32) as u16), 0);
+}
+
+#[tokio::test]
+async fn client_uploads_eds_file() {
+    let mut client = connect(false).await;
+
+    let info = client.initiate_upload(EDS_FILE_INSTANCE, 255).await.unwrap();
+    assert_eq!(info.file_size as usize, EDS.len());
+    assert_eq!(info.transfer_size, 32);
+
+    let packet = client.upload_transfer(EDS_FILE_INSTANCE, 0).await.unwrap();
+    assert_eq!(packet.packet_type, TransferPacketType::First);
+    assert_eq!(packet.data, EDS[..32]);
+
+    assert_eq!(client.get_eds_file().await.unwrap(), EDS);
+}
+
+#[tokio::test]
+async fn upload_verifies_checksum() {
+    let mut client = connect(true).await;
+
+    assert!(matches!(client.get_eds_file().await, Err(CipError::ChecksumMismatch { .. })));
+}
+
+#[tokio::test]
+async fn client_downloads_file() {
+    let mut client = connect(false).await;
+    let file: Vec<u8> = (0..=40).collect();
+
+    client.download_file(1, &file, FileRevision { major: 1, minor: 2 }, "config.bin").await.unwrap();
+    assert_eq!(client.upload_file(1).await.unwrap(), file);
+
+    client.download_file(1, b"short", FileRevision { major: 1, minor: 3 }, "config.bin").await.unwrap();
+    assert_eq!(client.upload_file(1).await.unwrap(), b"short");
+}
@@ -0,0 +1,82 @@
+use cip::cip::CipClient;
+use enip::{codec::EnipCodec, handle::CipHandle, session::EnipSession};
+use tokio::{io::AsyncWriteExt, net::{TcpListener, TcpStream}};
+
+fn header(command: u16, length: usize, sender_context: u64) -> Vec<u8> {
+    let mut vec = Vec::new();
+    vec.extend_from_slice(&command.to_le_bytes());
+    vec.extend_from_slice(&(length as u16).to_le_bytes());
+    vec.extend_from_slice(&0x42u32.to_le_bytes());
+    vec.extend_from_slice(&0u32.to_le_bytes());
+    vec.extend_from_slice(&sender_context.to_le_bytes());
+    vec.extend_from_slice(&0u32.to_le_bytes());
+    vec
+}
+
+/// Answers every SendRRData with a Get_Attribute_Single reply carrying the
+/// low byte of its sender context, and returns the last command it received.
+async fn fake_device(listener: TcpListener) -> u16 {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut codec = EnipCodec::new();
+
+    codec.read_packet(&mut stream).await.unwrap();
+    let mut reply = header(0x65, 4, 0);
+    reply.extend_from_slice(&[1, 0, 0, 0]);
+    stream.write_all(&reply).await.unwrap();
+
+    loop {
+        let request = codec.read_packet(&mut stream).await.unwrap();
+        let command = u16::from_le_bytes([request[0], request[1]]);
+        if command != 0x6F {
+            return command;
+        }
+
+        let sender_context = u64::from_le_bytes(request[12..20].try_into().unwrap());
+        let data = [0x8E, 0, 0, 0, sender_context as u8];
+        let mut reply = header(0x6F, data.len() + 16, sender_context);
+        reply.extend_from_slice(&[0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0xB2, 0, data.len() as u8, 0]);
+        reply.extend_from_slice(&data);
+        stream.write_all(&reply).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn handle_is_shared_between_tasks_and_unregisters_on_shutdown() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let device = tokio::spawn(fake_device(listener));
+
+    let session = EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await.unwrap();
+    let handle = CipHandle::spawn(CipClient::new(session), 4);
+
+    let mut tasks = Vec::new();
+    for _ in 0..8 {
+        let handle = handle.clone();
+        tasks.push(tokio::spawn(async move { handle.get_attribute_single(1, 1, 1).await.unwrap() }));
+    }
+
+    let mut contexts = Vec::new();
+    for task in tasks {
+        let response = task.await.unwrap();
+        assert_eq!(response.general_status, 0);
+        contexts.push(response.data[0]);
+    }
+    contexts.sort();
+    contexts.dedup();
+    assert_eq!(contexts.len(), 8);
+
+    handle.shutdown().await.unwrap();
+    assert_eq!(device.await.unwrap(), 0x66);
+    assert!(handle.get_attribute_single(1, 1, 1).await.is_err());
+}
+
+#[tokio::test]
+async fn zero_capacity_still_queues_one_request() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(fake_device(listener));
+
+    let session = EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await.unwrap();
+    let handle = CipHandle::spawn(CipClient::new(session), 0);
+    assert_eq!(handle.get_attribute_single(1, 1, 1).await.unwrap().general_status, 0);
+}
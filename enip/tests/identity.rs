@@ -0,0 +1,138 @@
+use std::{sync::{Arc, Mutex as StdMutex}, time::Duration};
+
+use cip::{cip::{CipClient, EPath, ElectronicKeySegment}, error::{CipError, KeyMismatch}, objects::{connection_manager::ForwardOpenRequest, identity::{DeviceState, ExtendedDeviceStatus, Identity, IdentityObject, IdentityStatus, ResetType}}, router::Router};
+use enip::{reconnect::{ReconnectingClient, RetryPolicy}, server::{EnipServer, ServerConfig}};
+use tokio::{net::TcpListener, sync::{oneshot, Mutex}};
+
+mod common;
+
+fn identity() -> Identity {
+    Identity { vendor_id: 1, device_type: 0x0C, product_code: 0x36, major_revision: 2, minor_revision: 7, status: IdentityStatus(0x0065), serial_number: 0x12345678, product_name: "Gateway".into(), state: Some(DeviceState::Operational) }
+}
+
+async fn connect(router: Router) -> CipClient {
+    common::connect(router, "Gateway").await
+}
+
+#[test]
+fn status_bits_are_decoded() {
+    let status = IdentityStatus(0x0565);
+    assert!(status.owned());
+    assert!(status.configured());
+    assert_eq!(status.extended_status(), ExtendedDeviceStatus::IoConnectionRunMode);
+    assert!(status.minor_recoverable_fault());
+    assert!(!status.minor_unrecoverable_fault());
+    assert!(status.major_recoverable_fault());
+    assert!(!status.major_unrecoverable_fault());
+}
+
+#[tokio::test]
+async fn client_reads_identity_from_server() {
+    let mut router = Router::new();
+    router.register(IdentityObject::new(&identity()));
+    let mut client = connect(router).await;
+
+    assert_eq!(client.get_identity().await.unwrap(), identity());
+
+    let response = client.get_attribute_single(1, 1, 7).await.unwrap();
+    assert_eq!(response.data, b"\x07Gateway");
+    assert!(matches!(client.reset(ResetType::PowerCycle).await, Err(CipError::Service { general_status: 0x08, .. })));
+
+    // Reset is only listed among the optional services once a handler is installed.
+    assert_eq!(client.get_attribute_single(1, 0, 5).await.unwrap().data, [0, 0]);
+}
+
+#[tokio::test]
+async fn reset_types_reach_the_handler() {
+    let resets = Arc::new(StdMutex::new(Vec::new()));
+    let seen = resets.clone();
+    let mut router = Router::new();
+    router.register(IdentityObject::new(&identity()).with_reset_handler(move |reset_type| {
+        seen.lock().unwrap().push(reset_type);
+        Ok(())
+    }));
+    let mut client = connect(router).await;
+
+    assert_eq!(client.get_attribute_single(1, 0, 5).await.unwrap().data, [1, 0, 0x05, 0]);
+    client.reset(ResetType::FactoryDefaults).await.unwrap();
+    client.reset(ResetType::FactoryDefaultsExceptCommunication).await.unwrap();
+    assert_eq!(client.call_service(1, 1, 0x05, vec![7]).await.unwrap().general_status, 0x20);
+    assert_eq!(*resets.lock().unwrap(), vec![ResetType::FactoryDefaults, ResetType::FactoryDefaultsExceptCommunication]);
+}
+
+#[tokio::test]
+async fn writes_wait_for_a_matching_key() {
+    let resets = Arc::new(StdMutex::new(Vec::new()));
+    let seen = resets.clone();
+    let mut router = Router::new();
+    router.register(IdentityObject::new(&identity()).with_reset_handler(move |reset_type| {
+        seen.lock().unwrap().push(reset_type);
+        Ok(())
+    }));
+    let mut client = connect(router).await;
+
+    let wrong = ElectronicKeySegment { product_code: 0x37, ..ElectronicKeySegment::exact(&identity()) };
+    client.set_expected_key(Some(wrong));
+    assert!(matches!(client.reset(ResetType::FactoryDefaults).await, Err(CipError::KeyMismatch(KeyMismatch::VendorOrProduct))));
+    assert!(resets.lock().unwrap().is_empty());
+
+    // Reads are not checked.
+    client.get_attribute_single(1, 1, 7).await.unwrap();
+
+    client.set_expected_key(Some(ElectronicKeySegment::compatible(&identity())));
+    client.reset(ResetType::FactoryDefaults).await.unwrap();
+    assert_eq!(*resets.lock().unwrap(), vec![ResetType::FactoryDefaults]);
+    assert_eq!(client.verify_identity(&ElectronicKeySegment::exact(&identity())).await.unwrap(), identity());
+}
+
+#[tokio::test]
+async fn counter_clears_and_forward_opens_need_the_key() {
+    let mut router = Router::new();
+    router.register(IdentityObject::new(&identity()));
+    let mut client = connect(router).await;
+
+    client.set_expected_key(Some(ElectronicKeySegment { device_type: 0x2B, ..ElectronicKeySegment::exact(&identity()) }));
+    assert!(matches!(client.get_and_clear_interface_counters(1).await, Err(CipError::KeyMismatch(KeyMismatch::DeviceType))));
+    let request = ForwardOpenRequest::create_null_forward_open(EPath::new());
+    assert!(matches!(client.forward_open(&request).await, Err(CipError::KeyMismatch(KeyMismatch::DeviceType))));
+}
+
+#[tokio::test]
+async fn key_is_checked_again_after_a_reconnect() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (swap, swapped) = oneshot::channel::<()>();
+    let resets = Arc::new(StdMutex::new(Vec::new()));
+
+    let seen = resets.clone();
+    tokio::spawn(async move {
+        // The first device answers until it is swapped for one with another product code.
+        let mut router = Router::new();
+        router.register(IdentityObject::new(&identity()).with_reset_handler(move |reset_type| {
+            seen.lock().unwrap().push(reset_type);
+            Ok(())
+        }));
+        let server = EnipServer::new(Mutex::new(router), common::item("Gateway"), ServerConfig::default());
+        let (stream, _) = listener.accept().await.unwrap();
+        tokio::select! {
+            _ = server.serve_connection(stream) => {}
+            _ = swapped => {}
+        }
+
+        let mut router = Router::new();
+        router.register(IdentityObject::new(&Identity { product_code: 0x37, ..identity() }).with_reset_handler(|_| Ok(())));
+        let server = EnipServer::new(Mutex::new(router), common::item("Gateway"), ServerConfig::default());
+        server.serve(listener).await
+    });
+
+    let policy = RetryPolicy { request_timeout: Duration::from_millis(500), initial_backoff: Duration::from_millis(10), ..RetryPolicy::default() };
+    let mut client = CipClient::new(ReconnectingClient::new(addr, policy));
+    client.set_expected_key(Some(ElectronicKeySegment::exact(&identity())));
+    client.reset(ResetType::FactoryDefaults).await.unwrap();
+    assert_eq!(*resets.lock().unwrap(), vec![ResetType::FactoryDefaults]);
+
+    swap.send(()).unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert!(matches!(client.reset(ResetType::FactoryDefaults).await, Err(CipError::KeyMismatch(KeyMismatch::VendorOrProduct))));
+}
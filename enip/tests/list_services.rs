@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use enip::{codec::EnipCodec, tcp::TcpEnipClient, udp::UdpENIPClient};
+use tokio::{io::AsyncWriteExt, net::{TcpListener, TcpStream, UdpSocket}};
+
+fn reply(command: u16, items: &[u8], count: u16) -> Vec<u8> {
+    let mut vec = Vec::new();
+    vec.extend_from_slice(&command.to_le_bytes());
+    vec.extend_from_slice(&((items.len() + 2) as u16).to_le_bytes());
+    vec.extend_from_slice(&[0; 20]);
+    vec.extend_from_slice(&count.to_le_bytes());
+    vec.extend_from_slice(items);
+    vec
+}
+
+#[tokio::test]
+async fn lists_services_and_interfaces_without_session() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut codec = EnipCodec::new();
+
+        let request = codec.read_packet(&mut stream).await.unwrap();
+        assert_eq!(u16::from_le_bytes([request[0], request[1]]), 0x04);
+        assert_eq!(u32::from_le_bytes(request[4..8].try_into().unwrap()), 0);
+        let mut item = vec![0x00, 0x01, 20, 0, 1, 0, 0x20, 0x01];
+        item.extend_from_slice(b"Communications\0\0");
+        stream.write_all(&reply(0x04, &item, 1)).await.unwrap();
+
+        let request = codec.read_packet(&mut stream).await.unwrap();
+        assert_eq!(u16::from_le_bytes([request[0], request[1]]), 0x64);
+        stream.write_all(&reply(0x64, &[0x34, 0x12, 2, 0, 0xAA, 0xBB], 1)).await.unwrap();
+    });
+
+    let mut client = TcpEnipClient::new(TcpStream::connect(addr).await.unwrap());
+
+    let services = client.list_services().await.unwrap();
+    assert_eq!(services.len(), 1);
+    assert_eq!(services[0].name, "Communications");
+    assert!(services[0].supports_cip_tcp());
+    assert!(services[0].supports_class_0_1_udp());
+
+    let interfaces = client.list_interfaces().await.unwrap();
+    assert_eq!(interfaces.len(), 1);
+    assert_eq!(interfaces[0].header.type_id, 0x1234);
+    assert_eq!(interfaces[0].data, vec![0xAA, 0xBB]);
+}
+
+#[tokio::test]
+async fn lists_services_and_interfaces_over_udp() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let addr = socket.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let mut data = vec![0u8; 512];
+
+        // A service item with a longer name than usual must not shift the next one.
+        let (_, from) = socket.recv_from(&mut data).await.unwrap();
+        assert_eq!(u16::from_le_bytes([data[0], data[1]]), 0x04);
+        let mut items = vec![0x00, 0x01, 24, 0, 1, 0, 0x20, 0x00];
+        items.extend_from_slice(b"Communications L\0\0\0\0");
+        items.extend_from_slice(&[0x00, 0x01, 20, 0, 1, 0, 0x00, 0x01]);
+        items.extend_from_slice(b"IO\0\0\0\0\0\0\0\0\0\0\0\0\0\0");
+        socket.send_to(&reply(0x04, &items, 2), from).await.unwrap();
+
+        let (_, from) = socket.recv_from(&mut data).await.unwrap();
+        assert_eq!(u16::from_le_bytes([data[0], data[1]]), 0x64);
+        socket.send_to(&reply(0x64, &[0x34, 0x12, 2, 0, 0xAA, 0xBB], 1), from).await.unwrap();
+    });
+
+    let mut client = UdpENIPClient::connect(addr).await.unwrap();
+    client.set_timeout(Some(Duration::from_millis(500)));
+
+    let services = client.list_services().await.unwrap();
+    assert_eq!(services.len(), 2);
+    assert_eq!(services[0].name, "Communications L");
+    assert!(services[0].supports_cip_tcp());
+    assert!(!services[0].supports_class_0_1_udp());
+    assert_eq!(services[1].name, "IO");
+    assert!(services[1].supports_class_0_1_udp());
+
+    let interfaces = client.list_interfaces().await.unwrap();
+    assert_eq!(interfaces.len(), 1);
+    assert_eq!(interfaces[0].data, vec![0xAA, 0xBB]);
+}
@@ -0,0 +1,123 @@
+use cip::{attributes::{Attribute, AttributeObject, AttributeTable}, cip::{CipClient, CipDataType}, error::CipError, objects::parameter::{ParameterBackup, ParameterBackupEntry, RestoreOutcome, Scaling}, router::Router, value::CipValue};
+
+mod common;
+
+fn short_string(value: &str) -> Vec<u8> {
+    let mut vec = vec![value.len() as u8];
+    vec.extend_from_slice(value.as_bytes());
+    vec
+}
+
+/// A scaled speed setpoint, a read-only current monitor and a stub acceleration time.
+fn parameter_object() -> AttributeObject {
+    let speed = AttributeTable::new()
+        .with(Attribute::get(1, CipDataType::Uint, vec![0xDC, 0x05]).settable())
+        .with(Attribute::get(2, CipDataType::Usint, vec![6]))
+        .with(Attribute::get(3, CipDataType::Array, vec![0x20, 0x04, 0x24, 0x96, 0x30, 0x03]))
+        .with(Attribute::get(4, CipDataType::Word, vec![0x04, 0x00]))
+        .with(Attribute::get(5, CipDataType::Usint, vec![0xC7]))
+        .with(Attribute::get(6, CipDataType::Usint, vec![2]))
+        .with(Attribute::get(7, CipDataType::ShortString, short_string("Speed")))
+        .with(Attribute::get(8, CipDataType::ShortString, short_string("rpm")))
+        .with(Attribute::get(9, CipDataType::ShortString, short_string("Motor speed setpoint")))
+        .with(Attribute::get(10, CipDataType::Uint, vec![0, 0]))
+        .with(Attribute::get(11, CipDataType::Uint, vec![0x70, 0x17]))
+        .with(Attribute::get(12, CipDataType::Uint, vec![0xE8, 0x03]))
+        .with(Attribute::get(13, CipDataType::Uint, vec![1, 0]))
+        .with(Attribute::get(14, CipDataType::Uint, vec![1, 0]))
+        .with(Attribute::get(15, CipDataType::Uint, vec![1, 0]))
+        .with(Attribute::get(16, CipDataType::Int, vec![0, 0]))
+        .with(Attribute::get(21, CipDataType::Usint, vec![1]));
+    let current = AttributeTable::new()
+        .with(Attribute::get(1, CipDataType::Real, 4.5f32.to_le_bytes().to_vec()))
+        .with(Attribute::get(2, CipDataType::Usint, vec![0]))
+        .with(Attribute::get(3, CipDataType::Array, vec![]))
+        .with(Attribute::get(4, CipDataType::Word, vec![0x30, 0x00]))
+        .with(Attribute::get(5, CipDataType::Usint, vec![0xCA]))
+        .with(Attribute::get(6, CipDataType::Usint, vec![4]))
+        .with(Attribute::get(7, CipDataType::ShortString, short_string("Current")));
+    let acceleration = AttributeTable::new()
+        .with(Attribute::get(1, CipDataType::Dint, vec![10, 0, 0, 0]).settable())
+        .with(Attribute::get(2, CipDataType::Usint, vec![0]))
+        .with(Attribute::get(3, CipDataType::Array, vec![]))
+        .with(Attribute::get(4, CipDataType::Word, vec![0, 0]))
+        .with(Attribute::get(5, CipDataType::Usint, vec![0xC4]))
+        .with(Attribute::get(6, CipDataType::Usint, vec![4]));
+
+    AttributeObject::new(0x0F, 1).with_instance(1, speed).with_instance(2, current).with_instance(3, acceleration)
+}
+
+async fn connect() -> CipClient {
+    let mut router = Router::new();
+    router.register(parameter_object());
+
+    common::connect(router, "Drive").await
+}
+
+#[tokio::test]
+async fn client_reads_parameters() {
+    let mut client = connect().await;
+    assert_eq!(client.get_parameter_max_instance().await.unwrap(), 3);
+
+    let speed = client.get_parameter(1).await.unwrap();
+    assert_eq!(speed.value, CipValue::Uint(1500));
+    assert_eq!(speed.data_type, CipDataType::Uint);
+    assert_eq!(speed.link_path, vec![0x20, 0x04, 0x24, 0x96, 0x30, 0x03]);
+    assert_eq!(speed.name.as_deref(), Some("Speed"));
+    assert_eq!(speed.units.as_deref(), Some("rpm"));
+    assert_eq!(speed.maximum, Some(CipValue::Uint(6000)));
+    assert_eq!(speed.default, Some(CipValue::Uint(1000)));
+    assert_eq!(speed.scaling, Some(Scaling { multiplier: 1, divisor: 1, base: 1, offset: 0, decimal_precision: 1 }));
+    assert_eq!(speed.scaled_value(), Some(150.0));
+
+    let current = client.get_parameter(2).await.unwrap();
+    assert!(current.descriptor.read_only());
+    assert!(current.descriptor.monitor());
+    assert_eq!(current.scaling, None);
+
+    let acceleration = client.get_parameter(3).await.unwrap();
+    assert_eq!(acceleration.name, None);
+    assert_eq!(acceleration.minimum, None);
+}
+
+#[tokio::test]
+async fn backup_and_restore_round_trip() {
+    let mut client = connect().await;
+
+    let backup = client.backup_parameters().await.unwrap();
+    assert_eq!(backup.entries.len(), 3);
+    let text = backup.to_text();
+    assert_eq!(ParameterBackup::from_text(&text).unwrap(), backup);
+
+    client.set_parameter_value(1, &CipValue::Uint(42)).await.unwrap();
+    client.set_parameter_value(3, &CipValue::Dint(99)).await.unwrap();
+
+    let outcomes = client.restore_parameters(&ParameterBackup::from_text(&text).unwrap()).await.unwrap();
+    assert_eq!(outcomes, vec![(1, RestoreOutcome::Written), (2, RestoreOutcome::ReadOnly), (3, RestoreOutcome::Written)]);
+    assert_eq!(client.get_parameter(1).await.unwrap().value, CipValue::Uint(1500));
+    assert_eq!(client.get_parameter(3).await.unwrap().value, CipValue::Dint(10));
+}
+
+#[tokio::test]
+async fn restore_checks_types_before_writing() {
+    let mut client = connect().await;
+
+    let backup = ParameterBackup { entries: vec![ParameterBackupEntry { instance: 3, value: CipValue::Real(1.0), name: String::new() }, ParameterBackupEntry { instance: 7, value: CipValue::Uint(1), name: String::new() }] };
+    let outcomes = client.restore_parameters(&backup).await.unwrap();
+    assert_eq!(outcomes, vec![(3, RestoreOutcome::TypeMismatch { device: CipDataType::Dint }), (7, RestoreOutcome::Rejected { general_status: 0x05 })]);
+    assert_eq!(client.get_parameter(3).await.unwrap().value, CipValue::Dint(10));
+
+    assert!(matches!(client.set_parameter_value(2, &CipValue::Real(0.0)).await, Err(CipError::InvalidValue(_))));
+}
+
+#[test]
+fn backup_text_is_validated() {
+    let text = "# CIP parameter backup v1\n1\tC7\tDC05\tSpeed\n2\tCA\t00009040\tCurrent\n";
+    let backup = ParameterBackup::from_text(text).unwrap();
+    assert_eq!(backup.entries[1].value, CipValue::Real(4.5));
+    assert_eq!(backup.to_text(), text);
+
+    assert!(ParameterBackup::from_text("1\tC7\tDC05\tSpeed\n").is_err());
+    assert!(ParameterBackup::from_text("# CIP parameter backup v1\n1\tC7\tDC0500\tSpeed\n").is_err());
+    assert!(ParameterBackup::from_text("# CIP parameter backup v1\n1\tC7\tDC05\n").is_err());
+}
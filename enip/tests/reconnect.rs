@@ -0,0 +1,254 @@
+use std::{sync::{Arc, Mutex as StdMutex}, time::Duration};
+
+use cip::{cip::{CipClient, EPath, LogicalSegment, LogicalType}, common::Serializable, error::CipError, objects::{connection_manager::{ForwardOpenRequest, ForwardOpenResponse}, message_router::MessageRouterResponseStatusCodes}, router::{CipObject, ObjectPath, Router, ServiceError, ServiceResult}};
+use enip::{codec::EnipCodec, reconnect::{is_idempotent, ConnectionState, ReconnectingClient, ReopenResult, RetryPolicy}, server::{EnipServer, ServerConfig}};
+use tokio::{io::AsyncWriteExt, net::{TcpListener, TcpStream}, sync::{mpsc, Mutex, Notify}};
+
+mod common;
+
+fn header(command: u16, length: usize, sender_context: u64) -> Vec<u8> {
+    let mut vec = Vec::new();
+    vec.extend_from_slice(&command.to_le_bytes());
+    vec.extend_from_slice(&(length as u16).to_le_bytes());
+    vec.extend_from_slice(&7u32.to_le_bytes());
+    vec.extend_from_slice(&0u32.to_le_bytes());
+    vec.extend_from_slice(&sender_context.to_le_bytes());
+    vec.extend_from_slice(&0u32.to_le_bytes());
+    vec
+}
+
+async fn register(stream: &mut TcpStream, codec: &mut EnipCodec) {
+    codec.read_packet(stream).await.unwrap();
+    let mut reply = header(0x65, 4, 0);
+    reply.extend_from_slice(&[1, 0, 0, 0]);
+    stream.write_all(&reply).await.unwrap();
+}
+
+async fn reply(stream: &mut TcpStream, request: &[u8]) {
+    let sender_context = u64::from_le_bytes(request[12..20].try_into().unwrap());
+    let data = [0x8E, 0, 0, 0, 1];
+    let mut reply = header(0x6F, data.len() + 16, sender_context);
+    reply.extend_from_slice(&[0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0xB2, 0, data.len() as u8, 0]);
+    reply.extend_from_slice(&data);
+    stream.write_all(&reply).await.unwrap();
+}
+
+fn policy() -> RetryPolicy {
+    RetryPolicy { request_timeout: Duration::from_millis(200), initial_backoff: Duration::from_millis(10), ..RetryPolicy::default() }
+}
+
+#[test]
+fn only_read_services_are_idempotent() {
+    let get_attribute_single = [0x0E, 0x03, 0x20, 0x01, 0x24, 0x01, 0x30, 0x01];
+    let set_attribute_single = [0x10, 0x03, 0x20, 0x01, 0x24, 0x01, 0x30, 0x01, 0x05];
+    let mut unconnected_send = vec![0x52, 0x02, 0x20, 0x06, 0x24, 0x01, 0x03, 0xF0, 0x08, 0x00];
+    unconnected_send.extend_from_slice(&get_attribute_single);
+
+    assert!(is_idempotent(&get_attribute_single));
+    assert!(!is_idempotent(&set_attribute_single));
+    assert!(is_idempotent(&unconnected_send));
+}
+
+#[test]
+fn read_tag_codes_are_idempotent_only_for_tags() {
+    let read_tag = [0x4C, 0x03, 0x91, 0x03, b'T', b'a', b'g', 0x00, 0x01, 0x00];
+    let read_symbol_instance = [0x4C, 0x02, 0x20, 0x6B, 0x24, 0x01, 0x01, 0x00];
+    let get_and_clear = [0x4C, 0x03, 0x20, 0xF6, 0x24, 0x01, 0x30, 0x04];
+    let initiate_download = [0x4C, 0x02, 0x20, 0x37, 0x24, 0xC8, 0x10, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00];
+
+    assert!(is_idempotent(&read_tag));
+    assert!(is_idempotent(&read_symbol_instance));
+    assert!(!is_idempotent(&get_and_clear));
+    assert!(!is_idempotent(&initiate_download));
+}
+
+#[test]
+fn backoff_doubles_up_to_maximum() {
+    let policy = RetryPolicy { initial_backoff: Duration::from_millis(100), max_backoff: Duration::from_millis(500), ..RetryPolicy::default() };
+    assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff(2), Duration::from_millis(200));
+    assert_eq!(policy.backoff(3), Duration::from_millis(400));
+    assert_eq!(policy.backoff(4), Duration::from_millis(500));
+}
+
+#[tokio::test]
+async fn reconnects_and_retries_idempotent_request() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (accepted, mut accepts) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        // The first connection answers one request and then drops, like a rebooting PLC.
+        let (mut stream, _) = listener.accept().await.unwrap();
+        accepted.send(()).unwrap();
+        let mut codec = EnipCodec::new();
+        register(&mut stream, &mut codec).await;
+        let request = codec.read_packet(&mut stream).await.unwrap();
+        reply(&mut stream, &request).await;
+        drop(stream);
+
+        let (mut stream, _) = listener.accept().await.unwrap();
+        accepted.send(()).unwrap();
+        let mut codec = EnipCodec::new();
+        register(&mut stream, &mut codec).await;
+        while let Ok(request) = codec.read_packet(&mut stream).await {
+            reply(&mut stream, &request).await;
+        }
+    });
+
+    let reconnecting = ReconnectingClient::new(addr, policy());
+    let state = reconnecting.subscribe();
+    let mut client = CipClient::new(reconnecting);
+
+    client.connect().await.unwrap();
+    assert_eq!(*state.borrow(), ConnectionState::Connected);
+    assert_eq!(client.get_attribute_single(1, 1, 1).await.unwrap().general_status, 0);
+    assert_eq!(client.get_attribute_single(1, 1, 1).await.unwrap().general_status, 0);
+    assert_eq!(*state.borrow(), ConnectionState::Connected);
+
+    accepts.recv().await.unwrap();
+    accepts.recv().await.unwrap();
+
+    client.disconnect().await.unwrap();
+    assert_eq!(*state.borrow(), ConnectionState::Closed);
+}
+
+#[tokio::test]
+async fn times_out_without_resending_writes() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (received, mut requests) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut codec = EnipCodec::new();
+        register(&mut stream, &mut codec).await;
+        while let Ok(request) = codec.read_packet(&mut stream).await {
+            received.send(request).unwrap();
+        }
+    });
+
+    let mut client = CipClient::new(ReconnectingClient::new(addr, policy()));
+    client.connect().await.unwrap();
+
+    let result = client.set_attribute_single(1, 1, 1, vec![0x05]).await;
+    assert!(matches!(result, Err(CipError::Timeout)));
+    requests.recv().await.unwrap();
+    assert!(requests.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn times_out_without_resending_get_and_clear() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (received, mut requests) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut codec = EnipCodec::new();
+        register(&mut stream, &mut codec).await;
+        while let Ok(request) = codec.read_packet(&mut stream).await {
+            received.send(request).unwrap();
+        }
+    });
+
+    let mut client = CipClient::new(ReconnectingClient::new(addr, policy()));
+    client.connect().await.unwrap();
+
+    let result = client.get_and_clear_interface_counters(1).await;
+    assert!(matches!(result, Err(CipError::Timeout)));
+    requests.recv().await.unwrap();
+    assert!(requests.try_recv().is_err());
+}
+
+#[derive(Default)]
+struct Opens {
+    serial_numbers: Vec<u16>,
+    reject: Option<u16>
+}
+
+struct ConnectionManager(Arc<StdMutex<Opens>>);
+
+impl CipObject for ConnectionManager {
+    fn class_id(&self) -> u16 {
+        0x06
+    }
+
+    fn has_instance(&self, instance: u32) -> bool {
+        instance == 1
+    }
+
+    fn instance_service(&mut self, service: u8, _path: &ObjectPath, data: &[u8]) -> ServiceResult {
+        if service != 0x54 {
+            return Err(MessageRouterResponseStatusCodes::ServiceNotSupported.into());
+        }
+
+        let (_, request) = ForwardOpenRequest::deserialize(data).map_err(|_| ServiceError::from(MessageRouterResponseStatusCodes::NotEnoughData))?;
+        let mut opens = self.0.lock().unwrap();
+        if opens.reject == Some(request.connection_serial_number) {
+            return Err(ServiceError { general_status: 0x01, additional_status: vec![0x0100], data: Vec::new() });
+        }
+
+        opens.serial_numbers.push(request.connection_serial_number);
+        let ot_network_connection_id = 0x100 + opens.serial_numbers.len() as u32;
+        Ok(ForwardOpenResponse { ot_network_connection_id, to_network_connection_id: request.to_network_connection_id, connection_serial_number: request.connection_serial_number, original_vendor_id: request.original_vendor_id, original_serial_number: request.original_serial_number, ot_api: request.ot_rpi, to_api: request.to_rpi, application_reply: Vec::new() }.serialize())
+    }
+}
+
+fn forward_open(connection_serial_number: u16) -> ForwardOpenRequest {
+    let mut path = EPath::new();
+    path.attributes.push(Box::new(LogicalSegment::init(LogicalType::ClassId as u8, 0x04)));
+    path.attributes.push(Box::new(LogicalSegment::init(LogicalType::ConnectionPoint as u8, 0x64)));
+
+    let mut request = ForwardOpenRequest::create_null_forward_open(path);
+    request.connection_serial_number = connection_serial_number;
+    request.to_network_connection_id = connection_serial_number as u32;
+    request
+}
+
+#[tokio::test]
+async fn reopens_connections_and_reports_new_ids() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let opens = Arc::new(StdMutex::new(Opens::default()));
+    let kick = Arc::new(Notify::new());
+
+    let mut router = Router::new();
+    router.register(ConnectionManager(opens.clone()));
+    let server = EnipServer::new(Mutex::new(router), common::item("Gateway"), ServerConfig::default());
+    let kicked = kick.clone();
+    tokio::spawn(async move {
+        // One connection at a time, dropped whenever the test kicks it.
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::select! {
+                _ = server.serve_connection(stream) => {}
+                _ = kicked.notified() => {}
+            }
+        }
+    });
+
+    let reconnecting = ReconnectingClient::new(addr, policy());
+    let mut reopened = reconnecting.subscribe_reopened();
+    let mut client = CipClient::new(reconnecting);
+    client.connect().await.unwrap();
+    assert_eq!(client.forward_open(&forward_open(1)).await.unwrap().ot_network_connection_id, 0x101);
+    assert_eq!(client.forward_open(&forward_open(2)).await.unwrap().ot_network_connection_id, 0x102);
+
+    // Connection 2 is refused after the first reconnect; connection 1 gets a new ID.
+    opens.lock().unwrap().reject = Some(2);
+    kick.notify_one();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    client.connect().await.unwrap();
+
+    let results = reopened.borrow_and_update().clone();
+    assert_eq!(results.len(), 2);
+    assert!(matches!(&results[0], ReopenResult::Reopened(response) if response.connection_serial_number == 1 && response.ot_network_connection_id == 0x103));
+    assert_eq!(results[1], ReopenResult::Rejected { connection_serial_number: 2, general_status: 0x01, additional_status: vec![0x0100] });
+
+    // The rejected connection is not sent again.
+    kick.notify_one();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    client.connect().await.unwrap();
+    assert_eq!(reopened.borrow_and_update().len(), 1);
+    assert_eq!(opens.lock().unwrap().serial_numbers, [1, 2, 1, 1]);
+}
@@ -0,0 +1,84 @@
+use cip::{cip::Client, error::CipError};
+use enip::{codec::EnipCodec, session::EnipSession, tcp::TcpEnipClient};
+use tokio::{io::AsyncWriteExt, net::{TcpListener, TcpStream}};
+
+fn header(command: u16, length: usize, session_handle: u32, status: u32, sender_context: u64) -> Vec<u8> {
+    let mut vec = Vec::new();
+    vec.extend_from_slice(&command.to_le_bytes());
+    vec.extend_from_slice(&(length as u16).to_le_bytes());
+    vec.extend_from_slice(&session_handle.to_le_bytes());
+    vec.extend_from_slice(&status.to_le_bytes());
+    vec.extend_from_slice(&sender_context.to_le_bytes());
+    vec.extend_from_slice(&0u32.to_le_bytes());
+    vec
+}
+
+/// Answers RegisterSession with the given handle, status and version, then
+/// answers the first SendRRData from `reply_handle`.
+async fn device(listener: TcpListener, session_handle: u32, status: u32, version: u16, reply_handle: u32) {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut codec = EnipCodec::new();
+
+    let request = codec.read_packet(&mut stream).await.unwrap();
+    assert_eq!(request[24..28], [1, 0, 0, 0]);
+    let mut reply = header(0x65, 4, session_handle, status, 0);
+    reply.extend_from_slice(&version.to_le_bytes());
+    reply.extend_from_slice(&[0, 0]);
+    stream.write_all(&reply).await.unwrap();
+
+    if let Ok(request) = codec.read_packet(&mut stream).await {
+        let sender_context = u64::from_le_bytes(request[12..20].try_into().unwrap());
+        let mut reply = header(0x6F, 16, reply_handle, 0, sender_context);
+        reply.extend_from_slice(&[0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0xB2, 0, 0, 0]);
+        stream.write_all(&reply).await.unwrap();
+        let _ = codec.read_packet(&mut stream).await;
+    }
+}
+
+async fn listen() -> (TcpListener, std::net::SocketAddr) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    (listener, addr)
+}
+
+#[tokio::test]
+async fn tcp_client_registers_and_checks_session_handle() {
+    let (listener, addr) = listen().await;
+    tokio::spawn(device(listener, 0x99, 0, 1, 0x98));
+
+    let mut client = TcpEnipClient::new(TcpStream::connect(addr).await.unwrap());
+    client.begin_session().await.unwrap();
+    assert_eq!(client.session_handle, 0x99);
+
+    client.send_unconnected(vec![0x0E, 0]).await.unwrap();
+    let result = client.read_data().await;
+    assert!(matches!(result, Err(CipError::SessionMismatch { expected: 0x99, received: 0x98 })));
+}
+
+#[tokio::test]
+async fn unsupported_revision_reports_target_version() {
+    let (listener, addr) = listen().await;
+    tokio::spawn(device(listener, 0, 0x69, 2, 0));
+
+    let result = EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await;
+    assert!(matches!(result, Err(CipError::UnsupportedProtocolVersion(2))));
+}
+
+#[tokio::test]
+async fn zero_session_handle_is_rejected() {
+    let (listener, addr) = listen().await;
+    tokio::spawn(device(listener, 0, 0, 1, 0));
+
+    let result = EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await;
+    assert!(matches!(result, Err(CipError::InvalidReply)));
+}
+
+#[tokio::test]
+async fn session_rejects_reply_for_stray_session_handle() {
+    let (listener, addr) = listen().await;
+    tokio::spawn(device(listener, 0x10, 0, 1, 0x11));
+
+    let session = EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await.unwrap();
+    let result = session.send_rr_data(vec![0x0E, 0]).await;
+    assert!(matches!(result, Err(CipError::SessionMismatch { expected: 0x10, received: 0x11 })));
+}
@@ -0,0 +1,86 @@
+use cip::{cip::{CipClient, CipService}, objects::message_router::MessageRouterResponseStatusCodes, router::{CipObject, ObjectPath, Router, ServiceResult}};
+use enip::{cpf::{CipIdentityItem, CommonPacketHeader}, server::{EnipServer, ServerConfig}, session::EnipSession};
+use tokio::{net::{TcpListener, TcpStream}, sync::Mutex};
+
+/// Vendor specific class with one instance and one readable attribute.
+struct Counter {
+    value: u16
+}
+
+impl CipObject for Counter {
+    fn class_id(&self) -> u16 {
+        0x64
+    }
+
+    fn has_instance(&self, instance: u32) -> bool {
+        instance == 1
+    }
+
+    fn instance_service(&mut self, service: u8, path: &ObjectPath, _data: &[u8]) -> ServiceResult {
+        if service != CipService::GetAttributeSingle as u8 {
+            return Err(MessageRouterResponseStatusCodes::ServiceNotSupported.into());
+        }
+
+        match path.attribute {
+            Some(1) => {
+                self.value += 1;
+                Ok(self.value.to_le_bytes().to_vec())
+            }
+            _ => Err(MessageRouterResponseStatusCodes::AttributeNotSupported.into())
+        }
+    }
+}
+
+fn identity() -> CipIdentityItem {
+    CipIdentityItem { header: CommonPacketHeader { type_id: 0x0C, length: 0 }, encapsulation_version: 1, sin_family: 0, sin_port: 0, sin_addr: 0, sin_zero: [0; 8], vendor_id: 1, device_type: 0x0C, product_code: 1, major_revision: 1, minor_revision: 1, status: 0, serial_number: 1, product_name: "Router".into(), state: 3 }
+}
+
+async fn connect() -> CipClient {
+    let mut router = Router::new();
+    router.register(Counter { value: 0 });
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = EnipServer::new(Mutex::new(router), identity(), ServerConfig::default());
+    tokio::spawn(async move { server.serve(listener).await });
+
+    CipClient::new(EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await.unwrap())
+}
+
+#[tokio::test]
+async fn router_lists_registered_classes() {
+    let mut client = connect().await;
+    assert_eq!(client.get_supported_classes().await.unwrap(), vec![0x02, 0x64]);
+}
+
+#[tokio::test]
+async fn router_dispatches_through_unconnected_send() {
+    let mut client = connect().await;
+
+    let response = client.get_attribute_single(0x64, 1, 1).await.unwrap();
+    assert_eq!((response.service, response.general_status), (0x8E, 0));
+    assert_eq!(response.data, vec![1, 0]);
+    let response = client.get_attribute_single(0x64, 1, 1).await.unwrap();
+    assert_eq!(response.data, vec![2, 0]);
+}
+
+#[tokio::test]
+async fn router_reports_general_status() {
+    let mut client = connect().await;
+
+    assert_eq!(client.get_attribute_single(0x65, 1, 1).await.unwrap().general_status, 0x05);
+    assert_eq!(client.get_attribute_single(0x64, 2, 1).await.unwrap().general_status, 0x05);
+    assert_eq!(client.get_attribute_single(0x64, 1, 9).await.unwrap().general_status, 0x14);
+    assert_eq!(client.call_service(0x64, 1, 0x05, vec![]).await.unwrap().general_status, 0x08);
+    assert_eq!(client.call_service(0x64, 0, 0x0E, vec![]).await.unwrap().general_status, 0x08);
+}
+
+#[test]
+fn router_rejects_malformed_paths() {
+    let mut router = Router::new();
+
+    // Symbolic segment, which the router does not address objects by.
+    assert_eq!(router.handle(&[0x0E, 0x02, 0x91, 0x02, b'a', b'b']), vec![0x8E, 0, 0x04, 0]);
+    // 16-bit class and instance, Message Router attribute 1.
+    assert_eq!(router.handle(&[0x0E, 0x05, 0x21, 0x00, 0x02, 0x00, 0x25, 0x00, 0x01, 0x00, 0x30, 0x01]), vec![0x8E, 0, 0, 0, 1, 0, 2, 0]);
+}
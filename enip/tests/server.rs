@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use cip::error::{CipError, EncapsulationStatus};
+use enip::{codec::EnipCodec, cpf::{CipIdentityItem, CommonPacketHeader}, server::{EnipServer, MessageHandler, ServerConfig}, session::EnipSession, tcp::TcpEnipClient};
+use tokio::{io::AsyncWriteExt, net::{TcpListener, TcpStream}};
+
+struct Echo;
+
+#[async_trait::async_trait]
+impl MessageHandler for Echo {
+    async fn handle_unconnected(&self, request: Vec<u8>) -> Vec<u8> {
+        request
+    }
+}
+
+fn identity() -> CipIdentityItem {
+    CipIdentityItem { header: CommonPacketHeader { type_id: 0x0C, length: 0 }, encapsulation_version: 1, sin_family: 0, sin_port: 0, sin_addr: 0, sin_zero: [0; 8], vendor_id: 1, device_type: 0x0C, product_code: 42, major_revision: 2, minor_revision: 3, status: 0, serial_number: 0xDEADBEEF, product_name: "Test Adapter".into(), state: 3 }
+}
+
+async fn start(config: ServerConfig) -> (EnipServer, std::net::SocketAddr) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = EnipServer::new(Echo, identity(), config);
+    let serving = server.clone();
+    tokio::spawn(async move { serving.serve(listener).await });
+    (server, addr)
+}
+
+#[tokio::test]
+async fn server_answers_send_rr_data_through_handler() {
+    let (server, addr) = start(ServerConfig::default()).await;
+
+    let session = EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await.unwrap();
+    assert_ne!(session.session_handle(), 0);
+    let reply = session.send_rr_data(vec![0x0E, 0x02, 0x20, 0x01, 0x24, 0x01]).await.unwrap();
+    assert_eq!(reply.data, vec![0x0E, 0x02, 0x20, 0x01, 0x24, 0x01]);
+    assert_eq!(server.session_count().await, 1);
+
+    session.close().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(server.session_count().await, 0);
+}
+
+#[tokio::test]
+async fn server_lists_identity_with_local_address() {
+    let (_server, addr) = start(ServerConfig::default()).await;
+
+    let mut client = TcpEnipClient::new(TcpStream::connect(addr).await.unwrap());
+    let identities = client.list_identity().await.unwrap();
+    assert_eq!(identities.len(), 1);
+    assert_eq!(identities[0].product_name, "Test Adapter");
+    assert_eq!(identities[0].serial_number, 0xDEADBEEF);
+    assert_eq!(identities[0].socket_addr().port(), addr.port());
+
+    let services = client.list_services().await.unwrap();
+    assert!(services[0].supports_cip_tcp());
+}
+
+#[tokio::test]
+async fn server_refuses_sessions_over_limit() {
+    let (_server, addr) = start(ServerConfig { max_sessions: 1, ..Default::default() }).await;
+
+    let _first = EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await.unwrap();
+    let second = EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await;
+    assert!(matches!(second, Err(CipError::Encapsulation(EncapsulationStatus::InsufficientMemory))));
+}
+
+#[tokio::test]
+async fn server_rejects_unknown_session_and_command() {
+    let (_server, addr) = start(ServerConfig::default()).await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let mut codec = EnipCodec::new();
+
+    // SendRRData without a registered session.
+    let mut request = vec![0x6F, 0, 16, 0, 7, 0, 0, 0, 0, 0, 0, 0, 9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    request.extend_from_slice(&[0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0xB2, 0, 0, 0]);
+    stream.write_all(&request).await.unwrap();
+    let reply = codec.read_packet(&mut stream).await.unwrap();
+    assert_eq!(u32::from_le_bytes(reply[8..12].try_into().unwrap()), 0x64);
+    assert_eq!(reply[12], 9);
+
+    let request = vec![0x55, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    stream.write_all(&request).await.unwrap();
+    let reply = codec.read_packet(&mut stream).await.unwrap();
+    assert_eq!(u32::from_le_bytes(reply[8..12].try_into().unwrap()), 0x01);
+}
+
+#[tokio::test]
+async fn server_closes_idle_connections() {
+    let (server, addr) = start(ServerConfig { inactivity_timeout: Duration::from_millis(100), ..Default::default() }).await;
+
+    let session = EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(session.is_closed());
+    assert_eq!(server.session_count().await, 0);
+}
@@ -0,0 +1,123 @@
+use cip::error::{CipError, EncapsulationStatus};
+use enip::{codec::EnipCodec, session::EnipSession};
+use tokio::{io::AsyncWriteExt, net::{TcpListener, TcpStream}};
+
+const SESSION_HANDLE: u32 = 0x1234;
+
+fn header(command: u16, length: usize, sender_context: u64) -> Vec<u8> {
+    let mut vec = Vec::new();
+    vec.extend_from_slice(&command.to_le_bytes());
+    vec.extend_from_slice(&(length as u16).to_le_bytes());
+    vec.extend_from_slice(&SESSION_HANDLE.to_le_bytes());
+    vec.extend_from_slice(&0u32.to_le_bytes());
+    vec.extend_from_slice(&sender_context.to_le_bytes());
+    vec.extend_from_slice(&0u32.to_le_bytes());
+    vec
+}
+
+fn rr_data_reply(sender_context: u64, data: &[u8]) -> Vec<u8> {
+    let mut vec = header(0x6F, data.len() + 16, sender_context);
+    vec.extend_from_slice(&[0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0xB2, 0]);
+    vec.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    vec.extend_from_slice(data);
+    vec
+}
+
+/// Registers the session, then answers `count` requests in reverse order.
+async fn fake_device(listener: TcpListener, count: usize) {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut codec = EnipCodec::new();
+
+    let register = codec.read_packet(&mut stream).await.unwrap();
+    assert_eq!(u16::from_le_bytes([register[0], register[1]]), 0x65);
+    let mut reply = header(0x65, 4, 0);
+    reply.extend_from_slice(&[1, 0, 0, 0]);
+    stream.write_all(&reply).await.unwrap();
+
+    let mut requests = Vec::new();
+    for _ in 0..count {
+        let request = codec.read_packet(&mut stream).await.unwrap();
+        let sender_context = u64::from_le_bytes(request[12..20].try_into().unwrap());
+        requests.push((sender_context, request[40..].to_vec()));
+    }
+
+    let mut replies = Vec::new();
+    for (sender_context, data) in requests.iter().rev() {
+        replies.extend(rr_data_reply(*sender_context, data));
+    }
+    stream.write_all(&replies).await.unwrap();
+    let _ = codec.read_packet(&mut stream).await;
+}
+
+#[tokio::test]
+async fn session_routes_pipelined_replies_by_sender_context() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let device = tokio::spawn(fake_device(listener, 8));
+
+    let session = EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await.unwrap();
+    assert_eq!(session.session_handle(), SESSION_HANDLE);
+
+    let mut tasks = Vec::new();
+    for i in 0..8u8 {
+        let session = session.clone();
+        tasks.push(tokio::spawn(async move {
+            let reply = session.send_rr_data(vec![i, i, i, i]).await.unwrap();
+            assert_eq!(reply.data, vec![i, i, i, i]);
+        }));
+    }
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    session.close().await.unwrap();
+    device.await.unwrap();
+}
+
+#[tokio::test]
+async fn session_fails_pending_requests_when_connection_drops() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let device = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut codec = EnipCodec::new();
+        codec.read_packet(&mut stream).await.unwrap();
+        let mut reply = header(0x65, 4, 0);
+        reply.extend_from_slice(&[1, 0, 0, 0]);
+        stream.write_all(&reply).await.unwrap();
+        codec.read_packet(&mut stream).await.unwrap();
+    });
+
+    let session = EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await.unwrap();
+    assert!(session.send_rr_data(vec![1, 2]).await.is_err());
+    assert!(session.is_closed());
+    device.await.unwrap();
+}
+
+#[tokio::test]
+async fn session_reports_encapsulation_status_as_error() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let device = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut codec = EnipCodec::new();
+        codec.read_packet(&mut stream).await.unwrap();
+        let mut reply = header(0x65, 4, 0);
+        reply.extend_from_slice(&[1, 0, 0, 0]);
+        stream.write_all(&reply).await.unwrap();
+
+        let request = codec.read_packet(&mut stream).await.unwrap();
+        let mut reply = header(0x6F, 0, u64::from_le_bytes(request[12..20].try_into().unwrap()));
+        reply[8..12].copy_from_slice(&0x0003u32.to_le_bytes());
+        stream.write_all(&reply).await.unwrap();
+        let _ = codec.read_packet(&mut stream).await;
+    });
+
+    let session = EnipSession::connect(TcpStream::connect(addr).await.unwrap()).await.unwrap();
+    let result = session.send_rr_data(vec![1, 2]).await;
+    assert!(matches!(result, Err(CipError::Encapsulation(EncapsulationStatus::IncorrectData))));
+
+    session.close().await.unwrap();
+    device.await.unwrap();
+}
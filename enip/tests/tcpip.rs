@@ -0,0 +1,89 @@
+use std::net::Ipv4Addr;
+
+use cip::{attributes::{Attribute, AttributeObject, AttributeTable}, cip::{CipClient, CipDataType}, error::CipError, objects::tcpip::{InterfaceConfiguration, StartupConfiguration, TcpIpChange, TcpIpSetting}, router::Router};
+
+mod common;
+
+/// A TCP/IP Interface with static addressing and no optional attributes but the inactivity timeout.
+fn tcpip_object() -> AttributeObject {
+    let mut configuration = Vec::new();
+    for address in [0xC0A8_0114u32, 0xFFFF_FF00, 0xC0A8_0101, 0, 0] {
+        configuration.extend_from_slice(&address.to_le_bytes());
+    }
+    configuration.extend_from_slice(&[3, 0, b'l', b'a', b'n', 0]);
+
+    let table = AttributeTable::new()
+        .with(Attribute::get(1, CipDataType::Dword, vec![0x21, 0, 0, 0]))
+        .with(Attribute::get(2, CipDataType::Dword, vec![0x94, 0, 0, 0]))
+        .with(Attribute::get(3, CipDataType::Dword, vec![0x00, 0, 0, 0]).settable())
+        .with(Attribute::get(4, CipDataType::Struct, vec![2, 0, 0x20, 0xF6, 0x24, 0x01]))
+        .with(Attribute::get(5, CipDataType::Struct, configuration).settable())
+        .with(Attribute::get(6, CipDataType::Struct, vec![4, 0, b'p', b'l', b'c', b'1']).settable())
+        .with(Attribute::get(13, CipDataType::Uint, vec![120, 0]).settable());
+
+    AttributeObject::new(0xF5, 4).with_instance(1, table)
+}
+
+async fn connect() -> CipClient {
+    let mut router = Router::new();
+    router.register(tcpip_object());
+
+    common::connect(router, "Adapter").await
+}
+
+#[tokio::test]
+async fn client_reads_tcpip_interface() {
+    let mut client = connect().await;
+    let interface = client.get_tcpip_interface(1).await.unwrap();
+
+    assert_eq!(interface.status.configuration_status(), 1);
+    assert!(interface.status.interface_configuration_pending());
+    assert!(interface.configuration_capability.dhcp_client());
+    assert!(interface.configuration_capability.configuration_settable());
+    assert!(interface.configuration_capability.acd_capable());
+    assert_eq!(interface.configuration_control.startup, StartupConfiguration::Static);
+    assert_eq!(interface.physical_link_path, vec![0x20, 0xF6, 0x24, 0x01]);
+    assert_eq!(interface.physical_link_epath().unwrap().attributes.len(), 2);
+    assert_eq!(interface.interface_configuration.ip_address, Ipv4Addr::new(192, 168, 1, 20));
+    assert_eq!(interface.interface_configuration.gateway_address, Ipv4Addr::new(192, 168, 1, 1));
+    assert_eq!(interface.interface_configuration.domain_name, "lan");
+    assert_eq!(interface.host_name, "plc1");
+    assert_eq!(interface.time_to_live, None);
+    assert_eq!(interface.encapsulation_inactivity_timeout, Some(120));
+}
+
+#[tokio::test]
+async fn tcpip_changes_need_confirmation() {
+    let mut client = connect().await;
+
+    let change = TcpIpChange::new(TcpIpSetting::EncapsulationInactivityTimeout(30)).unwrap();
+    assert!(matches!(client.set_tcpip_attribute(1, &change).await, Err(CipError::NotConfirmed)));
+    assert_eq!(client.get_tcpip_interface(1).await.unwrap().encapsulation_inactivity_timeout, Some(120));
+
+    client.set_tcpip_attribute(1, &change.confirm()).await.unwrap();
+    assert_eq!(client.get_tcpip_interface(1).await.unwrap().encapsulation_inactivity_timeout, Some(30));
+
+    let change = TcpIpChange::new(TcpIpSetting::HostName("plc-2".into())).unwrap().confirm();
+    client.set_tcpip_attribute(1, &change).await.unwrap();
+    assert_eq!(client.get_tcpip_interface(1).await.unwrap().host_name, "plc-2");
+}
+
+#[test]
+fn tcpip_settings_are_validated() {
+    let configuration = InterfaceConfiguration { ip_address: Ipv4Addr::new(10, 0, 0, 5), network_mask: Ipv4Addr::new(255, 255, 255, 0), gateway_address: Ipv4Addr::new(10, 0, 0, 1), name_server: Ipv4Addr::UNSPECIFIED, name_server_2: Ipv4Addr::UNSPECIFIED, domain_name: String::new() };
+    assert!(TcpIpChange::new(TcpIpSetting::InterfaceConfiguration(configuration.clone())).is_ok());
+
+    let invalid = [
+        InterfaceConfiguration { network_mask: Ipv4Addr::new(255, 0, 255, 0), ..configuration.clone() },
+        InterfaceConfiguration { gateway_address: Ipv4Addr::new(10, 0, 1, 1), ..configuration.clone() },
+        InterfaceConfiguration { ip_address: Ipv4Addr::new(10, 0, 0, 255), ..configuration.clone() },
+        InterfaceConfiguration { ip_address: Ipv4Addr::new(224, 0, 0, 5), ..configuration.clone() }
+    ];
+    for configuration in invalid {
+        assert!(matches!(TcpIpChange::new(TcpIpSetting::InterfaceConfiguration(configuration)), Err(CipError::InvalidValue(_))));
+    }
+
+    assert!(TcpIpChange::new(TcpIpSetting::HostName("bad name".into())).is_err());
+    assert!(TcpIpChange::new(TcpIpSetting::TimeToLive(0)).is_err());
+    assert!(TcpIpChange::new(TcpIpSetting::EncapsulationInactivityTimeout(3601)).is_err());
+}
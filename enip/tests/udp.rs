@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use cip::{cip::CipClient, error::CipError};
+use enip::udp::UdpENIPClient;
+use tokio::net::UdpSocket;
+
+fn rr_data_reply(sender_context: u64, data: &[u8]) -> Vec<u8> {
+    let mut vec = Vec::new();
+    vec.extend_from_slice(&0x6Fu16.to_le_bytes());
+    vec.extend_from_slice(&((data.len() + 16) as u16).to_le_bytes());
+    vec.extend_from_slice(&[0; 8]);
+    vec.extend_from_slice(&sender_context.to_le_bytes());
+    vec.extend_from_slice(&[0; 4]);
+    vec.extend_from_slice(&[0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0xB2, 0]);
+    vec.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    vec.extend_from_slice(data);
+    vec
+}
+
+/// Answers every SendRRData, preceded by a stale reply with the wrong sender context.
+async fn responder(socket: UdpSocket) {
+    let mut data = vec![0u8; 2048];
+    loop {
+        let (_, from) = socket.recv_from(&mut data).await.unwrap();
+        let sender_context = u64::from_le_bytes(data[12..20].try_into().unwrap());
+        socket.send_to(&rr_data_reply(sender_context + 100, &[0x8E, 0, 0x08, 0]), from).await.unwrap();
+        let mut reply = vec![0x8E, 0, 0, 0];
+        reply.extend(vec![0x5A; 1000]);
+        socket.send_to(&rr_data_reply(sender_context, &reply), from).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn unconnected_request_over_udp() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let addr = socket.local_addr().unwrap();
+    tokio::spawn(responder(socket));
+
+    let mut udp = UdpENIPClient::connect(addr).await.unwrap();
+    udp.set_timeout(Some(Duration::from_millis(500)));
+    let mut client = CipClient::new(udp);
+
+    let response = client.get_attribute_single(1, 1, 7).await.unwrap();
+    assert_eq!(response.general_status, 0);
+    assert_eq!(response.data.len(), 1000);
+}
+
+#[tokio::test]
+async fn one_shot_request_reports_sender() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let addr = socket.local_addr().unwrap();
+    tokio::spawn(responder(socket));
+
+    let mut udp = UdpENIPClient::bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+    udp.set_timeout(Some(Duration::from_millis(500)));
+
+    let (from, reply) = udp.send_rr_data_to(addr, vec![0x0E, 0x02, 0x20, 0x01, 0x24, 0x01]).await.unwrap();
+    assert_eq!(from, addr);
+    assert_eq!(reply.data[2], 0);
+}
+
+#[tokio::test]
+async fn runt_datagram_does_not_end_the_wait() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let addr = socket.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut data = vec![0u8; 2048];
+        let (_, from) = socket.recv_from(&mut data).await.unwrap();
+        let sender_context = u64::from_le_bytes(data[12..20].try_into().unwrap());
+        socket.send_to(&[0x63, 0x00, 0x00], from).await.unwrap();
+        socket.send_to(&rr_data_reply(sender_context, &[0x8E, 0, 0, 0, 1]), from).await.unwrap();
+    });
+
+    let mut udp = UdpENIPClient::connect(addr).await.unwrap();
+    udp.set_timeout(Some(Duration::from_millis(500)));
+    let mut client = CipClient::new(udp);
+
+    let response = client.get_attribute_single(1, 1, 7).await.unwrap();
+    assert_eq!(response.data, [1]);
+}
+
+#[tokio::test]
+async fn silent_target_times_out() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let addr = socket.local_addr().unwrap();
+
+    let mut udp = UdpENIPClient::connect(addr).await.unwrap();
+    udp.set_timeout(Some(Duration::from_millis(100)));
+    let mut client = CipClient::new(udp);
+
+    assert!(matches!(client.get_attribute_single(1, 1, 7).await, Err(CipError::Timeout)));
+    drop(socket);
+}